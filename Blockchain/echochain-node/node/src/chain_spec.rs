@@ -1,6 +1,6 @@
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GrandpaConfig, RuntimeGenesisConfig, Signature,
-	SudoConfig, SystemConfig, WASM_BINARY,
+	SudoConfig, SystemConfig, WorkerKeysConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -51,7 +51,9 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				// Initial PoA authorities
 				vec![authority_keys_from_seed("Alice")],
 				// Sudo account
-				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				Some(get_account_id_from_seed::<sr25519::Public>("Alice")),
+				// Offchain-worker keys
+				vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
 				// Pre-funded accounts
 				vec![
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -91,7 +93,12 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				// Initial PoA authorities
 				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
 				// Sudo account
-				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				Some(get_account_id_from_seed::<sr25519::Public>("Alice")),
+				// Offchain-worker keys
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+				],
 				// Pre-funded accounts
 				vec![
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -124,11 +131,59 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
+/// Public testnet without a sudo key: Root is only reachable through the curator
+/// collective and `pallet_whitelist`, as on a chain migrated by `DisableSudo`.
+pub fn public_testnet_config() -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"EchoChain Testnet",
+		// ID
+		"echochain_testnet",
+		ChainType::Live,
+		move || {
+			testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
+				// Sudo account
+				None,
+				// Offchain-worker keys
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+				],
+				// Pre-funded accounts
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+					get_account_id_from_seed::<sr25519::Public>("Dave"),
+				],
+				true,
+			)
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		Some("echo-testnet"),
+		None,
+		// Properties
+		None,
+		// Extensions
+		None,
+	))
+}
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
-	root_key: AccountId,
+	root_key: Option<AccountId>,
+	initial_workers: Vec<AccountId>,
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
 ) -> RuntimeGenesisConfig {
@@ -150,9 +205,17 @@ fn testnet_genesis(
 			..Default::default()
 		},
 		sudo: SudoConfig {
-			// Assign network admin rights.
-			key: Some(root_key),
+			// Assign network admin rights, if any.
+			key: root_key,
 		},
 		transaction_payment: Default::default(),
+		// Curators are elected, or seeded through sudo, after launch.
+		curator_collective: Default::default(),
+		curator_elections: Default::default(),
+		treasury: Default::default(),
+		worker_keys: WorkerKeysConfig {
+			// Accounts of the `echo` keys dev nodes insert from their dev seed.
+			workers: initial_workers,
+		},
 	}
 }
@@ -157,6 +157,17 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 		other: (block_import, grandpa_link, mut telemetry),
 	} = new_partial(&config)?;
 
+	if let Some(seed) = config.dev_key_seed.as_deref() {
+		// Dev nodes sign offchain-worker payloads with the `echo` key of their dev account,
+		// which the dev chain specs authorize at genesis. Other nodes use `key insert`.
+		sp_keystore::Keystore::sr25519_generate_new(
+			&*keystore_container.keystore(),
+			node_template_runtime::pallet_worker_keys::KEY_TYPE,
+			Some(seed),
+		)
+		.map_err(|e| ServiceError::Other(format!("Failed to insert worker key: {e}")))?;
+	}
+
 	let mut net_config = sc_network::config::FullNetworkConfiguration::new(&config.network);
 
 	let grandpa_protocol_name = sc_consensus_grandpa::protocol_standard_name(
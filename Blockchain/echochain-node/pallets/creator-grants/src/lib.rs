@@ -0,0 +1,331 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Creator Grants Pallet
+//!
+//! Grant track of the creator fund. Artists apply for a grant split into milestones, with a
+//! deposit against spam. Once the approving origin (the curator collective in the runtime)
+//! accepts the application, its total is moved from the fund account, i.e. the treasury, to an
+//! escrow account of the grant, together with the existential deposit keeping the escrow alive.
+//! The artist then submits evidence for each milestone in turn and every approved milestone is
+//! paid out of the escrow, whatever the fund holds by then. The existential deposit returns to
+//! the fund with the last milestone.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Currency, ExistenceRequirement, OnUnbalanced, ReservableCurrency},
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{AccountIdConversion, Saturating};
+	use sp_std::prelude::*;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+
+	/// Review state of a grant.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum GrantStatus {
+		/// Waiting for approval, with the deposit reserved.
+		Proposed,
+		/// Approved, milestones are paid as they are completed.
+		Active,
+	}
+
+	/// A grant application and the progress of its milestones.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(MaxMilestones, MaxCidLength))]
+	#[codec(mel_bound(AccountId: MaxEncodedLen, Balance: MaxEncodedLen))]
+	pub struct Grant<AccountId, Balance, MaxMilestones: Get<u32>, MaxCidLength: Get<u32>> {
+		pub creator: AccountId,
+		/// IPFS CID of the application describing the project and its milestones.
+		pub description: BoundedVec<u8, MaxCidLength>,
+		/// Payout of each milestone, in order.
+		pub milestones: BoundedVec<Balance, MaxMilestones>,
+		/// Number of milestones paid so far.
+		pub paid: u32,
+		/// Deposit reserved from the creator until the application is reviewed.
+		pub deposit: Balance,
+		pub status: GrantStatus,
+		/// IPFS CID of the evidence submitted for the next milestone, if any.
+		pub evidence: Option<BoundedVec<u8, MaxCidLength>>,
+	}
+
+	pub type GrantOf<T> = Grant<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as Config>::MaxMilestones,
+		<T as Config>::MaxCidLength,
+	>;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency mechanism.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Origin approving applications and milestones, e.g. a curator collective majority.
+		type ApproveOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Account grants are funded from when they are approved.
+		#[pallet::constant]
+		type FundAccount: Get<Self::AccountId>;
+
+		/// Pallet ID the escrow accounts of approved grants are derived from.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// Deposit reserved when applying for a grant, slashed if the application is rejected.
+		#[pallet::constant]
+		type ApplicationDeposit: Get<BalanceOf<Self>>;
+
+		/// Receives the deposits of rejected applications.
+		type OnSlash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// Maximum number of milestones of a grant.
+		#[pallet::constant]
+		type MaxMilestones: Get<u32>;
+
+		/// Maximum length of an IPFS CID.
+		#[pallet::constant]
+		type MaxCidLength: Get<u32>;
+	}
+
+	/// Open grants, by grant ID.
+	#[pallet::storage]
+	#[pallet::getter(fn grants)]
+	pub type Grants<T: Config> = StorageMap<_, Twox64Concat, u32, GrantOf<T>, OptionQuery>;
+
+	/// ID of the next grant application.
+	#[pallet::storage]
+	#[pallet::getter(fn next_grant_id)]
+	pub type NextGrantId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A creator applied for a grant.
+		GrantProposed { grant_id: u32, creator: T::AccountId, total: BalanceOf<T> },
+		/// A grant application was approved.
+		GrantApproved { grant_id: u32 },
+		/// A grant application was rejected and its deposit slashed.
+		GrantRejected { grant_id: u32, slashed: BalanceOf<T> },
+		/// Evidence was submitted for the next milestone of a grant.
+		MilestoneSubmitted { grant_id: u32, milestone: u32 },
+		/// A milestone was approved and paid.
+		MilestonePaid { grant_id: u32, milestone: u32, amount: BalanceOf<T> },
+		/// All milestones of a grant were paid.
+		GrantCompleted { grant_id: u32 },
+		/// A grant was cancelled before all its milestones were paid.
+		GrantCancelled { grant_id: u32 },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The grant does not exist.
+		UnknownGrant,
+		/// A grant needs at least one milestone.
+		NoMilestones,
+		/// Too many milestones, or an IPFS CID that is too long.
+		TooLong,
+		/// The creator cannot afford the application deposit.
+		InsufficientDeposit,
+		/// The grant was already approved.
+		GrantNotProposed,
+		/// The grant has not been approved yet.
+		GrantNotActive,
+		/// Only the creator of the grant can submit milestones.
+		NotCreator,
+		/// No evidence was submitted for the next milestone.
+		MilestoneNotSubmitted,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Apply for a grant paying `milestones` in order, reserving `ApplicationDeposit`.
+		#[pallet::call_index(0)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 3).ref_time())]
+		pub fn propose_grant(
+			origin: OriginFor<T>,
+			description: Vec<u8>,
+			milestones: Vec<BalanceOf<T>>,
+		) -> DispatchResult {
+			let creator = ensure_signed(origin)?;
+			ensure!(!milestones.is_empty(), Error::<T>::NoMilestones);
+			let description: BoundedVec<_, _> = description.try_into().map_err(|_| Error::<T>::TooLong)?;
+			let milestones: BoundedVec<_, _> = milestones.try_into().map_err(|_| Error::<T>::TooLong)?;
+
+			let deposit = T::ApplicationDeposit::get();
+			T::Currency::reserve(&creator, deposit).map_err(|_| Error::<T>::InsufficientDeposit)?;
+
+			let total = Self::sum(&milestones);
+			let grant_id = NextGrantId::<T>::get();
+			Grants::<T>::insert(
+				grant_id,
+				Grant {
+					creator: creator.clone(),
+					description,
+					milestones,
+					paid: 0,
+					deposit,
+					status: GrantStatus::Proposed,
+					evidence: None,
+				},
+			);
+			NextGrantId::<T>::put(grant_id.saturating_add(1));
+
+			Self::deposit_event(Event::GrantProposed { grant_id, creator, total });
+			Ok(())
+		}
+
+		/// Approve a grant application, returning its deposit and moving the total of its
+		/// milestones plus the existential deposit from the fund account to the grant's escrow
+		/// account. Fails if the fund cannot cover the grant.
+		#[pallet::call_index(1)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 4).ref_time())]
+		pub fn approve_grant(origin: OriginFor<T>, grant_id: u32) -> DispatchResult {
+			T::ApproveOrigin::ensure_origin(origin)?;
+			Grants::<T>::try_mutate(grant_id, |grant| -> DispatchResult {
+				let grant = grant.as_mut().ok_or(Error::<T>::UnknownGrant)?;
+				ensure!(grant.status == GrantStatus::Proposed, Error::<T>::GrantNotProposed);
+				T::Currency::transfer(
+					&T::FundAccount::get(),
+					&Self::grant_account(grant_id),
+					Self::sum(&grant.milestones).saturating_add(T::Currency::minimum_balance()),
+					ExistenceRequirement::KeepAlive,
+				)?;
+				T::Currency::unreserve(&grant.creator, grant.deposit);
+				grant.status = GrantStatus::Active;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::GrantApproved { grant_id });
+			Ok(())
+		}
+
+		/// Reject a grant application, slashing its deposit.
+		#[pallet::call_index(2)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2).ref_time())]
+		pub fn reject_grant(origin: OriginFor<T>, grant_id: u32) -> DispatchResult {
+			T::ApproveOrigin::ensure_origin(origin)?;
+			let grant = Grants::<T>::get(grant_id).ok_or(Error::<T>::UnknownGrant)?;
+			ensure!(grant.status == GrantStatus::Proposed, Error::<T>::GrantNotProposed);
+
+			let (imbalance, _) = T::Currency::slash_reserved(&grant.creator, grant.deposit);
+			T::OnSlash::on_unbalanced(imbalance);
+			Grants::<T>::remove(grant_id);
+
+			Self::deposit_event(Event::GrantRejected { grant_id, slashed: grant.deposit });
+			Ok(())
+		}
+
+		/// Submit evidence that the next milestone of an approved grant is completed.
+		/// Replaces any evidence already submitted for it.
+		#[pallet::call_index(3)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn submit_milestone(origin: OriginFor<T>, grant_id: u32, evidence: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let evidence: BoundedVec<_, _> = evidence.try_into().map_err(|_| Error::<T>::TooLong)?;
+			let milestone = Grants::<T>::try_mutate(grant_id, |grant| -> Result<u32, DispatchError> {
+				let grant = grant.as_mut().ok_or(Error::<T>::UnknownGrant)?;
+				ensure!(grant.creator == who, Error::<T>::NotCreator);
+				ensure!(grant.status == GrantStatus::Active, Error::<T>::GrantNotActive);
+				grant.evidence = Some(evidence);
+				Ok(grant.paid)
+			})?;
+
+			Self::deposit_event(Event::MilestoneSubmitted { grant_id, milestone });
+			Ok(())
+		}
+
+		/// Approve the submitted milestone of a grant and pay it from the grant's escrow account.
+		/// The grant is closed once its last milestone is paid, returning the existential
+		/// deposit left in the escrow to the fund account.
+		#[pallet::call_index(4)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 4).ref_time())]
+		pub fn approve_milestone(origin: OriginFor<T>, grant_id: u32) -> DispatchResult {
+			T::ApproveOrigin::ensure_origin(origin)?;
+			let mut grant = Grants::<T>::get(grant_id).ok_or(Error::<T>::UnknownGrant)?;
+			ensure!(grant.status == GrantStatus::Active, Error::<T>::GrantNotActive);
+			ensure!(grant.evidence.is_some(), Error::<T>::MilestoneNotSubmitted);
+
+			let milestone = grant.paid;
+			let amount = grant.milestones[milestone as usize];
+			let escrow = Self::grant_account(grant_id);
+			// The escrow holds the existential deposit on top of the unpaid milestones
+			T::Currency::transfer(&escrow, &grant.creator, amount, ExistenceRequirement::KeepAlive)?;
+			grant.paid += 1;
+			grant.evidence = None;
+			Self::deposit_event(Event::MilestonePaid { grant_id, milestone, amount });
+
+			if grant.paid as usize == grant.milestones.len() {
+				T::Currency::transfer(
+					&escrow,
+					&T::FundAccount::get(),
+					T::Currency::free_balance(&escrow),
+					ExistenceRequirement::AllowDeath,
+				)?;
+				Grants::<T>::remove(grant_id);
+				Self::deposit_event(Event::GrantCompleted { grant_id });
+			} else {
+				Grants::<T>::insert(grant_id, grant);
+			}
+			Ok(())
+		}
+
+		/// Cancel a grant, e.g. one whose creator stopped delivering. Milestones already paid
+		/// are kept, the unpaid ones are returned from the escrow to the fund account, and the
+		/// deposit of an application not yet reviewed is returned.
+		#[pallet::call_index(5)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3).ref_time())]
+		pub fn cancel_grant(origin: OriginFor<T>, grant_id: u32) -> DispatchResult {
+			T::ApproveOrigin::ensure_origin(origin)?;
+			let grant = Grants::<T>::take(grant_id).ok_or(Error::<T>::UnknownGrant)?;
+			match grant.status {
+				GrantStatus::Proposed => {
+					T::Currency::unreserve(&grant.creator, grant.deposit);
+				},
+				GrantStatus::Active => {
+					let escrow = Self::grant_account(grant_id);
+					T::Currency::transfer(
+						&escrow,
+						&T::FundAccount::get(),
+						T::Currency::free_balance(&escrow),
+						ExistenceRequirement::AllowDeath,
+					)?;
+				},
+			}
+
+			Self::deposit_event(Event::GrantCancelled { grant_id });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Escrow account holding the unpaid milestones of an approved grant.
+		pub fn grant_account(grant_id: u32) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(grant_id)
+		}
+
+		fn sum(milestones: &[BalanceOf<T>]) -> BalanceOf<T> {
+			milestones.iter().fold(BalanceOf::<T>::default(), |total, amount| total.saturating_add(*amount))
+		}
+	}
+}
@@ -0,0 +1,165 @@
+use crate::{mock::*, Error, Event, GrantStatus};
+use frame_support::{assert_noop, assert_ok, traits::Currency};
+use sp_runtime::traits::BadOrigin;
+
+fn propose(milestones: Vec<u128>) -> u32 {
+	let grant_id = CreatorGrants::next_grant_id();
+	assert_ok!(CreatorGrants::propose_grant(RuntimeOrigin::signed(CREATOR), b"QmGrant".to_vec(), milestones));
+	grant_id
+}
+
+#[test]
+fn applications_reserve_a_deposit_until_reviewed() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			CreatorGrants::propose_grant(RuntimeOrigin::signed(CREATOR), b"QmGrant".to_vec(), vec![]),
+			Error::<Test>::NoMilestones
+		);
+		assert_noop!(
+			CreatorGrants::propose_grant(RuntimeOrigin::signed(CREATOR), b"QmGrant".to_vec(), vec![1; 4]),
+			Error::<Test>::TooLong
+		);
+		assert_noop!(
+			CreatorGrants::propose_grant(RuntimeOrigin::signed(2), b"QmGrant".to_vec(), vec![1]),
+			Error::<Test>::InsufficientDeposit
+		);
+
+		let approved = propose(vec![100, 200]);
+		System::assert_last_event(Event::GrantProposed { grant_id: approved, creator: CREATOR, total: 300 }.into());
+		assert_eq!(Balances::reserved_balance(CREATOR), 10);
+
+		assert_noop!(CreatorGrants::approve_grant(RuntimeOrigin::signed(CREATOR), approved), BadOrigin);
+		assert_ok!(CreatorGrants::approve_grant(RuntimeOrigin::root(), approved));
+		assert_eq!(CreatorGrants::grants(approved).unwrap().status, GrantStatus::Active);
+		assert_eq!(Balances::reserved_balance(CREATOR), 0);
+		assert_noop!(CreatorGrants::approve_grant(RuntimeOrigin::root(), approved), Error::<Test>::GrantNotProposed);
+
+		let rejected = propose(vec![100]);
+		assert_ok!(CreatorGrants::reject_grant(RuntimeOrigin::root(), rejected));
+		System::assert_last_event(Event::GrantRejected { grant_id: rejected, slashed: 10 }.into());
+		assert_eq!(Balances::free_balance(CREATOR), 90);
+		assert_eq!(Balances::reserved_balance(CREATOR), 0);
+		assert_eq!(CreatorGrants::grants(rejected), None);
+	});
+}
+
+#[test]
+fn milestones_are_paid_in_order_once_approved() {
+	new_test_ext().execute_with(|| {
+		let grant_id = propose(vec![100, 200]);
+		assert_noop!(
+			CreatorGrants::submit_milestone(RuntimeOrigin::signed(CREATOR), grant_id, b"QmDemo".to_vec()),
+			Error::<Test>::GrantNotActive
+		);
+		assert_ok!(CreatorGrants::approve_grant(RuntimeOrigin::root(), grant_id));
+		let escrow = CreatorGrants::grant_account(grant_id);
+		assert_eq!(Balances::free_balance(FUND), 699);
+		assert_eq!(Balances::free_balance(escrow), 301);
+
+		assert_noop!(CreatorGrants::approve_milestone(RuntimeOrigin::root(), grant_id), Error::<Test>::MilestoneNotSubmitted);
+		assert_noop!(
+			CreatorGrants::submit_milestone(RuntimeOrigin::signed(2), grant_id, b"QmDemo".to_vec()),
+			Error::<Test>::NotCreator
+		);
+		assert_ok!(CreatorGrants::submit_milestone(RuntimeOrigin::signed(CREATOR), grant_id, b"QmDemo".to_vec()));
+		System::assert_last_event(Event::MilestoneSubmitted { grant_id, milestone: 0 }.into());
+
+		assert_ok!(CreatorGrants::approve_milestone(RuntimeOrigin::root(), grant_id));
+		System::assert_last_event(Event::MilestonePaid { grant_id, milestone: 0, amount: 100 }.into());
+		assert_eq!(Balances::free_balance(CREATOR), 200);
+		assert_eq!(Balances::free_balance(escrow), 201);
+		assert_noop!(CreatorGrants::approve_milestone(RuntimeOrigin::root(), grant_id), Error::<Test>::MilestoneNotSubmitted);
+
+		assert_ok!(CreatorGrants::submit_milestone(RuntimeOrigin::signed(CREATOR), grant_id, b"QmAlbum".to_vec()));
+		assert_ok!(CreatorGrants::approve_milestone(RuntimeOrigin::root(), grant_id));
+		System::assert_last_event(Event::GrantCompleted { grant_id }.into());
+		assert_eq!(Balances::free_balance(CREATOR), 400);
+		assert_eq!(Balances::free_balance(escrow), 0);
+		assert_eq!(Balances::free_balance(FUND), 700);
+		assert_eq!(CreatorGrants::grants(grant_id), None);
+	});
+}
+
+#[test]
+fn grants_the_fund_cannot_cover_are_not_approved() {
+	new_test_ext().execute_with(|| {
+		let grant_id = propose(vec![400, 600]);
+
+		// The fund must stay above the existential deposit
+		assert!(CreatorGrants::approve_grant(RuntimeOrigin::root(), grant_id).is_err());
+		assert_eq!(Balances::free_balance(FUND), 1_000);
+		assert_eq!(Balances::reserved_balance(CREATOR), 10);
+		assert_eq!(CreatorGrants::grants(grant_id).unwrap().status, GrantStatus::Proposed);
+	});
+}
+
+#[test]
+fn approved_grants_are_paid_while_the_fund_is_empty() {
+	new_test_ext().execute_with(|| {
+		let grant_id = propose(vec![100, 200]);
+		assert_ok!(CreatorGrants::approve_grant(RuntimeOrigin::root(), grant_id));
+
+		// Everything left in the fund is spent elsewhere
+		assert_ok!(Balances::transfer_all(RuntimeOrigin::signed(FUND), 2, false));
+		assert_eq!(Balances::free_balance(FUND), 0);
+
+		assert_ok!(CreatorGrants::submit_milestone(RuntimeOrigin::signed(CREATOR), grant_id, b"QmDemo".to_vec()));
+		assert_ok!(CreatorGrants::approve_milestone(RuntimeOrigin::root(), grant_id));
+		System::assert_last_event(Event::MilestonePaid { grant_id, milestone: 0, amount: 100 }.into());
+		assert_eq!(Balances::free_balance(CREATOR), 200);
+		assert_eq!(Balances::free_balance(CreatorGrants::grant_account(grant_id)), 201);
+	});
+}
+
+#[test]
+fn cancelled_grants_stop_paying() {
+	new_test_ext().execute_with(|| {
+		let proposed = propose(vec![100]);
+		assert_ok!(CreatorGrants::cancel_grant(RuntimeOrigin::root(), proposed));
+		System::assert_last_event(Event::GrantCancelled { grant_id: proposed }.into());
+		assert_eq!(Balances::reserved_balance(CREATOR), 0);
+		assert_eq!(Balances::free_balance(CREATOR), 100);
+
+		let active = propose(vec![100, 200]);
+		assert_ok!(CreatorGrants::approve_grant(RuntimeOrigin::root(), active));
+		assert_ok!(CreatorGrants::submit_milestone(RuntimeOrigin::signed(CREATOR), active, b"QmDemo".to_vec()));
+		assert_ok!(CreatorGrants::approve_milestone(RuntimeOrigin::root(), active));
+		assert_eq!(Balances::free_balance(FUND), 699);
+
+		// The unpaid milestone goes back to the fund
+		assert_ok!(CreatorGrants::cancel_grant(RuntimeOrigin::root(), active));
+		assert_eq!(Balances::free_balance(FUND), 900);
+		assert_eq!(Balances::free_balance(CreatorGrants::grant_account(active)), 0);
+		assert_noop!(
+			CreatorGrants::submit_milestone(RuntimeOrigin::signed(CREATOR), active, b"QmDemo".to_vec()),
+			Error::<Test>::UnknownGrant
+		);
+		assert_noop!(CreatorGrants::cancel_grant(RuntimeOrigin::signed(CREATOR), active), BadOrigin);
+	});
+}
+
+#[test]
+fn escrows_survive_a_realistic_existential_deposit() {
+	new_test_ext().execute_with(|| {
+		// As in the runtime
+		ExistentialDeposit::set(500);
+		let _ = Balances::deposit_creating(&FUND, 9_000);
+		let issuance = Balances::total_issuance();
+
+		let grant_id = propose(vec![300, 300]);
+		assert_ok!(CreatorGrants::approve_grant(RuntimeOrigin::root(), grant_id));
+		let escrow = CreatorGrants::grant_account(grant_id);
+		assert_eq!(Balances::free_balance(escrow), 1_100);
+
+		for evidence in [b"QmDemo", b"QmSong"] {
+			assert_ok!(CreatorGrants::submit_milestone(RuntimeOrigin::signed(CREATOR), grant_id, evidence.to_vec()));
+			assert_ok!(CreatorGrants::approve_milestone(RuntimeOrigin::root(), grant_id));
+		}
+		System::assert_last_event(Event::GrantCompleted { grant_id }.into());
+		assert_eq!(Balances::free_balance(CREATOR), 700);
+		assert_eq!(Balances::free_balance(escrow), 0);
+		assert_eq!(Balances::free_balance(FUND), 9_400);
+		// Nothing was reaped as dust
+		assert_eq!(Balances::total_issuance(), issuance);
+	});
+}
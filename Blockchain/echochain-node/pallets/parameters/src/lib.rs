@@ -0,0 +1,191 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Parameters Pallet
+//!
+//! Stores runtime parameters that governance can change without a runtime upgrade, such as
+//! reward amounts and thresholds. Every parameter has a typed key, a default and bounds
+//! provided by the runtime through [`ParameterInfo`]. Pallets read parameters through the
+//! `Get` adapters of this module, e.g. `type ContentRewardAmount = ContentRewardAmount<Runtime>;`,
+//! which fall back to the default until a value is set.
+
+pub use pallet::*;
+
+use frame_support::traits::Get;
+use sp_std::{marker::PhantomData, ops::RangeInclusive};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Defaults and bounds of the runtime parameters, provided by the runtime.
+pub trait ParameterInfo {
+	/// Value of `key` until one is set.
+	fn default_value(key: ParameterKey) -> RuntimeParameter;
+
+	/// Values accepted for `key`.
+	fn bounds(key: ParameterKey) -> RangeInclusive<u128>;
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::ParameterInfo;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	/// Key of a runtime parameter.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum ParameterKey {
+		/// ECHO paid per content reward by `pallet-proof-of-contribution`.
+		ContentRewardAmount,
+		/// Approved samples required for a content reward.
+		MinSamplesForContentReward,
+		/// Blocks between network reward distributions.
+		NetworkRewardPeriod,
+		/// ECHO shared between seeders per network reward period.
+		TotalRewardPerPeriod,
+		/// Bytes a seeder must upload in a period to be rewarded.
+		MinBytesUploaded,
+	}
+
+	/// A runtime parameter with its value.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum RuntimeParameter {
+		ContentRewardAmount(u128),
+		MinSamplesForContentReward(u32),
+		NetworkRewardPeriod(u32),
+		TotalRewardPerPeriod(u128),
+		MinBytesUploaded(u64),
+	}
+
+	impl RuntimeParameter {
+		/// Key of the parameter.
+		pub fn key(&self) -> ParameterKey {
+			match self {
+				RuntimeParameter::ContentRewardAmount(_) => ParameterKey::ContentRewardAmount,
+				RuntimeParameter::MinSamplesForContentReward(_) => ParameterKey::MinSamplesForContentReward,
+				RuntimeParameter::NetworkRewardPeriod(_) => ParameterKey::NetworkRewardPeriod,
+				RuntimeParameter::TotalRewardPerPeriod(_) => ParameterKey::TotalRewardPerPeriod,
+				RuntimeParameter::MinBytesUploaded(_) => ParameterKey::MinBytesUploaded,
+			}
+		}
+
+		/// Value of the parameter, widened for bounds checks.
+		pub fn value(&self) -> u128 {
+			match *self {
+				RuntimeParameter::ContentRewardAmount(value) => value,
+				RuntimeParameter::MinSamplesForContentReward(value) => value.into(),
+				RuntimeParameter::NetworkRewardPeriod(value) => value.into(),
+				RuntimeParameter::TotalRewardPerPeriod(value) => value,
+				RuntimeParameter::MinBytesUploaded(value) => value.into(),
+			}
+		}
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Origin allowed to set and reset parameters, e.g. root or governance.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Defaults and bounds of the parameters.
+		type Info: ParameterInfo;
+	}
+
+	/// Parameters set by governance. Parameters not in the map have their default value.
+	#[pallet::storage]
+	pub type Parameters<T: Config> = StorageMap<_, Twox64Concat, ParameterKey, RuntimeParameter, OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A parameter was set.
+		ParameterSet { parameter: RuntimeParameter },
+		/// A parameter was reset to its default.
+		ParameterReset { key: ParameterKey },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The value is outside the bounds of the parameter.
+		OutOfBounds,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set a parameter to a value within its bounds.
+		#[pallet::call_index(0)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn set_parameter(origin: OriginFor<T>, parameter: RuntimeParameter) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(T::Info::bounds(parameter.key()).contains(&parameter.value()), Error::<T>::OutOfBounds);
+
+			Parameters::<T>::insert(parameter.key(), parameter);
+			Self::deposit_event(Event::ParameterSet { parameter });
+			Ok(())
+		}
+
+		/// Reset a parameter to its default.
+		#[pallet::call_index(1)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn reset_parameter(origin: OriginFor<T>, key: ParameterKey) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			Parameters::<T>::remove(key);
+			Self::deposit_event(Event::ParameterReset { key });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Current value of `key`: the value set by governance, or the default.
+		pub fn parameter(key: ParameterKey) -> RuntimeParameter {
+			Parameters::<T>::get(key).unwrap_or_else(|| T::Info::default_value(key))
+		}
+	}
+}
+
+/// Defines a `Get` adapter reading a parameter, converted into any type it fits in.
+macro_rules! parameter_getter {
+	($(#[$doc:meta])* $name:ident: $ty:ty) => {
+		$(#[$doc])*
+		pub struct $name<T>(PhantomData<T>);
+
+		impl<T: Config, V: From<$ty>> Get<V> for $name<T> {
+			fn get() -> V {
+				match Pallet::<T>::parameter(ParameterKey::$name) {
+					RuntimeParameter::$name(value) => value.into(),
+					// Only reachable if `ParameterInfo` gives the default of another parameter
+					_ => <$ty>::default().into(),
+				}
+			}
+		}
+	};
+}
+
+parameter_getter! {
+	/// `Get` adapter for [`ParameterKey::ContentRewardAmount`].
+	ContentRewardAmount: u128
+}
+parameter_getter! {
+	/// `Get` adapter for [`ParameterKey::MinSamplesForContentReward`].
+	MinSamplesForContentReward: u32
+}
+parameter_getter! {
+	/// `Get` adapter for [`ParameterKey::NetworkRewardPeriod`].
+	NetworkRewardPeriod: u32
+}
+parameter_getter! {
+	/// `Get` adapter for [`ParameterKey::TotalRewardPerPeriod`].
+	TotalRewardPerPeriod: u128
+}
+parameter_getter! {
+	/// `Get` adapter for [`ParameterKey::MinBytesUploaded`].
+	MinBytesUploaded: u64
+}
@@ -0,0 +1,85 @@
+use crate as pallet_parameters;
+use crate::{ParameterInfo, ParameterKey, RuntimeParameter};
+use frame_support::traits::{ConstU16, ConstU32, ConstU64};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup},
+	BuildStorage,
+};
+use sp_std::ops::RangeInclusive;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Parameters: pallet_parameters,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Nonce = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Block = Block;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+pub struct TestParameters;
+
+impl ParameterInfo for TestParameters {
+	fn default_value(key: ParameterKey) -> RuntimeParameter {
+		match key {
+			ParameterKey::ContentRewardAmount => RuntimeParameter::ContentRewardAmount(100),
+			ParameterKey::MinSamplesForContentReward => RuntimeParameter::MinSamplesForContentReward(5),
+			ParameterKey::NetworkRewardPeriod => RuntimeParameter::NetworkRewardPeriod(600),
+			ParameterKey::TotalRewardPerPeriod => RuntimeParameter::TotalRewardPerPeriod(10_000),
+			ParameterKey::MinBytesUploaded => RuntimeParameter::MinBytesUploaded(1_024),
+		}
+	}
+
+	fn bounds(key: ParameterKey) -> RangeInclusive<u128> {
+		match key {
+			ParameterKey::ContentRewardAmount => 0..=1_000,
+			ParameterKey::MinSamplesForContentReward => 1..=100,
+			ParameterKey::NetworkRewardPeriod => 100..=10_000,
+			ParameterKey::TotalRewardPerPeriod => 0..=100_000,
+			ParameterKey::MinBytesUploaded => 0..=u64::MAX.into(),
+		}
+	}
+}
+
+impl pallet_parameters::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type AdminOrigin = EnsureRoot<u64>;
+	type Info = TestParameters;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities =
+		frame_system::GenesisConfig::<Test>::default().build_storage().unwrap().into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,56 @@
+use crate::{
+	mock::*, ContentRewardAmount, Error, Event, MinBytesUploaded, NetworkRewardPeriod, ParameterKey,
+	RuntimeParameter,
+};
+use frame_support::{assert_noop, assert_ok, traits::Get};
+use sp_runtime::traits::BadOrigin;
+
+#[test]
+fn adapters_read_defaults_until_a_value_is_set() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(<ContentRewardAmount<Test> as Get<u128>>::get(), 100);
+		assert_eq!(<NetworkRewardPeriod<Test> as Get<u64>>::get(), 600);
+
+		assert_ok!(Parameters::set_parameter(RuntimeOrigin::root(), RuntimeParameter::ContentRewardAmount(250)));
+		System::assert_last_event(
+			Event::ParameterSet { parameter: RuntimeParameter::ContentRewardAmount(250) }.into(),
+		);
+		assert_eq!(<ContentRewardAmount<Test> as Get<u128>>::get(), 250);
+		assert_eq!(Parameters::parameter(ParameterKey::ContentRewardAmount), RuntimeParameter::ContentRewardAmount(250));
+
+		assert_ok!(Parameters::reset_parameter(RuntimeOrigin::root(), ParameterKey::ContentRewardAmount));
+		System::assert_last_event(Event::ParameterReset { key: ParameterKey::ContentRewardAmount }.into());
+		assert_eq!(<ContentRewardAmount<Test> as Get<u128>>::get(), 100);
+	});
+}
+
+#[test]
+fn values_must_be_within_bounds() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Parameters::set_parameter(RuntimeOrigin::root(), RuntimeParameter::NetworkRewardPeriod(99)),
+			Error::<Test>::OutOfBounds
+		);
+		assert_noop!(
+			Parameters::set_parameter(RuntimeOrigin::root(), RuntimeParameter::MinSamplesForContentReward(0)),
+			Error::<Test>::OutOfBounds
+		);
+		assert_ok!(Parameters::set_parameter(RuntimeOrigin::root(), RuntimeParameter::NetworkRewardPeriod(10_000)));
+		assert_ok!(Parameters::set_parameter(RuntimeOrigin::root(), RuntimeParameter::MinBytesUploaded(u64::MAX)));
+		assert_eq!(<MinBytesUploaded<Test> as Get<u64>>::get(), u64::MAX);
+	});
+}
+
+#[test]
+fn only_the_admin_origin_changes_parameters() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Parameters::set_parameter(RuntimeOrigin::signed(1), RuntimeParameter::ContentRewardAmount(1)),
+			BadOrigin
+		);
+		assert_noop!(
+			Parameters::reset_parameter(RuntimeOrigin::signed(1), ParameterKey::ContentRewardAmount),
+			BadOrigin
+		);
+	});
+}
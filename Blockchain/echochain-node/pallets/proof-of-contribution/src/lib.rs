@@ -6,10 +6,11 @@ pub use pallet::*;
 pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
-	use frame_support::traits::{Currency, ReservableCurrency};
+	use frame_support::traits::{Currency, ExistenceRequirement, ReservableCurrency};
 	use sp_runtime::traits::UniqueSaturatedInto;
 	use sp_std::prelude::*;
 
+	use pallet_reputation::ReputationProvider;
 	use pallet_sample_registry::{SampleStatus, Samples as SampleRegistrySamples};
 
 	#[pallet::pallet]
@@ -25,20 +26,27 @@ pub mod pallet {
 		type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
 
 		/// The amount of ECHO tokens to reward for content contribution.
-		#[pallet::constant]
+		/// Not a metadata constant: the runtime reads it from `pallet-parameters`.
 		type ContentRewardAmount: Get<BalanceOf<Self>>;
 
 		/// The minimum number of approved samples required for content reward.
-		#[pallet::constant]
 		type MinSamplesForContentReward: Get<u32>;
 
 		/// The period in blocks for distributing network rewards.
-		#[pallet::constant]
 		type NetworkRewardPeriod: Get<Self::BlockNumber>;
 
-		/// The account ID for the treasury where rewards are minted from.
+		/// The content reward pool that rewards are paid from. It is funded by the emission
+		/// schedule of `pallet-tokenomics`.
 		#[pallet::constant]
-		type TreasuryAccount: Get<Self::AccountId>;
+		type RewardPoolAccount: Get<Self::AccountId>;
+
+		/// The network reward pool that network rewards are paid from, funded by the same
+		/// emission schedule.
+		#[pallet::constant]
+		type NetworkRewardPoolAccount: Get<Self::AccountId>;
+
+		/// Scales each creator's reward by their reputation.
+		type Reputation: ReputationProvider<Self::AccountId>;
 	}
 
 	pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
@@ -109,6 +117,7 @@ pub mod pallet {
 			let mut rewarded_creators = 0;
 			let mut total_reward_amount: BalanceOf<T> = Zero::zero();
 			let current_block_number = <frame_system::Pallet<T>>::block_number();
+			let pool = T::RewardPoolAccount::get();
 
 			// Iterate through all samples and group by creator to count approved samples
 			let mut creator_approved_samples: sp_std::collections::btree_map::BTreeMap<T::AccountId, u32> = 
@@ -122,9 +131,11 @@ pub mod pallet {
 
 			for (creator, count) in creator_approved_samples.into_iter() {
 				if count >= T::MinSamplesForContentReward::get() {
-					let reward = T::ContentRewardAmount::get();
-					// Mint and transfer tokens from treasury to creator
-					T::Currency::deposit_creating(&creator, reward);
+					let reward = T::Reputation::reward_multiplier(&creator) * T::ContentRewardAmount::get();
+					// Pay from the reward pool; creators are skipped once it runs dry
+					if T::Currency::transfer(&pool, &creator, reward, ExistenceRequirement::KeepAlive).is_err() {
+						continue;
+					}
 					rewarded_creators += 1;
 					total_reward_amount = total_reward_amount.saturating_add(reward);
 				}
@@ -141,7 +152,7 @@ pub mod pallet {
 		/// Distribute network rewards based on reported contributions.
 		fn distribute_network_rewards() -> Weight {
 			let current_block_number = <frame_system::Pallet<T>>::block_number();
-			let total_network_reward_pool: BalanceOf<T> = T::Currency::free_balance(&T::TreasuryAccount::get());
+			let total_network_reward_pool: BalanceOf<T> = T::Currency::free_balance(&T::NetworkRewardPoolAccount::get());
 			
 			// For simplicity, we'll just log a message for now.
 			// In a real implementation, this would involve more complex logic
@@ -0,0 +1,206 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Reputation Pallet
+//!
+//! Accumulates a per-account reputation score from sample approvals and rejections, upheld
+//! reports against the account and seeding reliability. Scores decay over time and are turned
+//! into a reward multiplier that every reward pallet applies through [`ReputationProvider`].
+
+pub use pallet::*;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::{Perbill, RuntimeDebug};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Something that affects an account's reputation.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum ReputationEvent {
+	/// One of the account's samples was approved.
+	SampleApproved,
+	/// One of the account's samples was rejected.
+	SampleRejected,
+	/// A report against the account was upheld.
+	ReportUpheld,
+	/// The account's seeding report for a period was accepted.
+	SeedingReliable,
+	/// The account failed to back up its seeding report.
+	SeedingUnreliable,
+}
+
+/// Records reputation events. Implemented by this pallet, used by the pallets observing them.
+pub trait ReputationHandler<AccountId> {
+	fn note(who: &AccountId, event: ReputationEvent);
+}
+
+impl<AccountId> ReputationHandler<AccountId> for () {
+	fn note(_who: &AccountId, _event: ReputationEvent) {}
+}
+
+/// Exposes reputation to the reward pallets.
+pub trait ReputationProvider<AccountId> {
+	/// Current, decayed score of `who`.
+	fn score(who: &AccountId) -> u32;
+	/// Portion of a full reward `who` is entitled to.
+	fn reward_multiplier(who: &AccountId) -> Perbill;
+}
+
+/// Without a reputation system every account receives full rewards.
+impl<AccountId> ReputationProvider<AccountId> for () {
+	fn score(_who: &AccountId) -> u32 {
+		0
+	}
+
+	fn reward_multiplier(_who: &AccountId) -> Perbill {
+		Perbill::one()
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{ReputationEvent, ReputationHandler, ReputationProvider};
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::{
+		traits::{Saturating, UniqueSaturatedInto, Zero},
+		PerThing, Perbill,
+	};
+
+	/// Score of an account as of `updated_at`.
+	#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct ReputationRecord<BlockNumber> {
+		pub score: u32,
+		pub updated_at: BlockNumber,
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Origin allowed to uphold reports against an account.
+		type ReportOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Score gained when a sample is approved.
+		#[pallet::constant]
+		type ApprovalBonus: Get<u32>;
+
+		/// Score lost when a sample is rejected.
+		#[pallet::constant]
+		type RejectionPenalty: Get<u32>;
+
+		/// Score lost when a report against the account is upheld.
+		#[pallet::constant]
+		type UpheldReportPenalty: Get<u32>;
+
+		/// Score gained for every accepted seeding period.
+		#[pallet::constant]
+		type SeedingBonus: Get<u32>;
+
+		/// Score lost for every seeding report that could not be backed up.
+		#[pallet::constant]
+		type SeedingPenalty: Get<u32>;
+
+		/// Upper bound on a score. An account at `MaxScore` receives full rewards.
+		#[pallet::constant]
+		type MaxScore: Get<u32>;
+
+		/// Reward multiplier of an account without any reputation.
+		#[pallet::constant]
+		type MinMultiplier: Get<Perbill>;
+
+		/// Number of blocks after which a score decays by `DecayRate`.
+		#[pallet::constant]
+		type DecayPeriod: Get<BlockNumberFor<Self>>;
+
+		/// Share of the score lost every `DecayPeriod`.
+		#[pallet::constant]
+		type DecayRate: Get<Perbill>;
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn reputation)]
+	pub type Reputations<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, ReputationRecord<BlockNumberFor<T>>, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The score of an account changed.
+		ReputationChanged { who: T::AccountId, event: ReputationEvent, score: u32 },
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Record that a report against `who` was upheld.
+		#[pallet::call_index(0)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn report_upheld(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::ReportOrigin::ensure_origin(origin)?;
+			<Self as ReputationHandler<_>>::note(&who, ReputationEvent::ReportUpheld);
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Decay `record` to block `now`. Partial periods are carried over to the next update.
+		fn decayed(record: ReputationRecord<BlockNumberFor<T>>, now: BlockNumberFor<T>) -> ReputationRecord<BlockNumberFor<T>> {
+			let period = T::DecayPeriod::get();
+			if period.is_zero() || record.score == 0 {
+				return ReputationRecord { score: record.score, updated_at: now };
+			}
+			let periods = now.saturating_sub(record.updated_at) / period;
+			let remaining = Perbill::one()
+				.saturating_sub(T::DecayRate::get())
+				.saturating_pow(UniqueSaturatedInto::<u32>::unique_saturated_into(periods) as usize);
+			ReputationRecord {
+				score: remaining.mul_floor(record.score),
+				updated_at: record.updated_at.saturating_add(periods.saturating_mul(period)),
+			}
+		}
+
+		fn delta(event: ReputationEvent) -> i64 {
+			match event {
+				ReputationEvent::SampleApproved => T::ApprovalBonus::get().into(),
+				ReputationEvent::SampleRejected => -i64::from(T::RejectionPenalty::get()),
+				ReputationEvent::ReportUpheld => -i64::from(T::UpheldReportPenalty::get()),
+				ReputationEvent::SeedingReliable => T::SeedingBonus::get().into(),
+				ReputationEvent::SeedingUnreliable => -i64::from(T::SeedingPenalty::get()),
+			}
+		}
+	}
+
+	impl<T: Config> ReputationHandler<T::AccountId> for Pallet<T> {
+		fn note(who: &T::AccountId, event: ReputationEvent) {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let score = Reputations::<T>::mutate(who, |record| {
+				*record = Self::decayed(record.clone(), now);
+				let score = i64::from(record.score).saturating_add(Self::delta(event));
+				record.score = score.clamp(0, T::MaxScore::get().into()) as u32;
+				record.score
+			});
+			Self::deposit_event(Event::ReputationChanged { who: who.clone(), event, score });
+		}
+	}
+
+	impl<T: Config> ReputationProvider<T::AccountId> for Pallet<T> {
+		fn score(who: &T::AccountId) -> u32 {
+			let now = <frame_system::Pallet<T>>::block_number();
+			Self::decayed(Reputations::<T>::get(who), now).score
+		}
+
+		fn reward_multiplier(who: &T::AccountId) -> Perbill {
+			let min = T::MinMultiplier::get();
+			let earned = Perbill::from_rational(Self::score(who), T::MaxScore::get().max(1));
+			min.saturating_add((Perbill::one() - min) * earned)
+		}
+	}
+}
@@ -0,0 +1,75 @@
+use crate as pallet_reputation;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup},
+	BuildStorage, Perbill,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Reputation: pallet_reputation,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Nonce = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Block = Block;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+	pub const MinMultiplier: Perbill = Perbill::from_percent(50);
+	pub const DecayRate: Perbill = Perbill::from_percent(10);
+}
+
+impl pallet_reputation::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type ReportOrigin = EnsureRoot<u64>;
+	type ApprovalBonus = ConstU32<100>;
+	type RejectionPenalty = ConstU32<50>;
+	type UpheldReportPenalty = ConstU32<300>;
+	type SeedingBonus = ConstU32<20>;
+	type SeedingPenalty = ConstU32<40>;
+	type MaxScore = ConstU32<1_000>;
+	type MinMultiplier = MinMultiplier;
+	type DecayPeriod = ConstU64<100>;
+	type DecayRate = DecayRate;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities =
+		frame_system::GenesisConfig::<Test>::default().build_storage().unwrap().into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
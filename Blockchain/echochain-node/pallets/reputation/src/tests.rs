@@ -0,0 +1,72 @@
+use crate::{mock::*, Event, ReputationEvent, ReputationHandler, ReputationProvider};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{traits::BadOrigin, Perbill};
+
+#[test]
+fn events_move_the_score_within_bounds() {
+	new_test_ext().execute_with(|| {
+		Reputation::note(&1, ReputationEvent::SampleRejected);
+		assert_eq!(Reputation::score(&1), 0);
+
+		Reputation::note(&1, ReputationEvent::SampleApproved);
+		Reputation::note(&1, ReputationEvent::SeedingReliable);
+		assert_eq!(Reputation::score(&1), 120);
+		Reputation::note(&1, ReputationEvent::SeedingUnreliable);
+		assert_eq!(Reputation::score(&1), 80);
+		System::assert_last_event(
+			Event::ReputationChanged { who: 1, event: ReputationEvent::SeedingUnreliable, score: 80 }.into(),
+		);
+
+		for _ in 0..20 {
+			Reputation::note(&1, ReputationEvent::SampleApproved);
+		}
+		assert_eq!(Reputation::score(&1), 1_000);
+	});
+}
+
+#[test]
+fn scores_decay_per_full_period() {
+	new_test_ext().execute_with(|| {
+		Reputation::note(&1, ReputationEvent::SampleApproved);
+		System::set_block_number(100);
+		assert_eq!(Reputation::score(&1), 100);
+		System::set_block_number(101);
+		assert_eq!(Reputation::score(&1), 90);
+		System::set_block_number(301);
+		assert_eq!(Reputation::score(&1), 72);
+
+		// The partial period before an update is not lost
+		System::set_block_number(150);
+		Reputation::note(&1, ReputationEvent::SeedingReliable);
+		assert_eq!(Reputation::score(&1), 110);
+		System::set_block_number(201);
+		assert_eq!(Reputation::score(&1), 99);
+	});
+}
+
+#[test]
+fn multiplier_scales_from_minimum_to_full() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Reputation::reward_multiplier(&1), Perbill::from_percent(50));
+		for _ in 0..5 {
+			Reputation::note(&1, ReputationEvent::SampleApproved);
+		}
+		assert_eq!(Reputation::reward_multiplier(&1), Perbill::from_percent(75));
+		for _ in 0..5 {
+			Reputation::note(&1, ReputationEvent::SampleApproved);
+		}
+		assert_eq!(Reputation::reward_multiplier(&1), Perbill::one());
+	});
+}
+
+#[test]
+fn only_report_origin_can_uphold_reports() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..5 {
+			Reputation::note(&2, ReputationEvent::SampleApproved);
+		}
+		assert_noop!(Reputation::report_upheld(RuntimeOrigin::signed(1), 2), BadOrigin);
+		assert_ok!(Reputation::report_upheld(RuntimeOrigin::root(), 2));
+		assert_eq!(Reputation::score(&2), 200);
+	});
+}
@@ -2,18 +2,54 @@
 
 pub use pallet::*;
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
 #[frame_support::pallet]
 pub mod pallet {
     use frame_support::{pallet_prelude::*, traits::{Currency, ExistenceRequirement, Get}};
     use frame_system::pallet_prelude::*;
-    use crate::Config;
+    use pallet_reputation::ReputationProvider;
+    use sp_runtime::traits::Zero;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
 
     #[pallet::config]
     pub trait Config: frame_system::Config {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
         type Currency: Currency<Self::AccountId>;
         type AssetRegistry: AssetRegistryProvider<Self::AccountId>;
-        type EpochDuration: Get<Self::BlockNumber>;
+        type EpochDuration: Get<BlockNumberFor<Self>>;
+        /// Account funded by the emission schedule that rewards are paid from.
+        #[pallet::constant]
+        type RewardPoolAccount: Get<Self::AccountId>;
+        /// Scales each account's reward by its reputation.
+        type Reputation: ReputationProvider<Self::AccountId>;
+        /// Maximum number of accounts inspected per block while an epoch is being paid out.
+        #[pallet::constant]
+        type MaxRewardsPerBlock: Get<u32>;
+        /// Maximum number of epochs waiting for the current payout to finish. Epochs starting
+        /// while the queue is full are skipped.
+        #[pallet::constant]
+        type MaxQueuedEpochs: Get<u32>;
+    }
+
+    /// Upper bound on the length of a raw `frame_system::Account` storage key.
+    pub const MAX_CURSOR_KEY_LEN: u32 = 128;
+
+    /// Progress of an epoch's reward payout, carried across blocks.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct PayoutCursor<BlockNumber> {
+        /// Block at which the epoch being paid out started.
+        pub epoch: BlockNumber,
+        /// Raw storage key of the last account inspected, `None` before the first batch.
+        pub last_key: Option<BoundedVec<u8, ConstU32<MAX_CURSOR_KEY_LEN>>>,
+        /// Number of accounts rewarded so far in this epoch.
+        pub rewarded: u32,
     }
 
     pub trait AssetRegistryProvider<AccountId> {
@@ -30,14 +66,29 @@ pub mod pallet {
 
     #[pallet::storage]
     #[pallet::getter(fn last_reward_block)]
-    pub type LastRewardBlock<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+    pub type LastRewardBlock<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    /// The in-progress epoch payout, if any.
+    #[pallet::storage]
+    #[pallet::getter(fn payout_cursor)]
+    pub type RewardCursor<T: Config> = StorageValue<_, PayoutCursor<BlockNumberFor<T>>, OptionQuery>;
+
+    /// Epochs that started while another epoch was being paid out, oldest first.
+    #[pallet::storage]
+    #[pallet::getter(fn queued_epochs)]
+    pub type QueuedEpochs<T: Config> =
+        StorageValue<_, BoundedVec<BlockNumberFor<T>, T::MaxQueuedEpochs>, ValueQuery>;
 
     type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
-        RewardsDistributed { epoch: T::BlockNumber },
+        RewardsDistributed { epoch: BlockNumberFor<T>, rewarded: u32 },
+        /// An epoch started while a payout was in progress and waits for it to finish.
+        EpochQueued { epoch: BlockNumberFor<T> },
+        /// An epoch started while the queue was full and is not paid out.
+        EpochSkipped { epoch: BlockNumberFor<T> },
         RewardParametersUpdated { amount: BalanceOf<T>, min_assets: u32 },
     }
 
@@ -47,24 +98,23 @@ pub mod pallet {
     }
 
     #[pallet::hooks]
-    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
-        fn on_initialize(n: T::BlockNumber) -> Weight {
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
             if (n % T::EpochDuration::get()).is_zero() {
-                let min_assets = MinimumAssetsForReward::<T>::get();
-                let reward_amount = EpochRewardAmount::<T>::get();
-                let mut rewarded = 0u32;
-                // Iterate all accounts (in production, use an efficient index or offchain worker)
-                // Here, we assume a small devnet for PoC
-                for (who, _) in <frame_system::Account<T>>::iter() {
-                    if T::AssetRegistry::asset_count(&who) >= min_assets {
-                        let _ = T::Currency::deposit_creating(&who, reward_amount);
-                        rewarded += 1;
-                    }
+                if !RewardCursor::<T>::exists() {
+                    // Accounts are paid in bounded batches from this block on
+                    Self::open_epoch(n);
+                    weight = weight.saturating_add(T::DbWeight::get().writes(2));
+                } else if QueuedEpochs::<T>::try_append(n).is_ok() {
+                    Self::deposit_event(Event::EpochQueued { epoch: n });
+                    weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+                } else {
+                    Self::deposit_event(Event::EpochSkipped { epoch: n });
+                    weight = weight.saturating_add(T::DbWeight::get().reads(1));
                 }
-                LastRewardBlock::<T>::put(n);
-                Self::deposit_event(Event::RewardsDistributed { epoch: n });
             }
-            0
+            weight.saturating_add(Self::process_rewards(T::MaxRewardsPerBlock::get()))
         }
     }
 
@@ -83,4 +133,77 @@ pub mod pallet {
             Ok(())
         }
     }
+
+    impl<T: Config> Pallet<T> {
+        fn open_epoch(epoch: BlockNumberFor<T>) {
+            RewardCursor::<T>::put(PayoutCursor { epoch, last_key: None, rewarded: 0 });
+            LastRewardBlock::<T>::put(epoch);
+        }
+
+        /// Reward at most `limit` accounts of the epoch tracked by `RewardCursor`,
+        /// resuming from the last inspected `frame_system::Account` key.
+        fn process_rewards(limit: u32) -> Weight {
+            let mut cursor = match RewardCursor::<T>::get() {
+                Some(cursor) => cursor,
+                None => return Weight::zero(),
+            };
+
+            let min_assets = MinimumAssetsForReward::<T>::get();
+            let reward_amount = EpochRewardAmount::<T>::get();
+            let pool = T::RewardPoolAccount::get();
+            let accounts = match &cursor.last_key {
+                Some(key) => <frame_system::Account<T>>::iter_keys_from(key.to_vec()),
+                None => <frame_system::Account<T>>::iter_keys(),
+            };
+
+            let mut inspected = 0u32;
+            let mut rewarded = 0u32;
+            let mut last_key = None;
+            for who in accounts.take(limit as usize) {
+                if T::AssetRegistry::asset_count(&who) >= min_assets
+                    && T::Currency::transfer(
+                        &pool,
+                        &who,
+                        T::Reputation::reward_multiplier(&who) * reward_amount,
+                        ExistenceRequirement::KeepAlive,
+                    ).is_ok()
+                {
+                    rewarded += 1;
+                }
+                last_key = Some(<frame_system::Account<T>>::hashed_key_for(&who));
+                inspected += 1;
+            }
+            cursor.rewarded = cursor.rewarded.saturating_add(rewarded);
+
+            // Cursor, parameters and the pool balance, then per account: the account entry,
+            // its asset count, its reputation and, for rewarded accounts, both balance writes
+            let mut weight = T::DbWeight::get().reads_writes(
+                4u64.saturating_add(3u64.saturating_mul(inspected.into())),
+                1u64.saturating_add(2u64.saturating_mul(rewarded.into())),
+            );
+
+            let next_key = last_key.and_then(|key| BoundedVec::try_from(key).ok());
+            match next_key {
+                Some(key) if inspected == limit => {
+                    cursor.last_key = Some(key);
+                    RewardCursor::<T>::put(cursor);
+                }
+                _ => {
+                    RewardCursor::<T>::kill();
+                    Self::deposit_event(Event::RewardsDistributed {
+                        epoch: cursor.epoch,
+                        rewarded: cursor.rewarded,
+                    });
+                    // The next queued epoch is paid out from the next block on
+                    let mut queued = QueuedEpochs::<T>::get();
+                    if !queued.is_empty() {
+                        Self::open_epoch(queued.remove(0));
+                        QueuedEpochs::<T>::put(queued);
+                    }
+                    weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 3));
+                }
+            }
+            weight
+        }
+    }
 }
@@ -0,0 +1,112 @@
+use crate as pallet_rewards;
+use crate::AssetRegistryProvider;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU128, ConstU16, ConstU32, ConstU64},
+};
+use sp_core::H256;
+use sp_runtime::{
+    traits::{BlakeTwo256, IdentityLookup},
+    BuildStorage,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const POOL: u64 = 100;
+/// Account without any registered asset.
+pub const NO_ASSETS: u64 = 3;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test
+    {
+        System: frame_system,
+        Balances: pallet_balances,
+        Rewards: pallet_rewards,
+    }
+);
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = Block;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u128>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ConstU16<42>;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = u128;
+    type RuntimeEvent = RuntimeEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ConstU128<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type FreezeIdentifier = ();
+    type MaxFreezes = ();
+    type RuntimeHoldReason = ();
+    type MaxHolds = ();
+}
+
+/// Every account but the pool and `NO_ASSETS` holds one asset.
+pub struct MockAssets;
+
+impl AssetRegistryProvider<u64> for MockAssets {
+    fn asset_count(who: &u64) -> u32 {
+        if *who == POOL || *who == NO_ASSETS {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+parameter_types! {
+    pub const RewardPoolAccount: u64 = POOL;
+    pub static MaxRewardsPerBlock: u32 = 2;
+}
+
+impl pallet_rewards::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type AssetRegistry = MockAssets;
+    type EpochDuration = ConstU64<4>;
+    type RewardPoolAccount = RewardPoolAccount;
+    type Reputation = ();
+    type MaxRewardsPerBlock = MaxRewardsPerBlock;
+    type MaxQueuedEpochs = ConstU32<1>;
+}
+
+// Build genesis storage according to the mock runtime: accounts 1 to 5 hold 10 units each and
+// the pool 1_000.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: (1..=5).map(|who| (who, 10)).chain([(POOL, 1_000)]).collect(),
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    let mut ext: sp_io::TestExternalities = storage.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
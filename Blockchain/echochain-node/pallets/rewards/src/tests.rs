@@ -0,0 +1,62 @@
+use crate::{mock::*, Event};
+use frame_support::{assert_ok, traits::Hooks};
+
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        let next = System::block_number() + 1;
+        System::set_block_number(next);
+        Rewards::on_initialize(next);
+    }
+}
+
+fn rewarded_balances() -> Vec<u128> {
+    [1, 2, 4, 5].iter().map(|who| Balances::free_balance(who)).collect()
+}
+
+#[test]
+fn payouts_resume_across_blocks() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Rewards::set_reward_parameters(RuntimeOrigin::root(), 10, 1));
+
+        // Six accounts, two per block: the epoch opened at block 4 is still in progress at 6
+        run_to_block(6);
+        let cursor = Rewards::payout_cursor().unwrap();
+        assert_eq!(cursor.epoch, 4);
+        assert!(cursor.last_key.is_some());
+        assert!(cursor.rewarded <= 4);
+
+        run_to_block(7);
+        assert_eq!(Rewards::payout_cursor(), None);
+        System::assert_last_event(Event::RewardsDistributed { epoch: 4, rewarded: 4 }.into());
+        assert_eq!(rewarded_balances(), vec![20; 4]);
+        assert_eq!(Balances::free_balance(NO_ASSETS), 10);
+        assert_eq!(Balances::free_balance(POOL), 960);
+        assert_eq!(Rewards::last_reward_block(), 4);
+    });
+}
+
+#[test]
+fn epochs_starting_during_a_payout_are_queued_or_skipped() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Rewards::set_reward_parameters(RuntimeOrigin::root(), 10, 1));
+        MaxRewardsPerBlock::set(1);
+
+        // Epoch 4 takes blocks 4 to 10, so epoch 8 waits for it
+        run_to_block(8);
+        System::assert_has_event(Event::EpochQueued { epoch: 8 }.into());
+        assert_eq!(Rewards::queued_epochs().into_inner(), vec![8]);
+
+        run_to_block(10);
+        System::assert_has_event(Event::RewardsDistributed { epoch: 4, rewarded: 4 }.into());
+        assert_eq!(Rewards::payout_cursor().unwrap().epoch, 8);
+        assert!(Rewards::queued_epochs().is_empty());
+
+        // Epoch 12 fills the queue, so epoch 16 is skipped
+        run_to_block(17);
+        System::assert_has_event(Event::EpochQueued { epoch: 12 }.into());
+        System::assert_has_event(Event::EpochSkipped { epoch: 16 }.into());
+        System::assert_has_event(Event::RewardsDistributed { epoch: 8, rewarded: 4 }.into());
+        assert_eq!(Rewards::payout_cursor().unwrap().epoch, 12);
+        assert_eq!(rewarded_balances(), vec![30; 4]);
+    });
+}
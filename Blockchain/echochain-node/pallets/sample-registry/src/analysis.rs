@@ -0,0 +1,125 @@
+//! Offchain worker pulling the results of the audio analysis service for pending samples.
+//!
+//! For every pending sample the worker queries `GET {url}/analysis/{ipfs_cid}`, where `url` is
+//! read from the offchain local storage under [`ANALYSIS_URL_KEY`](crate::ANALYSIS_URL_KEY).
+//! The service answers `404` until the sample has been analysed, then
+//! `{"bpm": 128.0, "key": "A minor", "quality": 0.87}`, `bpm` and `key` being `null` when
+//! nothing was detected. The worker signs a `submit_analysis` transaction with the result; the
+//! pallet approves the sample if `quality` reaches `MinAnalysisQuality` and rejects it otherwise.
+
+use crate::{pallet::*, ANALYSIS_URL_KEY};
+use frame_support::{traits::Get, BoundedVec};
+use frame_system::offchain::{SendSignedTransaction, Signer};
+use lite_json::{JsonValue, NumberValue};
+use sp_runtime::{
+	offchain::{http, storage::StorageValueRef, Duration, StorageKind},
+	traits::Saturating,
+	Percent,
+};
+use sp_std::vec::Vec;
+
+/// Time the worker waits for the analysis service to answer.
+const HTTP_TIMEOUT_MS: u64 = 2_000;
+
+/// Prefix of the offchain local storage keys recording when a sample was last queried.
+const LAST_QUERY_PREFIX: &[u8] = b"sample-registry::last-query::";
+
+impl<T: Config> Pallet<T> {
+	/// Query the analysis service for up to `MaxAnalysesPerBlock` pending samples and submit
+	/// the results that are available.
+	pub(crate) fn analyse_pending_samples(now: T::BlockNumber) {
+		let base_url = match sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, ANALYSIS_URL_KEY) {
+			Some(url) => url,
+			None => return,
+		};
+		let signer = Signer::<T, T::AuthorityId>::any_account();
+		if !signer.can_sign() {
+			return;
+		}
+
+		let pending = Samples::<T>::iter()
+			.filter(|(_, sample)| sample.status == SampleStatus::Pending)
+			.filter(|(sample_id, _)| Self::claim_query(*sample_id, now))
+			.take(T::MaxAnalysesPerBlock::get() as usize);
+
+		for (sample_id, sample) in pending {
+			let analysis = match Self::fetch_analysis(&base_url, &sample.ipfs_cid) {
+				Ok(Some(analysis)) => analysis,
+				// Not analysed yet, or the service is unavailable: retried after the delay
+				_ => continue,
+			};
+			let _ = signer.send_signed_transaction(|_| Call::submit_analysis { sample_id, analysis: analysis.clone() });
+		}
+	}
+
+	/// Whether `sample_id` is due for a query, recording `now` as its last query if so.
+	pub(crate) fn claim_query(sample_id: u32, now: T::BlockNumber) -> bool {
+		let key = [LAST_QUERY_PREFIX, &sample_id.to_le_bytes()[..]].concat();
+		StorageValueRef::persistent(&key)
+			.mutate(|last: Result<Option<T::BlockNumber>, _>| match last {
+				Ok(Some(last)) if now < last.saturating_add(T::AnalysisRetryDelay::get()) => Err(()),
+				_ => Ok(now),
+			})
+			.is_ok()
+	}
+
+	/// Fetch the analysis of the sample stored under `ipfs_cid`. `None` if the service has
+	/// no result for it yet.
+	fn fetch_analysis(base_url: &[u8], ipfs_cid: &[u8]) -> Result<Option<SampleAnalysis>, http::Error> {
+		let url = [base_url, b"/analysis/", ipfs_cid].concat();
+		let url = sp_std::str::from_utf8(&url).map_err(|_| http::Error::Unknown)?;
+
+		let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT_MS));
+		let pending = http::Request::get(url).deadline(deadline).send().map_err(|_| http::Error::IoError)?;
+		let response = pending.try_wait(deadline).map_err(|_| http::Error::DeadlineReached)??;
+		match response.code {
+			200 => Ok(parse_analysis(&response.body().collect::<Vec<u8>>())),
+			404 => Ok(None),
+			_ => Err(http::Error::Unknown),
+		}
+	}
+}
+
+/// Parse a response body of the analysis service. `None` if it is malformed.
+pub(crate) fn parse_analysis(body: &[u8]) -> Option<SampleAnalysis> {
+	let text = sp_std::str::from_utf8(body).ok()?;
+	let fields = match lite_json::parse_json(text).ok()? {
+		JsonValue::Object(fields) => fields,
+		_ => return None,
+	};
+	let field = |name: &str| {
+		fields.iter().find(|(key, _)| key.iter().copied().eq(name.chars())).map(|(_, value)| value)
+	};
+
+	let bpm = match field("bpm")? {
+		JsonValue::Number(bpm) => Some(scaled(bpm, 1)?.try_into().ok()?),
+		JsonValue::Null => None,
+		_ => return None,
+	};
+	let key = match field("key")? {
+		JsonValue::String(key) => key.iter().fold(Vec::new(), |mut bytes, c| {
+			bytes.extend_from_slice(c.encode_utf8(&mut [0u8; 4]).as_bytes());
+			bytes
+		}),
+		JsonValue::Null => Vec::new(),
+		_ => return None,
+	};
+	let quality = match field("quality")? {
+		JsonValue::Number(quality) => Percent::from_percent(scaled(quality, 100)?.min(100) as u8),
+		_ => return None,
+	};
+
+	Some(SampleAnalysis { bpm, key: BoundedVec::try_from(key).ok()?, quality })
+}
+
+/// `number * scale`, rounded down. `None` for negative numbers or exponents.
+fn scaled(number: &NumberValue, scale: u64) -> Option<u64> {
+	if number.negative || number.exponent != 0 {
+		return None;
+	}
+	let fraction = number
+		.fraction
+		.checked_mul(scale)?
+		.checked_div(10u64.checked_pow(number.fraction_length)?)?;
+	number.integer.checked_mul(scale)?.checked_add(fraction)
+}
@@ -2,14 +2,38 @@
 
 pub use pallet::*;
 
+mod analysis;
+pub mod migrations;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Persistent offchain local storage key holding the base URL of the audio analysis service
+/// as raw bytes, e.g. `http://localhost:8000`. The offchain worker stays idle while it is unset.
+pub const ANALYSIS_URL_KEY: &[u8] = b"sample-registry::analysis-url";
+
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::pallet_prelude::*;
-	use frame_system::pallet_prelude::*;
+	use frame_system::{
+		offchain::{AppCrypto, CreateSignedTransaction},
+		pallet_prelude::*,
+	};
+	use echochain_chunk_proof::Hash;
+	use pallet_reputation::{ReputationEvent, ReputationHandler};
+	use sp_runtime::Percent;
 	use sp_std::vec::Vec;
 
+	/// Version 1 added the chunk commitment to `SampleMetadata`.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
 	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
@@ -19,7 +43,7 @@ pub mod pallet {
 		Rejected,
 	}
 
-	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 	#[scale_info(skip_type_params(T))]
 	pub struct SampleMetadata<T: Config> {
 		pub owner: T::AccountId,
@@ -27,16 +51,63 @@ pub mod pallet {
 		pub metadata_ipfs_cid: Vec<u8>,
 		pub status: SampleStatus,
 		pub created_at: T::BlockNumber,
+		/// Root of the Merkle tree over the SHA-256 hashes of the sample's chunks,
+		/// as built by `echochain_chunk_proof::chunk_root`.
+		pub chunk_root: Hash,
+		/// Size of the sample in bytes.
+		pub total_size: u64,
+		/// Number of `echochain_chunk_proof::CHUNK_SIZE` chunks. Zero for samples registered
+		/// before chunk commitments, which have no usable `chunk_root`.
+		pub chunk_count: u32,
+	}
+
+	/// Maximum length of a musical key in an analysis result, e.g. `"C# minor"`.
+	pub type MaxKeyLength = ConstU32<16>;
+
+	/// Results of the audio analysis service for a sample.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct SampleAnalysis {
+		/// Tempo in beats per minute, if one was detected.
+		pub bpm: Option<u16>,
+		/// Musical key, empty if none was detected.
+		pub key: BoundedVec<u8, MaxKeyLength>,
+		/// Audio quality score.
+		pub quality: Percent,
 	}
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
 		/// The maximum length of an IPFS CID.
 		#[pallet::constant]
 		type MaxIpfsCidLength: Get<u32>;
+
+		/// Notified when a sample is approved or rejected.
+		type Reputation: ReputationHandler<Self::AccountId>;
+
+		/// Key type the offchain worker signs analysis results with.
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+		/// Origin allowed to submit analysis results, e.g. authorized worker keys.
+		type AnalysisOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Origin allowed to set the status of samples and take them down, e.g. a curator
+		/// collective majority.
+		type ModerationOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Minimum analysis quality a sample is approved with.
+		#[pallet::constant]
+		type MinAnalysisQuality: Get<Percent>;
+
+		/// Maximum number of pending samples the offchain worker queries per block.
+		#[pallet::constant]
+		type MaxAnalysesPerBlock: Get<u32>;
+
+		/// Blocks before the offchain worker queries a still pending sample again.
+		#[pallet::constant]
+		type AnalysisRetryDelay: Get<Self::BlockNumber>;
 	}
 
 	#[pallet::storage]
@@ -46,9 +117,19 @@ pub mod pallet {
 		Blake2_128Concat,
 		u32, // Sample ID
 		SampleMetadata<T>,
-		ValueQuery,
+		OptionQuery,
 	>;
 
+	/// Analysis results of samples, by sample ID.
+	#[pallet::storage]
+	#[pallet::getter(fn analyses)]
+	pub type Analyses<T: Config> = StorageMap<_, Blake2_128Concat, u32, SampleAnalysis, OptionQuery>;
+
+	/// Samples disputed for moderation, with the account that disputed them.
+	#[pallet::storage]
+	#[pallet::getter(fn disputes)]
+	pub type Disputes<T: Config> = StorageMap<_, Blake2_128Concat, u32, T::AccountId, OptionQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn next_sample_id)]
 	pub(super) type NextSampleId<T: Config> = StorageValue<_, u32, ValueQuery>;
@@ -57,9 +138,15 @@ pub mod pallet {
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		/// A new sample has been registered.
-		SampleRegistered { sample_id: u32, owner: T::AccountId, ipfs_cid: Vec<u8> },
+		SampleRegistered { sample_id: u32, owner: T::AccountId, ipfs_cid: Vec<u8>, chunk_root: Hash },
 		/// A sample's status has been updated.
 		SampleStatusUpdated { sample_id: u32, new_status: SampleStatus },
+		/// The analysis of a pending sample was recorded.
+		SampleAnalysed { sample_id: u32, analysis: SampleAnalysis, approved: bool },
+		/// A sample was disputed and awaits moderation.
+		SampleDisputed { sample_id: u32, who: T::AccountId },
+		/// A sample was taken down by moderation.
+		SampleTakenDown { sample_id: u32 },
 	}
 
 	#[pallet::error]
@@ -70,17 +157,33 @@ pub mod pallet {
 		SampleNotFound,
 		/// Only the owner or a privileged origin can update the sample status.
 		Unauthorized,
+		/// The chunk count does not match the total size, or the sample is empty.
+		InvalidChunkCount,
+		/// The sample has already been approved or rejected.
+		SampleNotPending,
+		/// The sample is already disputed.
+		AlreadyDisputed,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn offchain_worker(now: BlockNumberFor<T>) {
+			Self::analyse_pending_samples(now);
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Register a new music sample.
+		/// Register a new music sample together with the commitment to its chunks.
 		/// This extrinsic can only be called by a trusted origin (e.g., the backend oracle).
-		#[pallet::weight(10_000 + T::DbWeight::writes(1).ref_time())]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
 		pub fn register_sample(
 			origin: OriginFor<T>,
 			ipfs_cid: Vec<u8>,
 			metadata_ipfs_cid: Vec<u8>,
+			chunk_root: Hash,
+			total_size: u64,
+			chunk_count: u32,
 		) -> DispatchResult {
 			// Ensure that this call can only be made by a trusted origin (e.g., Root or a designated Oracle).
 			// For now, we'll allow Signed, but this should be restricted in a production environment.
@@ -94,6 +197,10 @@ pub mod pallet {
 				metadata_ipfs_cid.len() <= T::MaxIpfsCidLength::get() as usize,
 				Error::<T>::IpfsCidTooLong
 			);
+			ensure!(
+				total_size > 0 && echochain_chunk_proof::chunk_count(total_size) == u64::from(chunk_count),
+				Error::<T>::InvalidChunkCount
+			);
 
 			let sample_id = NextSampleId::<T>::get();
 			let new_sample = SampleMetadata {
@@ -102,34 +209,108 @@ pub mod pallet {
 				metadata_ipfs_cid,
 				status: SampleStatus::Pending,
 				created_at: <frame_system::Pallet<T>>::block_number(),
+				chunk_root,
+				total_size,
+				chunk_count,
 			};
 
-			<Samples<T>>::insert(sample_id, new_sample);
+			<Samples<T>>::insert(sample_id, new_sample.clone());
 			<NextSampleId<T>>::put(sample_id + 1);
 
-			Self::deposit_event(Event::SampleRegistered { sample_id, owner: sender, ipfs_cid: new_sample.ipfs_cid });
+			Self::deposit_event(Event::SampleRegistered { sample_id, owner: sender, ipfs_cid: new_sample.ipfs_cid, chunk_root });
 			Ok(())
 		}
 
-		/// Update the status of a sample (e.g., from Pending to Approved/Rejected).
-		/// This extrinsic should only be callable by a privileged origin (e.g., Root or an Oracle).
-		#[pallet::weight(10_000 + T::DbWeight::writes(1).ref_time())]
+		/// Update the status of a sample (e.g., from Pending to Approved/Rejected), resolving
+		/// any dispute against it. Only callable by `ModerationOrigin`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
 		pub fn update_sample_status(
 			origin: OriginFor<T>,
 			sample_id: u32,
 			new_status: SampleStatus,
 		) -> DispatchResult {
-			// Only Root can update sample status for now. This will be refined with an Oracle pallet.
-			ensure_root(origin)?;
+			T::ModerationOrigin::ensure_origin(origin)?;
+			Self::set_status(sample_id, new_status)?;
+			<Disputes<T>>::remove(sample_id);
+			Ok(())
+		}
+
+		/// Record the audio analysis of a pending sample, approving it if its quality reaches
+		/// `MinAnalysisQuality` and rejecting it otherwise. Submitted by the offchain worker
+		/// from the results of the audio analysis service.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2).ref_time())]
+		pub fn submit_analysis(
+			origin: OriginFor<T>,
+			sample_id: u32,
+			analysis: SampleAnalysis,
+		) -> DispatchResult {
+			T::AnalysisOrigin::ensure_origin(origin)?;
+			let sample = <Samples<T>>::get(sample_id).ok_or(Error::<T>::SampleNotFound)?;
+			ensure!(sample.status == SampleStatus::Pending, Error::<T>::SampleNotPending);
+
+			let approved = analysis.quality >= T::MinAnalysisQuality::get();
+			<Analyses<T>>::insert(sample_id, analysis.clone());
+			let new_status = if approved { SampleStatus::Approved } else { SampleStatus::Rejected };
+			Self::set_status(sample_id, new_status)?;
+
+			Self::deposit_event(Event::SampleAnalysed { sample_id, analysis, approved });
+			Ok(())
+		}
 
-			<Samples<T>>::try_mutate(sample_id, |sample_opt| -> DispatchResult {
+		/// Flag a sample for moderation, e.g. for infringing content or a wrong analysis.
+		/// Curators resolve the dispute with `update_sample_status` or `takedown_sample`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1).ref_time())]
+		pub fn dispute_sample(origin: OriginFor<T>, sample_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(<Samples<T>>::contains_key(sample_id), Error::<T>::SampleNotFound);
+			ensure!(!<Disputes<T>>::contains_key(sample_id), Error::<T>::AlreadyDisputed);
+
+			<Disputes<T>>::insert(sample_id, who.clone());
+			Self::deposit_event(Event::SampleDisputed { sample_id, who });
+			Ok(())
+		}
+
+		/// Remove a sample and its analysis from the registry. The owner's reputation takes
+		/// the penalty of an upheld report. Only callable by `ModerationOrigin`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(3).ref_time())]
+		pub fn takedown_sample(origin: OriginFor<T>, sample_id: u32) -> DispatchResult {
+			T::ModerationOrigin::ensure_origin(origin)?;
+			let sample = <Samples<T>>::take(sample_id).ok_or(Error::<T>::SampleNotFound)?;
+			<Analyses<T>>::remove(sample_id);
+			<Disputes<T>>::remove(sample_id);
+
+			T::Reputation::note(&sample.owner, ReputationEvent::ReportUpheld);
+			Self::deposit_event(Event::SampleTakenDown { sample_id });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn set_status(sample_id: u32, new_status: SampleStatus) -> DispatchResult {
+			let (owner, old_status) = <Samples<T>>::try_mutate(sample_id, |sample_opt| -> Result<_, DispatchError> {
 				let sample = sample_opt.as_mut().ok_or(Error::<T>::SampleNotFound)?;
-				sample.status = new_status.clone();
-				Ok(())
+				let old_status = sp_std::mem::replace(&mut sample.status, new_status.clone());
+				Ok((sample.owner.clone(), old_status))
 			})?;
 
+			if old_status != new_status {
+				match new_status {
+					SampleStatus::Approved => T::Reputation::note(&owner, ReputationEvent::SampleApproved),
+					SampleStatus::Rejected => T::Reputation::note(&owner, ReputationEvent::SampleRejected),
+					SampleStatus::Pending => {},
+				}
+			}
+
 			Self::deposit_event(Event::SampleStatusUpdated { sample_id, new_status });
 			Ok(())
 		}
+
+		/// Chunk root and chunk count of `sample_id`, for storage challenges and verified downloads.
+		/// `None` for samples registered before chunk commitments.
+		pub fn chunk_commitment(sample_id: u32) -> Option<(Hash, u32)> {
+			<Samples<T>>::get(sample_id)
+				.filter(|sample| sample.chunk_count > 0)
+				.map(|sample| (sample.chunk_root, sample.chunk_count))
+		}
 	}
 }
@@ -0,0 +1,66 @@
+//! Storage migrations of the sample registry.
+
+use super::*;
+use frame_support::{
+	pallet_prelude::*,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+use sp_std::vec::Vec;
+
+/// Version 1 added the chunk commitment to `SampleMetadata`.
+pub mod v1 {
+	use super::*;
+
+	/// `SampleMetadata` before version 1.
+	#[derive(Encode, Decode)]
+	pub struct OldSampleMetadata<AccountId, BlockNumber> {
+		pub owner: AccountId,
+		pub ipfs_cid: Vec<u8>,
+		pub metadata_ipfs_cid: Vec<u8>,
+		pub status: SampleStatus,
+		pub created_at: BlockNumber,
+	}
+
+	/// Adds an empty chunk commitment to samples registered before version 1. Without a
+	/// chunk, such samples have no chunk commitment, so they can neither be announced by
+	/// seeders nor challenged.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1);
+			}
+
+			let mut translated = 0u64;
+			Samples::<T>::translate::<OldSampleMetadata<T::AccountId, T::BlockNumber>, _>(|_, old| {
+				translated += 1;
+				Some(SampleMetadata {
+					owner: old.owner,
+					ipfs_cid: old.ipfs_cid,
+					metadata_ipfs_cid: old.metadata_ipfs_cid,
+					status: old.status,
+					created_at: old.created_at,
+					chunk_root: [0; 32],
+					total_size: 0,
+					chunk_count: 0,
+				})
+			});
+			StorageVersion::new(1).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+			Ok((Samples::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+			let count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "storage version not updated");
+			ensure!(Samples::<T>::iter_values().count() as u32 == count, "samples lost in translation");
+			Ok(())
+		}
+	}
+}
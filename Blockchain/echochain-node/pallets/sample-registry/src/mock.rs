@@ -0,0 +1,138 @@
+use crate as pallet_sample_registry;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+};
+use sp_core::{sr25519, H256};
+use sp_runtime::{
+	testing::TestXt,
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
+	BuildStorage, Percent,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// Key type used by the worker keys of the tests.
+pub const KEY_TYPE: sp_core::crypto::KeyTypeId = sp_core::crypto::KeyTypeId(*b"echo");
+
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::{Public as Sr25519Public, Signature as Sr25519Signature};
+	use sp_runtime::app_crypto::{app_crypto, sr25519};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct TestAuthId;
+
+	impl frame_system::offchain::AppCrypto<Sr25519Public, Sr25519Signature> for TestAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = Sr25519Signature;
+		type GenericPublic = Sr25519Public;
+	}
+}
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		WorkerKeys: pallet_worker_keys,
+		SampleRegistry: pallet_sample_registry,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Nonce = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = sr25519::Public;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Block = Block;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
+type AccountId = <<sr25519::Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = <sr25519::Signature as Verify>::Signer;
+	type Signature = sr25519::Signature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		_public: <sr25519::Signature as Verify>::Signer,
+		_account: AccountId,
+		nonce: u64,
+	) -> Option<(RuntimeCall, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+impl pallet_worker_keys::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type WorkerOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxWorkers = ConstU32<3>;
+}
+
+/// Account of the authorized worker key.
+pub fn worker() -> AccountId {
+	sr25519::Public::from_raw([9; 32])
+}
+
+parameter_types! {
+	pub const MinAnalysisQuality: Percent = Percent::from_percent(60);
+}
+
+impl pallet_sample_registry::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type MaxIpfsCidLength = ConstU32<64>;
+	type Reputation = ();
+	type AuthorityId = crypto::TestAuthId;
+	type AnalysisOrigin = pallet_worker_keys::EnsureWorker<Test>;
+	type ModerationOrigin = frame_system::EnsureRoot<AccountId>;
+	type MinAnalysisQuality = MinAnalysisQuality;
+	type MaxAnalysesPerBlock = ConstU32<2>;
+	type AnalysisRetryDelay = ConstU64<10>;
+}
+
+// Build genesis storage according to the mock runtime, with `worker()` authorized.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities = RuntimeGenesisConfig {
+		system: Default::default(),
+		worker_keys: pallet_worker_keys::GenesisConfig { workers: vec![worker()] },
+	}
+	.build_storage()
+	.unwrap()
+	.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
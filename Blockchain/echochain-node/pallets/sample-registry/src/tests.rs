@@ -0,0 +1,283 @@
+use crate::{
+	analysis::parse_analysis, migrations::v1, mock::*, Error, Event, SampleAnalysis, SampleStatus, Samples,
+	ANALYSIS_URL_KEY,
+};
+use codec::Decode;
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::unhashed,
+	traits::{GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion},
+	BoundedVec,
+};
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt},
+	sr25519,
+};
+use sp_keystore::{testing::MemoryKeystore, Keystore, KeystoreExt};
+use sp_runtime::{traits::BadOrigin, Percent};
+use std::sync::Arc;
+
+const CID: &[u8] = b"QmSample";
+const SERVICE_URL: &[u8] = b"http://localhost:8000";
+const ANALYSIS_URI: &str = "http://localhost:8000/analysis/QmSample";
+
+fn owner() -> sr25519::Public {
+	sr25519::Public::from_raw([1; 32])
+}
+
+fn register_sample() -> u32 {
+	let sample_id = SampleRegistry::next_sample_id();
+	assert_ok!(SampleRegistry::register_sample(
+		RuntimeOrigin::signed(owner()),
+		CID.to_vec(),
+		b"QmMetadata".to_vec(),
+		[0; 32],
+		1_000,
+		1,
+	));
+	sample_id
+}
+
+fn analysis(bpm: Option<u16>, key: &[u8], quality: u8) -> SampleAnalysis {
+	SampleAnalysis {
+		bpm,
+		key: BoundedVec::try_from(key.to_vec()).unwrap(),
+		quality: Percent::from_percent(quality),
+	}
+}
+
+#[test]
+fn submitted_analysis_approves_or_rejects_pending_samples() {
+	new_test_ext().execute_with(|| {
+		let approved = register_sample();
+		let rejected = register_sample();
+		let result = analysis(Some(128), b"A minor", 87);
+		let low_quality = analysis(None, b"", 59);
+
+		assert_ok!(SampleRegistry::submit_analysis(RuntimeOrigin::signed(worker()), approved, result.clone()));
+		System::assert_last_event(Event::SampleAnalysed { sample_id: approved, analysis: result.clone(), approved: true }.into());
+		assert_eq!(SampleRegistry::samples(approved).unwrap().status, SampleStatus::Approved);
+		assert_eq!(SampleRegistry::analyses(approved), Some(result.clone()));
+
+		// Below `MinAnalysisQuality`
+		assert_ok!(SampleRegistry::submit_analysis(RuntimeOrigin::signed(worker()), rejected, low_quality.clone()));
+		System::assert_last_event(Event::SampleAnalysed { sample_id: rejected, analysis: low_quality, approved: false }.into());
+		assert_eq!(SampleRegistry::samples(rejected).unwrap().status, SampleStatus::Rejected);
+
+		assert_noop!(
+			SampleRegistry::submit_analysis(RuntimeOrigin::signed(worker()), approved, result.clone()),
+			Error::<Test>::SampleNotPending
+		);
+		assert_noop!(
+			SampleRegistry::submit_analysis(RuntimeOrigin::signed(worker()), 99, result.clone()),
+			Error::<Test>::SampleNotFound
+		);
+		assert_noop!(SampleRegistry::submit_analysis(RuntimeOrigin::none(), rejected, result), BadOrigin);
+	});
+}
+
+#[test]
+fn analysis_is_only_accepted_from_worker_keys() {
+	new_test_ext().execute_with(|| {
+		let sample_id = register_sample();
+		let result = analysis(Some(128), b"A minor", 87);
+
+		// The sample owner cannot approve their own sample
+		assert_noop!(SampleRegistry::submit_analysis(RuntimeOrigin::signed(owner()), sample_id, result.clone()), BadOrigin);
+		assert_noop!(SampleRegistry::submit_analysis(RuntimeOrigin::root(), sample_id, result), BadOrigin);
+		assert_eq!(SampleRegistry::samples(sample_id).unwrap().status, SampleStatus::Pending);
+	});
+}
+
+#[test]
+fn parses_analysis_service_responses() {
+	assert_eq!(
+		parse_analysis(br#"{"bpm": 128.75, "key": "C# minor", "quality": 0.87}"#),
+		Some(analysis(Some(128), "C# minor".as_bytes(), 87))
+	);
+	assert_eq!(
+		parse_analysis(br#"{"bpm": null, "key": null, "quality": 1, "message": "ok"}"#),
+		Some(analysis(None, b"", 100))
+	);
+	assert_eq!(parse_analysis(br#"{"bpm": 120, "key": "A"}"#), None);
+	assert_eq!(parse_analysis(br#"{"bpm": -1, "key": "A", "quality": 0.5}"#), None);
+	assert_eq!(parse_analysis(br#"{"bpm": 120, "key": "a very long key name", "quality": 0.5}"#), None);
+	assert_eq!(parse_analysis(b"not json"), None);
+}
+
+#[test]
+fn queries_are_retried_only_after_the_delay() {
+	let mut ext = new_test_ext();
+	let (offchain, _) = testing::TestOffchainExt::new();
+	ext.register_extension(OffchainDbExt::new(offchain));
+	ext.execute_with(|| {
+		assert!(SampleRegistry::claim_query(0, 1));
+		assert!(!SampleRegistry::claim_query(0, 10));
+		assert!(SampleRegistry::claim_query(1, 10));
+		assert!(SampleRegistry::claim_query(0, 11));
+	});
+}
+
+/// Externalities with offchain, transaction pool and keystore extensions and a worker key.
+fn offchain_test_ext() -> (sp_io::TestExternalities, Arc<parking_lot::RwLock<testing::OffchainState>>, Arc<parking_lot::RwLock<testing::PoolState>>) {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let keystore = MemoryKeystore::new();
+	keystore.sr25519_generate_new(KEY_TYPE, Some("//Worker")).unwrap();
+
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	ext.register_extension(OffchainDbExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt::new(keystore));
+	(ext, offchain_state, pool_state)
+}
+
+fn expect_analysis_request(state: &parking_lot::RwLock<testing::OffchainState>, body: &[u8]) {
+	state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: ANALYSIS_URI.into(),
+		response: Some(body.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+#[test]
+fn offchain_worker_submits_signed_analysis_results() {
+	let (mut ext, offchain_state, pool_state) = offchain_test_ext();
+	expect_analysis_request(&offchain_state, br#"{"bpm": 128.0, "key": "A minor", "quality": 0.87}"#);
+
+	ext.execute_with(|| {
+		let sample_id = register_sample();
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, ANALYSIS_URL_KEY, SERVICE_URL);
+
+		SampleRegistry::offchain_worker(1);
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature.unwrap().0, 0);
+		assert_eq!(
+			tx.call,
+			RuntimeCall::SampleRegistry(crate::Call::submit_analysis {
+				sample_id,
+				analysis: analysis(Some(128), b"A minor", 87),
+			})
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_rejects_low_quality_samples() {
+	let (mut ext, offchain_state, pool_state) = offchain_test_ext();
+	expect_analysis_request(&offchain_state, br#"{"bpm": null, "key": null, "quality": 0.2}"#);
+
+	ext.execute_with(|| {
+		let sample_id = register_sample();
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, ANALYSIS_URL_KEY, SERVICE_URL);
+
+		SampleRegistry::offchain_worker(1);
+
+		let tx = Extrinsic::decode(&mut &*pool_state.write().transactions.pop().unwrap()).unwrap();
+		assert_eq!(
+			tx.call,
+			RuntimeCall::SampleRegistry(crate::Call::submit_analysis {
+				sample_id,
+				analysis: analysis(None, b"", 20),
+			})
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_skips_malformed_results_and_unconfigured_nodes() {
+	let (mut ext, offchain_state, pool_state) = offchain_test_ext();
+	expect_analysis_request(&offchain_state, b"Internal Server Error");
+
+	ext.execute_with(|| {
+		register_sample();
+
+		// No service URL configured: nothing is queried
+		SampleRegistry::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, ANALYSIS_URL_KEY, SERVICE_URL);
+		SampleRegistry::offchain_worker(2);
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn moderation_resolves_disputed_samples() {
+	new_test_ext().execute_with(|| {
+		let sample_id = register_sample();
+		let curator = sr25519::Public::from_raw([2; 32]);
+
+		assert_noop!(SampleRegistry::dispute_sample(RuntimeOrigin::signed(curator), 99), Error::<Test>::SampleNotFound);
+		assert_ok!(SampleRegistry::dispute_sample(RuntimeOrigin::signed(curator), sample_id));
+		System::assert_last_event(Event::SampleDisputed { sample_id, who: curator }.into());
+		assert_eq!(SampleRegistry::disputes(sample_id), Some(curator));
+		assert_noop!(
+			SampleRegistry::dispute_sample(RuntimeOrigin::signed(owner()), sample_id),
+			Error::<Test>::AlreadyDisputed
+		);
+
+		assert_noop!(
+			SampleRegistry::update_sample_status(RuntimeOrigin::signed(curator), sample_id, SampleStatus::Rejected),
+			BadOrigin
+		);
+		assert_ok!(SampleRegistry::update_sample_status(RuntimeOrigin::root(), sample_id, SampleStatus::Rejected));
+		assert_eq!(SampleRegistry::samples(sample_id).unwrap().status, SampleStatus::Rejected);
+		assert_eq!(SampleRegistry::disputes(sample_id), None);
+	});
+}
+
+#[test]
+fn moderation_takes_samples_down() {
+	new_test_ext().execute_with(|| {
+		let sample_id = register_sample();
+		assert_ok!(SampleRegistry::submit_analysis(
+			RuntimeOrigin::signed(worker()),
+			sample_id,
+			analysis(Some(120), b"A", 90),
+		));
+		assert_ok!(SampleRegistry::dispute_sample(RuntimeOrigin::signed(owner()), sample_id));
+
+		assert_noop!(SampleRegistry::takedown_sample(RuntimeOrigin::signed(owner()), sample_id), BadOrigin);
+		assert_ok!(SampleRegistry::takedown_sample(RuntimeOrigin::root(), sample_id));
+		System::assert_last_event(Event::SampleTakenDown { sample_id }.into());
+		assert_eq!(SampleRegistry::samples(sample_id), None);
+		assert_eq!(SampleRegistry::analyses(sample_id), None);
+		assert_eq!(SampleRegistry::disputes(sample_id), None);
+		assert_noop!(SampleRegistry::takedown_sample(RuntimeOrigin::root(), sample_id), Error::<Test>::SampleNotFound);
+	});
+}
+
+#[test]
+fn samples_registered_before_chunk_commitments_are_migrated() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<SampleRegistry>();
+		let old = v1::OldSampleMetadata {
+			owner: owner(),
+			ipfs_cid: CID.to_vec(),
+			metadata_ipfs_cid: b"QmMetadata".to_vec(),
+			status: SampleStatus::Approved,
+			created_at: 1u64,
+		};
+		unhashed::put(&Samples::<Test>::hashed_key_for(0), &old);
+
+		v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(SampleRegistry::on_chain_storage_version(), 1);
+		let sample = SampleRegistry::samples(0).unwrap();
+		assert_eq!((sample.owner, sample.status, sample.created_at), (owner(), SampleStatus::Approved, 1));
+		assert_eq!((sample.chunk_root, sample.total_size, sample.chunk_count), ([0; 32], 0, 0));
+		// Without a chunk commitment the sample cannot be challenged
+		assert_eq!(SampleRegistry::chunk_commitment(0), None);
+
+		// Registered samples keep their commitment, and the migration only runs once
+		let sample_id = register_sample();
+		v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(SampleRegistry::chunk_commitment(sample_id), Some(([0; 32], 1)));
+	});
+}
@@ -0,0 +1,17 @@
+//! Runtime API for querying the ECHO emission schedule.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait TokenomicsApi<Balance> where Balance: Codec {
+		/// The uncapped emission of the given period according to the emission curve.
+		fn emission_for_period(period: u32) -> Balance;
+		/// Emissions of the next `periods` periods, starting with the current one,
+		/// taking the issuance cap into account.
+		fn projected_emissions(periods: u32) -> Vec<Balance>;
+		/// Amount that can still be issued before the hard cap is reached.
+		fn remaining_issuance() -> Balance;
+	}
+}
@@ -0,0 +1,185 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Tokenomics Pallet
+//!
+//! Defines the ECHO emission schedule. At the start of every emission period the pallet
+//! mints that period's emission and splits it between the configured reward pools, the
+//! remainder going to the treasury. Reward pallets pay out of these accounts instead of
+//! minting on their own, so total issuance never exceeds `MaxTotalIssuance`.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_support::traits::{Currency, Imbalance};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::{
+		traits::{Saturating, UniqueSaturatedInto, Zero},
+		Perbill,
+	};
+	use sp_std::prelude::*;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// Shape of the per-period emission.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum EmissionCurve {
+		/// The emission halves every `interval` periods.
+		Halving { interval: u32 },
+		/// The emission shrinks by `decay` every period.
+		Decaying { decay: Perbill },
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency mechanism.
+		type Currency: Currency<Self::AccountId>;
+
+		/// Length of an emission period in blocks.
+		#[pallet::constant]
+		type EmissionPeriod: Get<BlockNumberFor<Self>>;
+
+		/// Emission of the first period.
+		#[pallet::constant]
+		type InitialEmission: Get<BalanceOf<Self>>;
+
+		/// How the emission evolves from one period to the next.
+		#[pallet::constant]
+		type Curve: Get<EmissionCurve>;
+
+		/// Hard cap on the total issuance. Emissions stop once it is reached.
+		#[pallet::constant]
+		type MaxTotalIssuance: Get<BalanceOf<Self>>;
+
+		/// Reward pool accounts and their share of every emission.
+		type RewardPools: Get<Vec<(Self::AccountId, Perbill)>>;
+
+		/// Receives the part of every emission not assigned to a reward pool.
+		#[pallet::constant]
+		type TreasuryAccount: Get<Self::AccountId>;
+	}
+
+	/// Index of the next period to be emitted.
+	#[pallet::storage]
+	#[pallet::getter(fn current_period)]
+	pub type CurrentPeriod<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Total amount minted by this pallet so far.
+	#[pallet::storage]
+	#[pallet::getter(fn total_emitted)]
+	pub type TotalEmitted<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// Block of the last emission.
+	#[pallet::storage]
+	#[pallet::getter(fn last_emission_block)]
+	pub type LastEmissionBlock<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The emission of a period was minted.
+		PeriodEmitted { period: u32, amount: BalanceOf<T> },
+		/// An account was funded from the emission of a period.
+		PoolFunded { period: u32, pool: T::AccountId, amount: BalanceOf<T> },
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			if n.is_zero() || !(n % T::EmissionPeriod::get()).is_zero() {
+				return Weight::zero();
+			}
+			Self::emit(n)
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Uncapped emission of `period` according to `Curve`.
+		pub fn emission_for_period(period: u32) -> BalanceOf<T> {
+			let initial = T::InitialEmission::get();
+			match T::Curve::get() {
+				EmissionCurve::Halving { interval } => {
+					let halvings = period.checked_div(interval).unwrap_or(0);
+					let initial: u128 = initial.unique_saturated_into();
+					initial.checked_shr(halvings).unwrap_or(0).unique_saturated_into()
+				},
+				EmissionCurve::Decaying { decay } =>
+					Perbill::one().saturating_sub(decay).saturating_pow(period as usize) * initial,
+			}
+		}
+
+		/// Amount that can still be minted before `MaxTotalIssuance` is reached.
+		pub fn remaining_issuance() -> BalanceOf<T> {
+			T::MaxTotalIssuance::get().saturating_sub(T::Currency::total_issuance())
+		}
+
+		/// Emissions of the next `periods` periods, starting with `CurrentPeriod`, capped so
+		/// that total issuance stays below `MaxTotalIssuance`.
+		pub fn projected_emissions(periods: u32) -> Vec<BalanceOf<T>> {
+			let start = CurrentPeriod::<T>::get();
+			let mut remaining = Self::remaining_issuance();
+			(start..start.saturating_add(periods))
+				.map(|period| {
+					let amount = Self::emission_for_period(period).min(remaining);
+					remaining = remaining.saturating_sub(amount);
+					amount
+				})
+				.collect()
+		}
+
+		/// Mint the emission of `CurrentPeriod` into the reward pools and the treasury. A share
+		/// that cannot be minted, e.g. below the existential deposit of a new account, goes to
+		/// the treasury instead, and only what was actually minted counts as emitted.
+		fn emit(n: BlockNumberFor<T>) -> Weight {
+			let period = CurrentPeriod::<T>::get();
+			let amount = Self::emission_for_period(period).min(Self::remaining_issuance());
+			let pools = T::RewardPools::get();
+
+			let mut distributed: BalanceOf<T> = Zero::zero();
+			for (pool, share) in pools.iter() {
+				let funded = (*share * amount).min(amount.saturating_sub(distributed));
+				if funded.is_zero() {
+					continue;
+				}
+				let minted = T::Currency::deposit_creating(pool, funded).peek();
+				if minted.is_zero() {
+					continue;
+				}
+				distributed = distributed.saturating_add(minted);
+				Self::deposit_event(Event::PoolFunded { period, pool: pool.clone(), amount: minted });
+			}
+			let rest = amount.saturating_sub(distributed);
+			if !rest.is_zero() {
+				let treasury = T::TreasuryAccount::get();
+				let minted = T::Currency::deposit_creating(&treasury, rest).peek();
+				if !minted.is_zero() {
+					distributed = distributed.saturating_add(minted);
+					Self::deposit_event(Event::PoolFunded { period, pool: treasury, amount: minted });
+				}
+			}
+
+			TotalEmitted::<T>::mutate(|total| *total = total.saturating_add(distributed));
+			CurrentPeriod::<T>::put(period.saturating_add(1));
+			LastEmissionBlock::<T>::put(n);
+			Self::deposit_event(Event::PeriodEmitted { period, amount: distributed });
+
+			// Period, total issuance and one balance per funded account
+			let funded_accounts = pools.len() as u64 + 1;
+			T::DbWeight::get().reads_writes(2 + funded_accounts, 3 + funded_accounts)
+		}
+	}
+}
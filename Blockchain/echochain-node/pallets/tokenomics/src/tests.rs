@@ -0,0 +1,96 @@
+use crate::{mock::*, EmissionCurve, Event};
+use frame_support::traits::{Currency, Hooks};
+use sp_runtime::Perbill;
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		let next = System::block_number() + 1;
+		System::set_block_number(next);
+		Tokenomics::on_initialize(next);
+	}
+}
+
+#[test]
+fn halving_curve_halves_every_interval() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Tokenomics::emission_for_period(0), 1_000);
+		assert_eq!(Tokenomics::emission_for_period(1), 1_000);
+		assert_eq!(Tokenomics::emission_for_period(2), 500);
+		assert_eq!(Tokenomics::emission_for_period(5), 250);
+		assert_eq!(Tokenomics::emission_for_period(u32::MAX), 0);
+	});
+}
+
+#[test]
+fn decaying_curve_shrinks_every_period() {
+	new_test_ext().execute_with(|| {
+		Curve::set(EmissionCurve::Decaying { decay: Perbill::from_percent(10) });
+		assert_eq!(Tokenomics::emission_for_period(0), 1_000);
+		assert_eq!(Tokenomics::emission_for_period(1), 900);
+		assert_eq!(Tokenomics::emission_for_period(2), 810);
+	});
+}
+
+#[test]
+fn emission_funds_pools_and_treasury() {
+	new_test_ext().execute_with(|| {
+		run_to_block(9);
+		assert_eq!(Balances::total_issuance(), 0);
+
+		run_to_block(10);
+		assert_eq!(Balances::free_balance(CONTENT_POOL), 500);
+		assert_eq!(Balances::free_balance(NETWORK_POOL), 300);
+		assert_eq!(Balances::free_balance(TREASURY), 200);
+		assert_eq!(Tokenomics::current_period(), 1);
+		assert_eq!(Tokenomics::total_emitted(), 1_000);
+		assert_eq!(Tokenomics::last_emission_block(), 10);
+		System::assert_has_event(Event::PeriodEmitted { period: 0, amount: 1_000 }.into());
+
+		run_to_block(30);
+		assert_eq!(Tokenomics::current_period(), 3);
+		assert_eq!(Balances::total_issuance(), 2_500);
+	});
+}
+
+#[test]
+fn shares_below_the_existential_deposit_are_not_counted_as_emitted() {
+	new_test_ext().execute_with(|| {
+		// The network pool's 300 cannot create its account and goes to the treasury
+		ExistentialDeposit::set(400);
+		run_to_block(10);
+		assert_eq!(Balances::free_balance(CONTENT_POOL), 500);
+		assert_eq!(Balances::free_balance(NETWORK_POOL), 0);
+		assert_eq!(Balances::free_balance(TREASURY), 500);
+		assert_eq!(Tokenomics::total_emitted(), 1_000);
+	});
+
+	new_test_ext().execute_with(|| {
+		// Not even the treasury's share can be minted
+		ExistentialDeposit::set(2_000);
+		run_to_block(10);
+		assert_eq!(Balances::total_issuance(), 0);
+		assert_eq!(Tokenomics::total_emitted(), 0);
+		System::assert_has_event(Event::PeriodEmitted { period: 0, amount: 0 }.into());
+	});
+}
+
+#[test]
+fn emission_stops_at_issuance_cap() {
+	new_test_ext().execute_with(|| {
+		MaxTotalIssuance::set(1_800);
+		assert_eq!(Tokenomics::projected_emissions(4), vec![1_000, 800, 0, 0]);
+
+		run_to_block(40);
+		assert_eq!(Balances::total_issuance(), 1_800);
+		assert_eq!(Tokenomics::remaining_issuance(), 0);
+		assert_eq!(Tokenomics::projected_emissions(2), vec![0, 0]);
+	});
+}
+
+#[test]
+fn projections_start_at_the_current_period() {
+	new_test_ext().execute_with(|| {
+		run_to_block(20);
+		assert_eq!(Tokenomics::projected_emissions(3), vec![500, 500, 250]);
+	});
+}
@@ -0,0 +1,184 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Worker Keys Pallet
+//!
+//! Defines the `echo` key type EchoChain offchain workers sign their payloads with, and keeps
+//! the governance-controlled set of worker keys whose payloads the reward pallets accept.
+//! The pallet implements [`Contains`](frame_support::traits::Contains) for any public key
+//! resolving to a worker account, so it can be plugged into their `Workers` config item.
+//!
+//! Node operators add their key to the node's keystore with
+//! `echochain-node key insert --key-type echo --scheme sr25519 --suri <secret>`.
+
+pub use pallet::*;
+
+use sp_core::crypto::KeyTypeId;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Key type of EchoChain offchain-worker keys.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"echo");
+
+/// sr25519 application crypto of offchain-worker keys.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::{Public as Sr25519Public, Signature as Sr25519Signature};
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		MultiSignature, MultiSigner,
+	};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Signs offchain-worker payloads with `echo` keys from the node's keystore.
+	pub struct WorkerAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for WorkerAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = Sr25519Signature;
+		type GenericPublic = Sr25519Public;
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{pallet_prelude::*, traits::Contains};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::IdentifyAccount;
+	use sp_std::prelude::*;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Origin allowed to add and remove worker keys.
+		type WorkerOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Maximum number of authorized worker keys.
+		#[pallet::constant]
+		type MaxWorkers: Get<u32>;
+	}
+
+	/// Accounts of the authorized offchain-worker keys.
+	#[pallet::storage]
+	#[pallet::getter(fn workers)]
+	pub type Workers<T: Config> = StorageValue<_, BoundedVec<T::AccountId, T::MaxWorkers>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	#[derive(frame_support::DefaultNoBound)]
+	pub struct GenesisConfig<T: Config> {
+		/// Worker keys authorized from genesis, e.g. the dev authorities.
+		pub workers: Vec<T::AccountId>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			let mut workers = self.workers.clone();
+			workers.sort();
+			workers.dedup();
+			let workers: BoundedVec<_, T::MaxWorkers> =
+				workers.try_into().expect("genesis workers exceed MaxWorkers");
+			Workers::<T>::put(workers);
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A worker key was authorized.
+		WorkerAdded { who: T::AccountId },
+		/// A worker key was revoked.
+		WorkerRemoved { who: T::AccountId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The key is already an authorized worker.
+		AlreadyWorker,
+		/// The key is not an authorized worker.
+		NotWorker,
+		/// `MaxWorkers` keys are already authorized.
+		TooManyWorkers,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Authorize the worker key with account `who`.
+		#[pallet::call_index(0)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn add_worker(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::WorkerOrigin::ensure_origin(origin)?;
+
+			Workers::<T>::try_mutate(|workers| -> DispatchResult {
+				let index = workers.binary_search(&who).err().ok_or(Error::<T>::AlreadyWorker)?;
+				workers.try_insert(index, who.clone()).map_err(|_| Error::<T>::TooManyWorkers)?;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::WorkerAdded { who });
+			Ok(())
+		}
+
+		/// Revoke the worker key with account `who`.
+		#[pallet::call_index(1)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1).ref_time())]
+		pub fn remove_worker(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::WorkerOrigin::ensure_origin(origin)?;
+
+			Workers::<T>::try_mutate(|workers| -> DispatchResult {
+				let index = workers.binary_search(&who).map_err(|_| Error::<T>::NotWorker)?;
+				workers.remove(index);
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::WorkerRemoved { who });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Whether `who` is the account of an authorized worker key.
+		pub fn is_worker(who: &T::AccountId) -> bool {
+			Self::workers().binary_search(who).is_ok()
+		}
+	}
+
+	/// Origin of transactions signed by an authorized worker key, e.g. the analysis results of
+	/// the sample registry's offchain worker. Succeeds with the worker account.
+	pub struct EnsureWorker<T>(PhantomData<T>);
+
+	impl<T: Config> EnsureOrigin<T::RuntimeOrigin> for EnsureWorker<T> {
+		type Success = T::AccountId;
+
+		fn try_origin(o: T::RuntimeOrigin) -> Result<Self::Success, T::RuntimeOrigin> {
+			o.into().and_then(|o| match o {
+				frame_system::RawOrigin::Signed(who) if Pallet::<T>::is_worker(&who) => Ok(who),
+				o => Err(T::RuntimeOrigin::from(o)),
+			})
+		}
+
+		#[cfg(feature = "runtime-benchmarks")]
+		fn try_successful_origin() -> Result<T::RuntimeOrigin, ()> {
+			Pallet::<T>::workers().first().cloned().map(|who| frame_system::RawOrigin::Signed(who).into()).ok_or(())
+		}
+	}
+
+	/// Lets the reward pallets check the signer of an offchain-worker payload.
+	impl<T: Config, Public> Contains<Public> for Pallet<T>
+	where
+		Public: IdentifyAccount<AccountId = T::AccountId> + Clone,
+	{
+		fn contains(public: &Public) -> bool {
+			Self::is_worker(&public.clone().into_account())
+		}
+	}
+}
@@ -0,0 +1,60 @@
+use crate::{mock::*, EnsureWorker, Error, Event};
+use frame_support::{assert_noop, assert_ok, traits::{Contains, EnsureOrigin}};
+use sp_runtime::traits::{BadOrigin, IdentifyAccount};
+
+/// Public key resolving to the account with the same number.
+#[derive(Clone)]
+struct TestPublic(u64);
+
+impl IdentifyAccount for TestPublic {
+	type AccountId = u64;
+
+	fn into_account(self) -> u64 {
+		self.0
+	}
+}
+
+#[test]
+fn genesis_workers_are_sorted_and_deduplicated() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(WorkerKeys::workers().into_inner(), vec![1, 3]);
+		assert!(WorkerKeys::is_worker(&1));
+		assert!(!WorkerKeys::is_worker(&2));
+	});
+}
+
+#[test]
+fn governance_adds_and_removes_workers() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(WorkerKeys::add_worker(RuntimeOrigin::signed(1), 2), BadOrigin);
+
+		assert_ok!(WorkerKeys::add_worker(RuntimeOrigin::root(), 2));
+		System::assert_last_event(Event::WorkerAdded { who: 2 }.into());
+		assert_eq!(WorkerKeys::workers().into_inner(), vec![1, 2, 3]);
+		assert_noop!(WorkerKeys::add_worker(RuntimeOrigin::root(), 2), Error::<Test>::AlreadyWorker);
+		assert_noop!(WorkerKeys::add_worker(RuntimeOrigin::root(), 4), Error::<Test>::TooManyWorkers);
+
+		assert_ok!(WorkerKeys::remove_worker(RuntimeOrigin::root(), 1));
+		System::assert_last_event(Event::WorkerRemoved { who: 1 }.into());
+		assert_eq!(WorkerKeys::workers().into_inner(), vec![2, 3]);
+		assert_noop!(WorkerKeys::remove_worker(RuntimeOrigin::root(), 1), Error::<Test>::NotWorker);
+	});
+}
+
+#[test]
+fn contains_resolves_public_keys_to_worker_accounts() {
+	new_test_ext().execute_with(|| {
+		assert!(<WorkerKeys as Contains<TestPublic>>::contains(&TestPublic(3)));
+		assert!(!<WorkerKeys as Contains<TestPublic>>::contains(&TestPublic(2)));
+	});
+}
+
+#[test]
+fn ensure_worker_accepts_only_signed_worker_origins() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(EnsureWorker::<Test>::try_origin(RuntimeOrigin::signed(1)).ok(), Some(1));
+		assert!(EnsureWorker::<Test>::try_origin(RuntimeOrigin::signed(2)).is_err());
+		assert!(EnsureWorker::<Test>::try_origin(RuntimeOrigin::root()).is_err());
+		assert!(EnsureWorker::<Test>::try_origin(RuntimeOrigin::none()).is_err());
+	});
+}
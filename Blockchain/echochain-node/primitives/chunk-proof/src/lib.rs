@@ -0,0 +1,161 @@
+//! Chunk Merkle trees of EchoChain samples.
+//!
+//! The P2P node splits a sample into [`CHUNK_SIZE`] byte chunks and identifies each chunk
+//! by its SHA-256 hash. The chunk root registered on chain commits to these hashes:
+//!
+//! - leaves are `sha256(chunk)`, in chunk order;
+//! - an inner node is `sha256(left ++ right)`;
+//! - an unpaired node at the end of a level is promoted to the next level as is.
+//!
+//! A proof lists the sibling hashes from the leaf up to the root. Which side each sibling
+//! is on follows from the chunk index, so a proof is only valid for one index.
+//!
+//! The crate is `no_std` and does not depend on Substrate, so P2P clients can build and
+//! check proofs with exactly the code the runtime uses.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+
+/// A node of the chunk tree.
+pub type Hash = [u8; 32];
+
+/// Size of every chunk but the last, matching `chunk_size` in `p2p_node.py`.
+pub const CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Maximum number of siblings in a proof. Chunk counts are `u32`, so no tree is deeper.
+pub const MAX_PROOF_LENGTH: u32 = 32;
+
+/// Number of chunks of a sample of `total_size` bytes.
+pub fn chunk_count(total_size: u64) -> u64 {
+    total_size.div_ceil(CHUNK_SIZE)
+}
+
+/// Leaf of the chunk tree for `chunk`.
+pub fn chunk_leaf(chunk: &[u8]) -> Hash {
+    Sha256::digest(chunk).into()
+}
+
+fn hash_children(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_children(left, right),
+            [single] => *single,
+            _ => unreachable!("chunks(2) yields one or two nodes; qed"),
+        })
+        .collect()
+}
+
+/// Root of the chunk tree over `leaves`. The root of an empty tree is all zeroes.
+pub fn chunk_root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Proof that `leaves[index]` is part of `chunk_root(leaves)`.
+pub fn chunk_proof(leaves: &[Hash], index: usize) -> Vec<Hash> {
+    let mut proof = Vec::new();
+    if index >= leaves.len() {
+        return proof;
+    }
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+/// Check that `leaf` is chunk `index` of a tree with `chunk_count` leaves and root `root`.
+pub fn verify_chunk_proof(root: &Hash, chunk_count: u32, index: u32, leaf: Hash, proof: &[Hash]) -> bool {
+    if index >= chunk_count {
+        return false;
+    }
+    let mut siblings = proof.iter();
+    let mut node = leaf;
+    let mut index = index;
+    let mut width = chunk_count;
+    while width > 1 {
+        let promoted = index == width - 1 && width % 2 == 1;
+        if !promoted {
+            let sibling = match siblings.next() {
+                Some(sibling) => sibling,
+                None => return false,
+            };
+            node = if index % 2 == 0 {
+                hash_children(&node, sibling)
+            } else {
+                hash_children(sibling, &node)
+            };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    siblings.next().is_none() && &node == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proofs_verify_for_every_tree_shape() {
+        let chunks: [&[u8]; 7] = [b"a", b"b", b"c", b"d", b"e", b"f", b"g"];
+        for count in 1..=chunks.len() {
+            let leaves = chunks[..count].iter().map(|chunk| chunk_leaf(chunk)).collect::<Vec<_>>();
+            let root = chunk_root(&leaves);
+            for index in 0..count {
+                let proof = chunk_proof(&leaves, index);
+                assert!(verify_chunk_proof(&root, count as u32, index as u32, leaves[index], &proof));
+                assert!(!verify_chunk_proof(&root, count as u32, index as u32, [0u8; 32], &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn proof_is_bound_to_its_index() {
+        let leaves = [b"a", b"b", b"c", b"d"].iter().map(|chunk| chunk_leaf(*chunk)).collect::<Vec<_>>();
+        let root = chunk_root(&leaves);
+        assert!(!verify_chunk_proof(&root, 4, 1, leaves[0], &chunk_proof(&leaves, 0)));
+        assert!(!verify_chunk_proof(&root, 4, 4, leaves[0], &chunk_proof(&leaves, 0)));
+    }
+
+    #[test]
+    fn root_matches_p2p_node() {
+        // `P2PNode._chunk_root([b"a", b"b", b"c"])` in p2p_node.py
+        let leaves = [b"a", b"b", b"c"].iter().map(|chunk| chunk_leaf(*chunk)).collect::<Vec<_>>();
+        let expected = "7075152d03a5cd92104887b476862778ec0c87be5c2fa1c0a90f87c49fad6eff";
+        let hex = chunk_root(&leaves).iter().map(|byte| alloc::format!("{:02x}", byte)).collect::<alloc::string::String>();
+        assert_eq!(hex, expected);
+    }
+
+    #[test]
+    fn chunk_count_rounds_up() {
+        assert_eq!(chunk_count(0), 0);
+        assert_eq!(chunk_count(1), 1);
+        assert_eq!(chunk_count(CHUNK_SIZE), 1);
+        assert_eq!(chunk_count(CHUNK_SIZE + 1), 2);
+    }
+}
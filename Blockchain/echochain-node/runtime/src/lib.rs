@@ -13,7 +13,8 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount,
+		NumberFor, One, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
@@ -36,7 +37,7 @@ pub use frame_support::{
 		},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
@@ -50,6 +51,20 @@ pub use sp_runtime::{Perbill, Permill};
 pub use pallet_template;
 /// Import the Proof-of-Contribution pallet.
 pub use pallet_proof_of_contribution;
+/// Import the Tokenomics pallet.
+pub use pallet_tokenomics;
+/// Import the Reputation pallet.
+pub use pallet_reputation;
+/// Import the Worker Keys pallet.
+pub use pallet_worker_keys;
+/// Import the Parameters pallet.
+pub use pallet_parameters;
+/// Import the Creator Grants pallet.
+pub use pallet_creator_grants;
+/// Import the Reward Vesting pallet.
+pub use pallet_reward_vesting;
+
+mod migrations;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -106,7 +121,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -259,6 +274,23 @@ parameter_types! {
 	pub FeeMultiplier: Multiplier = Multiplier::one();
 }
 
+/// Sends transaction fees and tips to the treasury, funding spends and creator grants.
+pub struct DealWithFees;
+
+impl frame_support::traits::OnUnbalanced<pallet_balances::NegativeImbalance<Runtime>> for DealWithFees {
+	fn on_unbalanceds<B>(
+		mut fees_then_tips: impl Iterator<Item = pallet_balances::NegativeImbalance<Runtime>>,
+	) {
+		use frame_support::traits::{Imbalance, OnUnbalanced};
+		if let Some(mut fees) = fees_then_tips.next() {
+			if let Some(tips) = fees_then_tips.next() {
+				tips.merge_into(&mut fees);
+			}
+			Treasury::on_unbalanced(fees);
+		}
+	}
+}
+
 impl pallet_transaction_payment::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type OnChargeTransaction = CurrencyAdapter<Balances, DealWithFees>;
@@ -274,25 +306,356 @@ impl pallet_transaction_payment::Config for Runtime {
 	>;
 }
 
+parameter_types! {
+	pub const ReputationMinMultiplier: Perbill = Perbill::from_percent(25);
+	pub const ReputationDecayRate: Perbill = Perbill::from_percent(5);
+}
+
+impl pallet_reputation::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ReportOrigin = RootOrCuratorMajority;
+	type ApprovalBonus = ConstU32<50>;
+	type RejectionPenalty = ConstU32<100>;
+	type UpheldReportPenalty = ConstU32<500>;
+	type SeedingBonus = ConstU32<10>;
+	type SeedingPenalty = ConstU32<50>;
+	type MaxScore = ConstU32<1_000>;
+	type MinMultiplier = ReputationMinMultiplier;
+	type DecayPeriod = ConstU32<{ 7 * DAYS }>;
+	type DecayRate = ReputationDecayRate;
+}
+
+type CuratorCollectiveInstance = pallet_collective::Instance1;
+
+/// Origin of a two-thirds majority of the curator collective.
+pub type CuratorMajority =
+	pallet_collective::EnsureProportionAtLeast<AccountId, CuratorCollectiveInstance, 2, 3>;
+
+/// Root, or a two-thirds majority of the curator collective.
+pub type RootOrCuratorMajority =
+	frame_support::traits::EitherOfDiverse<frame_system::EnsureRoot<AccountId>, CuratorMajority>;
+
+/// Origin of a three-quarters majority of the curator collective.
+pub type CuratorSuperMajority =
+	pallet_collective::EnsureProportionAtLeast<AccountId, CuratorCollectiveInstance, 3, 4>;
+
+parameter_types! {
+	pub const CuratorMotionDuration: BlockNumber = 3 * DAYS;
+	pub const CuratorMaxProposals: u32 = 100;
+	pub const CuratorMaxMembers: u32 = 13;
+	pub MaxCuratorProposalWeight: Weight = Perbill::from_percent(50) * BlockWeights::get().max_block;
+}
+
+impl pallet_collective::Config<CuratorCollectiveInstance> for Runtime {
+	type RuntimeOrigin = RuntimeOrigin;
+	type Proposal = RuntimeCall;
+	type RuntimeEvent = RuntimeEvent;
+	type MotionDuration = CuratorMotionDuration;
+	type MaxProposals = CuratorMaxProposals;
+	type MaxMembers = CuratorMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+	// Members are elected by `CuratorElections`
+	type SetMembersOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxProposalWeight = MaxCuratorProposalWeight;
+}
+
+parameter_types! {
+	pub const CuratorElectionsPalletId: frame_support::traits::LockIdentifier = *b"echo/cur";
+	pub const CandidacyBond: Balance = 1_000_000_000_000_000; // 1k ECHO
+	pub const VotingBondBase: Balance = 10_000_000_000_000; // 10 ECHO
+	pub const VotingBondFactor: Balance = 1_000_000_000_000; // 1 ECHO
+	// Must not exceed `CuratorMaxMembers`
+	pub const DesiredCurators: u32 = 7;
+	pub const DesiredRunnersUp: u32 = 7;
+	pub const CuratorTermDuration: BlockNumber = 28 * DAYS;
+	pub const MaxCuratorCandidates: u32 = 64;
+	pub const MaxCuratorVoters: u32 = 512;
+	pub const MaxVotesPerVoter: u32 = 16;
+}
+
+impl pallet_elections_phragmen::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type PalletId = CuratorElectionsPalletId;
+	type Currency = Balances;
+	type ChangeMembers = CuratorCollective;
+	type InitializeMembers = CuratorCollective;
+	type CurrencyToVote = sp_staking::currency_to_vote::U128CurrencyToVote;
+	type CandidacyBond = CandidacyBond;
+	type VotingBondBase = VotingBondBase;
+	type VotingBondFactor = VotingBondFactor;
+	type LoserCandidate = ();
+	type KickedMember = ();
+	type DesiredMembers = DesiredCurators;
+	type DesiredRunnersUp = DesiredRunnersUp;
+	type TermDuration = CuratorTermDuration;
+	type MaxCandidates = MaxCuratorCandidates;
+	type MaxVoters = MaxCuratorVoters;
+	type MaxVotesPerVoter = MaxVotesPerVoter;
+	type WeightInfo = pallet_elections_phragmen::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const PreimageBaseDeposit: Balance = 1_000_000_000_000; // 1 ECHO
+	pub const PreimageByteDeposit: Balance = 1_000_000_000; // 0.001 ECHO
+}
+
+impl pallet_preimage::Config for Runtime {
+	type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type ManagerOrigin = RootOrCuratorMajority;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+}
+
+/// Root calls without sudo: a three-quarters curator majority whitelists the hash of a call,
+/// e.g. `System::set_code`, which anyone can then dispatch with Root from its preimage.
+impl pallet_whitelist::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type WhitelistOrigin = frame_support::traits::EitherOfDiverse<
+		frame_system::EnsureRoot<AccountId>,
+		CuratorSuperMajority,
+	>;
+	type DispatchWhitelistedOrigin = frame_system::EnsureSigned<AccountId>;
+	type Preimages = Preimage;
+	type WeightInfo = pallet_whitelist::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const MinAnalysisQuality: sp_runtime::Percent = sp_runtime::Percent::from_percent(60);
+}
+
 impl pallet_sample_registry::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type MaxIpfsCidLength = ConstU32<256>;
+	type Reputation = Reputation;
+	type AuthorityId = pallet_worker_keys::crypto::WorkerAuthId;
+	type AnalysisOrigin = pallet_worker_keys::EnsureWorker<Runtime>;
+	type ModerationOrigin = RootOrCuratorMajority;
+	type MinAnalysisQuality = MinAnalysisQuality;
+	type MaxAnalysesPerBlock = ConstU32<5>;
+	type AnalysisRetryDelay = ConstU32<{ 10 * MINUTES }>;
 }
 
-impl pallet_proof_of_contribution::Config for Runtime {
+parameter_types! {
+	pub const TreasuryPalletId: PalletId = PalletId(*b"echo/trs");
+	pub const ContentRewardsPalletId: PalletId = PalletId(*b"echo/crw");
+	pub const NetworkRewardsPalletId: PalletId = PalletId(*b"echo/nrw");
+	pub const CreatorGrantsPalletId: PalletId = PalletId(*b"echo/grt");
+	pub const RewardVestingPalletId: PalletId = PalletId(*b"echo/vst");
+	// Pot of `pallet_treasury`, which derives its account from the same pallet ID
+	pub TreasuryAccount: AccountId = TreasuryPalletId::get().into_account_truncating();
+	pub ContentRewardPoolAccount: AccountId = ContentRewardsPalletId::get().into_account_truncating();
+	pub NetworkRewardPoolAccount: AccountId = NetworkRewardsPalletId::get().into_account_truncating();
+	pub RewardPools: Vec<(AccountId, Perbill)> = vec![
+		(ContentRewardPoolAccount::get(), Perbill::from_percent(50)),
+		(NetworkRewardPoolAccount::get(), Perbill::from_percent(30)),
+	];
+	// 1M ECHO per week, halving every four years
+	pub const EmissionCurve: pallet_tokenomics::EmissionCurve =
+		pallet_tokenomics::EmissionCurve::Halving { interval: 208 };
+}
+
+impl pallet_tokenomics::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type Currency = Balances;
-	type ContentRewardAmount = ConstU128<100_000_000_000_000>; // 100 ECHO (assuming 12 decimals)
-	type MinSamplesForContentReward = ConstU32<5>;
-	type NetworkRewardPeriod = ConstU32<DAYS>; // Daily for testing, monthly in production
+	type EmissionPeriod = ConstU32<{ 7 * DAYS }>;
+	type InitialEmission = ConstU128<1_000_000_000_000_000_000>; // 1M ECHO (assuming 12 decimals)
+	type Curve = EmissionCurve;
+	type MaxTotalIssuance = ConstU128<1_000_000_000_000_000_000_000>; // 1B ECHO
+	type RewardPools = RewardPools;
 	type TreasuryAccount = TreasuryAccount;
 }
 
 parameter_types! {
-	pub const MinimumPeriod: Moment = SLOT_DURATION / 2;
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = 10_000_000_000_000; // 10 ECHO
+	pub const ProposalBondMaximum: Balance = 1_000_000_000_000_000; // 1k ECHO
+	pub const TreasuryBurn: Permill = Permill::from_percent(1);
 }
 
-impl pallet_timestamp::Config for Runtime {
+impl pallet_treasury::Config for Runtime {
+	type PalletId = TreasuryPalletId;
+	type Currency = Balances;
+	type ApproveOrigin = RootOrCuratorMajority;
+	type RejectOrigin = RootOrCuratorMajority;
+	type RuntimeEvent = RuntimeEvent;
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type ProposalBondMaximum = ProposalBondMaximum;
+	type SpendPeriod = ConstU32<{ 7 * DAYS }>;
+	// Burnt every spend period, out of the funds left after the approved spends
+	type Burn = TreasuryBurn;
+	type BurnDestination = ();
+	type SpendFunds = ();
+	type WeightInfo = pallet_treasury::weights::SubstrateWeight<Runtime>;
+	type MaxApprovals = ConstU32<100>;
+	type SpendOrigin = frame_support::traits::NeverEnsureOrigin<Balance>;
+}
+
+impl pallet_creator_grants::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type ApproveOrigin = RootOrCuratorMajority;
+	type FundAccount = TreasuryAccount;
+	type PalletId = CreatorGrantsPalletId;
+	type ApplicationDeposit = ConstU128<10_000_000_000_000>; // 10 ECHO
+	type OnSlash = Treasury;
+	type MaxMilestones = ConstU32<10>;
+	type MaxCidLength = ConstU32<64>;
+}
+
+impl pallet_reward_vesting::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type PalletId = RewardVestingPalletId;
+	type VestingPeriod = ConstU32<{ 30 * DAYS }>;
+	type MaxSchedules = ConstU32<8>;
+}
+
+/// Defaults and bounds of the parameters governance can change through `pallet_parameters`.
+pub struct EchoParameters;
+
+impl pallet_parameters::ParameterInfo for EchoParameters {
+	fn default_value(key: pallet_parameters::ParameterKey) -> pallet_parameters::RuntimeParameter {
+		use pallet_parameters::{ParameterKey::*, RuntimeParameter as P};
+		match key {
+			ContentRewardAmount => P::ContentRewardAmount(100_000_000_000_000), // 100 ECHO (assuming 12 decimals)
+			MinSamplesForContentReward => P::MinSamplesForContentReward(5),
+			NetworkRewardPeriod => P::NetworkRewardPeriod(DAYS), // Daily for testing, monthly in production
+			TotalRewardPerPeriod => P::TotalRewardPerPeriod(10_000_000_000_000_000), // 10k ECHO
+			MinBytesUploaded => P::MinBytesUploaded(1024 * 1024), // 1 MiB
+		}
+	}
+
+	fn bounds(key: pallet_parameters::ParameterKey) -> sp_std::ops::RangeInclusive<u128> {
+		use pallet_parameters::ParameterKey::*;
+		match key {
+			ContentRewardAmount => 0..=10_000_000_000_000_000, // Up to 10k ECHO
+			MinSamplesForContentReward => 1..=1_000,
+			NetworkRewardPeriod => (HOURS as u128)..=(30 * DAYS) as u128,
+			TotalRewardPerPeriod => 0..=1_000_000_000_000_000_000, // Up to a full weekly emission
+			MinBytesUploaded => 0..=1024 * 1024 * 1024 * 1024, // Up to 1 TiB
+		}
+	}
+}
+
+impl pallet_parameters::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AdminOrigin = RootOrCuratorMajority;
+	type Info = EchoParameters;
+}
+
+impl pallet_proof_of_contribution::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type ContentRewardAmount = pallet_parameters::ContentRewardAmount<Runtime>;
+	type MinSamplesForContentReward = pallet_parameters::MinSamplesForContentReward<Runtime>;
+	type NetworkRewardPeriod = pallet_parameters::NetworkRewardPeriod<Runtime>;
+	type RewardPoolAccount = ContentRewardPoolAccount;
+	type NetworkRewardPoolAccount = NetworkRewardPoolAccount;
+	type Reputation = Reputation;
+}
+
+impl pallet_worker_keys::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type WorkerOrigin = RootOrCuratorMajority;
+	type MaxWorkers = ConstU32<32>;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = RuntimeCall;
+}
+
+/// Offchain workers sign their transactions with keys from the node's keystore, e.g. the
+/// `echo` worker keys of `pallet_worker_keys`.
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Nonce,
+	) -> Option<(RuntimeCall, <UncheckedExtrinsic as sp_runtime::traits::Extrinsic>::SignaturePayload)> {
+		use codec::Encode;
+		use sp_runtime::{traits::StaticLookup, SaturatedConversion};
+
+		let period = BlockHashCount::get()
+			.checked_next_power_of_two()
+			.map(|c| c / 2)
+			.unwrap_or(2) as u64;
+		let current_block = System::block_number().saturated_into::<u64>().saturating_sub(1);
+		let extra: SignedExtra = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let raw_payload = SignedPayload::new(call, extra).ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let address = <Runtime as frame_system::Config>::Lookup::unlookup(account);
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (address, signature, extra)))
+	}
+}
+
+impl pallet_sudo::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type WeightInfo = pallet_sudo::weights::SubstrateWeight<Runtime>;
+}
+
+/// Configure the pallet-template in pallets/template.
+impl pallet_template::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = pallet_template::weights::SubstrateWeight<Runtime>;
+}
+
+// Create the runtime by composing the FRAME pallets that were previously configured.
+construct_runtime!(
+	pub struct Runtime {
+		System: frame_system,
+		Timestamp: pallet_timestamp,
+		Aura: pallet_aura,
+		Grandpa: pallet_grandpa,
+		Balances: pallet_balances,
+		TransactionPayment: pallet_transaction_payment,
+		Sudo: pallet_sudo,
+		// Include the custom logic from the pallet-template in the runtime.
+		TemplateModule: pallet_template,
+		Reputation: pallet_reputation,
+		CuratorCollective: pallet_collective::<Instance1>,
+		CuratorElections: pallet_elections_phragmen,
+		Preimage: pallet_preimage,
+		Whitelist: pallet_whitelist,
+		SampleRegistry: pallet_sample_registry,
+		Tokenomics: pallet_tokenomics,
+		Treasury: pallet_treasury,
+		CreatorGrants: pallet_creator_grants,
+		RewardVesting: pallet_reward_vesting,
+		Parameters: pallet_parameters,
+		ProofOfContribution: pallet_proof_of_contribution,
+		WorkerKeys: pallet_worker_keys,
+	}
+);
 
 /// The address format for describing accounts.
 pub type Address = sp_runtime::MultiAddress<AccountId, ()>;
@@ -324,6 +687,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	migrations::Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -511,6 +875,30 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_tokenomics_runtime_api::TokenomicsApi<Block, Balance> for Runtime {
+		fn emission_for_period(period: u32) -> Balance {
+			Tokenomics::emission_for_period(period)
+		}
+
+		fn projected_emissions(periods: u32) -> Vec<Balance> {
+			Tokenomics::projected_emissions(periods)
+		}
+
+		fn remaining_issuance() -> Balance {
+			Tokenomics::remaining_issuance()
+		}
+	}
+
+	impl pallet_reward_vesting_runtime_api::RewardVestingApi<Block, AccountId, Balance, BlockNumber> for Runtime {
+		fn claimable(who: AccountId) -> Balance {
+			RewardVesting::claimable(&who)
+		}
+
+		fn unlock_timeline(who: AccountId) -> Vec<pallet_reward_vesting::VestingSchedule<Balance, BlockNumber>> {
+			RewardVesting::unlock_timeline(&who)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -0,0 +1,71 @@
+//! Storage migrations of the runtime, run by `Executive` on runtime upgrades.
+
+use super::*;
+use frame_support::{
+	storage::{storage_prefix, unhashed},
+	traits::{Get, OnRuntimeUpgrade, PalletInfoAccess},
+};
+
+/// Migrations applied by the next runtime upgrade.
+pub type Migrations = (DisableSudo, pallet_sample_registry::migrations::v1::MigrateToV1<Runtime>);
+
+/// Spec version whose upgrade removes the sudo key.
+const SUDO_REMOVAL_SPEC_VERSION: u32 = 101;
+
+/// Removes the sudo key, leaving Root to the governance origins: the curator collective
+/// and calls whitelisted through `pallet_whitelist`.
+///
+/// Only runs on the upgrade to `SUDO_REMOVAL_SPEC_VERSION`, i.e. on chains launched with an
+/// older runtime. Chains launched at or after that version, such as `--dev` and
+/// `--chain local`, never run it and keep their sudo key, and later upgrades leave sudo
+/// alone. The key is also kept while the curator collective is empty, since nobody could
+/// reach Root without it.
+pub struct DisableSudo;
+
+impl DisableSudo {
+	fn key() -> [u8; 32] {
+		storage_prefix(<Sudo as PalletInfoAccess>::name().as_bytes(), b"Key")
+	}
+
+	/// Whether the runtime being upgraded to is the one removing sudo.
+	fn applies() -> bool {
+		VERSION.spec_version == SUDO_REMOVAL_SPEC_VERSION
+	}
+
+	fn has_curators() -> bool {
+		!pallet_collective::Members::<Runtime, CuratorCollectiveInstance>::get().is_empty()
+	}
+}
+
+impl OnRuntimeUpgrade for DisableSudo {
+	fn on_runtime_upgrade() -> Weight {
+		if !Self::applies() {
+			return Weight::zero();
+		}
+		if !Self::has_curators() {
+			return RocksDbWeight::get().reads(1);
+		}
+		if unhashed::exists(&Self::key()) {
+			unhashed::kill(&Self::key());
+			RocksDbWeight::get().reads_writes(2, 1)
+		} else {
+			RocksDbWeight::get().reads(2)
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+		if Self::applies() {
+			frame_support::ensure!(Self::has_curators(), "no curators: Root would be unreachable without sudo");
+		}
+		Ok(Vec::new())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+		if Self::applies() {
+			frame_support::ensure!(!unhashed::exists(&Self::key()), "sudo key still set");
+		}
+		Ok(())
+	}
+}
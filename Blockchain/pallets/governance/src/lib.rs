@@ -1,54 +1,167 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
-    decl_module, decl_storage, decl_event, decl_error,
+    decl_module, decl_storage, decl_event, decl_error, ensure,
     dispatch::DispatchResult,
-    traits::{Currency, Get},
+    traits::{
+        schedule::{v3::Named as ScheduleNamed, DispatchTime},
+        Bounded, Contains, Currency, Get, LockIdentifier, LockableCurrency, OnUnbalanced,
+        QueryPreimage, ReservableCurrency, StorePreimage, WithdrawReasons,
+    },
+    weights::Weight,
+    BoundedVec, Parameter, RuntimeDebug,
+};
+use frame_system::ensure_signed;
+use sp_runtime::{
+    traits::{Saturating, Zero},
+    Perbill,
 };
-use frame_system::ensure_root;
 use sp_std::prelude::*;
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
+
+/// Identifier of the lock holding the balance backing votes.
+const GOVERNANCE_ID: LockIdentifier = *b"echogov ";
+
 pub trait Config: frame_system::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
-    
-    /// The PoC pallet instance
-    type Poc: proof_of_contribution::Config;
-    
-    /// The currency type for governance deposits
-    type Currency: Currency<<Self as frame_system::Config>::AccountId>;
-    
-    /// Minimum deposit required to propose parameter changes
-    type ProposalDeposit: Get<<Self::Currency as Currency<<Self as frame_system::Config>::AccountId>>::Balance>;
+
+    /// The currency type for governance deposits and the balance locked by votes
+    type Currency: ReservableCurrency<Self::AccountId>
+        + LockableCurrency<Self::AccountId, Moment = Self::BlockNumber>;
+
+    /// Minimum deposit required to propose a call
+    type ProposalDeposit: Get<BalanceOf<Self>>;
+
+    /// The overarching call type proposals carry.
+    type Proposal: Parameter;
+
+    /// Runtime calls governance may dispatch, e.g. parameter setters of the EchoChain pallets.
+    /// Proposals of any other call are refused.
+    type CallFilter: Contains<Self::Proposal>;
+
+    /// Preimages of proposed calls. Calls too large to be inlined must be noted before
+    /// they are proposed; their preimage is then requested until the vote is closed.
+    type Preimages: QueryPreimage + StorePreimage;
+
+    /// The caller origin the scheduler dispatches enactments with.
+    type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+
+    /// Scheduler dispatching passed proposals with the root origin once their enactment
+    /// delay is over.
+    type Scheduler: ScheduleNamed<Self::BlockNumber, Self::Proposal, Self::PalletsOrigin>;
+
+    /// Number of blocks a proposal is open for voting.
+    type VotingPeriod: Get<Self::BlockNumber>;
+
+    /// Number of blocks between the end of a successful vote and the enactment of the proposal.
+    type EnactmentDelay: Get<Self::BlockNumber>;
+
+    /// Minimum turnout, as a fraction of the total issuance, for a vote to be decisive.
+    /// Proposals below quorum expire and get their deposit back.
+    type Quorum: Get<Perbill>;
+
+    /// Minimum fraction of the turnout voting aye for a proposal to pass.
+    /// Proposals voted down have their deposit slashed.
+    type ApprovalThreshold: Get<Perbill>;
+
+    /// Maximum number of proposals an account can have votes locked on at once.
+    type MaxVotes: Get<u32>;
+
+    /// Maximum number of proposals whose voting period ends in the same block.
+    type MaxProposalsPerBlock: Get<u32>;
+
+    /// Maximum number of votes of closed proposals removed per block.
+    type MaxVotesClearedPerBlock: Get<u32>;
+
+    /// Receives the deposits of proposals voted down, e.g. the treasury.
+    type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
 }
 
 decl_storage! {
     trait Store for Module<T: Config> as Governance {
-        /// Active parameter change proposals
+        /// Proposals in their voting period
         pub Proposals get(fn proposals):
-            map hasher(blake2_128_concat) u32 => Proposal<T>;
-            
+            map hasher(blake2_128_concat) u32 => Option<Proposal<T>>;
+
         /// Next proposal ID
         pub NextProposalId get(fn next_proposal_id): u32;
+
+        /// Proposals whose voting period ends at a block.
+        pub ProposalsEnding get(fn proposals_ending):
+            map hasher(twox_64_concat) T::BlockNumber => BoundedVec<u32, T::MaxProposalsPerBlock>;
+
+        /// Votes cast on proposals in their voting period. Votes of closed proposals are
+        /// removed over the following blocks.
+        pub Votes get(fn votes):
+            double_map hasher(twox_64_concat) u32, hasher(blake2_128_concat) T::AccountId => Option<Vote<BalanceOf<T>>>;
+
+        /// Closed proposals whose votes are still being removed, with the block they closed at.
+        pub VotesToClear get(fn votes_to_clear):
+            map hasher(twox_64_concat) u32 => Option<T::BlockNumber>;
+
+        /// Proposals an account has voted on, with the balance locked for each.
+        /// Entries of closed proposals are dropped by `unlock`.
+        pub VotingOf get(fn voting_of):
+            map hasher(blake2_128_concat) T::AccountId => Vec<(u32, BalanceOf<T>)>;
     }
 }
 
 decl_event!(
-    pub enum Event<T> where AccountId = <T as frame_system::Config>::AccountId {
-        /// New parameter change proposed [proposal_id, proposer]
+    pub enum Event<T> where
+        AccountId = <T as frame_system::Config>::AccountId,
+        Balance = BalanceOf<T>,
+        BlockNumber = <T as frame_system::Config>::BlockNumber,
+    {
+        /// New call proposed [proposal_id, proposer]
         ProposalCreated(u32, AccountId),
-        /// Parameter change enacted [proposal_id]
-        ProposalEnacted(u32),
+        /// A vote was cast or changed [proposal_id, voter, aye, balance]
+        Voted(u32, AccountId, bool, Balance),
+        /// A vote was withdrawn [proposal_id, voter]
+        VoteRemoved(u32, AccountId),
+        /// A proposal passed and its enactment was scheduled [proposal_id, enactment_block]
+        ProposalPassed(u32, BlockNumber),
+        /// A proposal was voted down and its deposit slashed [proposal_id, slashed]
+        ProposalRejected(u32, Balance),
+        /// A proposal did not reach quorum and its deposit was returned [proposal_id]
+        ProposalExpired(u32),
+        /// A proposal passed but its enactment could not be scheduled [proposal_id]
+        EnactmentSchedulingFailed(u32),
+        /// A proposal was cancelled by its proposer [proposal_id]
+        ProposalCancelled(u32),
     }
 );
 
 decl_error! {
     pub enum Error for Module<T: Config> {
-        /// Invalid reward parameters
-        InvalidParameters,
-        /// Proposal not found
+        /// Proposal not found or no longer open for voting
         UnknownProposal,
         /// Insufficient deposit
         InsufficientDeposit,
+        /// The proposed call is not among the calls governance may dispatch
+        CallFiltered,
+        /// The preimage of the proposed call has not been noted
+        PreimageUnavailable,
+        /// Only the proposer can cancel a proposal
+        NotProposer,
+        /// The account has not voted on the proposal
+        NotVoted,
+        /// The vote exceeds the account's free balance
+        InsufficientBalance,
+        /// The account already has votes locked on `MaxVotes` proposals
+        TooManyVotes,
+        /// `MaxProposalsPerBlock` proposals already end in the block this one would end in
+        TooManyProposals,
     }
 }
 
@@ -58,84 +171,284 @@ decl_module! {
 
         fn deposit_event() = default;
 
-        /// Propose new reward parameters
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let db_weight = <T as frame_system::Config>::DbWeight::get();
+            let ending = <ProposalsEnding<T>>::take(now);
+            let mut weight = db_weight.reads_writes(1, 1);
+
+            for proposal_id in ending {
+                Self::close_vote(proposal_id, now);
+                weight = weight.saturating_add(db_weight.reads_writes(3, 4));
+            }
+            weight.saturating_add(Self::clear_votes(T::MaxVotesClearedPerBlock::get()))
+        }
+
+        /// Propose a runtime call, reserving `ProposalDeposit`.
+        /// The call must pass `CallFilter` and its preimage must be available. The preimage
+        /// is requested so it stays available while the proposal is open.
+        /// The proposal is open for voting during `VotingPeriod` blocks.
         #[weight = 10_000]
-        pub fn propose_reward_params(
-            origin,
-            content_reward: u128,
-            storage_reward: u128,
-            bandwidth_reward: u128,
-            reward_interval: u32
-        ) -> DispatchResult {
+        pub fn propose(origin, call: Bounded<T::Proposal>) -> DispatchResult {
             let proposer = ensure_signed(origin)?;
-            
+            let (proposed, _) = T::Preimages::peek(&call).map_err(|_| Error::<T>::PreimageUnavailable)?;
+            ensure!(T::CallFilter::contains(&proposed), Error::<T>::CallFiltered);
+
+            let proposal_id = <NextProposalId>::get();
+            let end = <frame_system::Pallet<T>>::block_number().saturating_add(T::VotingPeriod::get());
+            let mut ending = <ProposalsEnding<T>>::get(end);
+            ending.try_push(proposal_id).map_err(|_| Error::<T>::TooManyProposals)?;
+
             // Take deposit
-            T::Currency::reserve(&proposer, T::ProposalDeposit::get())?;
-            
+            let deposit = T::ProposalDeposit::get();
+            T::Currency::reserve(&proposer, deposit).map_err(|_| Error::<T>::InsufficientDeposit)?;
+            T::Preimages::hold(&call);
+
             // Create and store proposal
-            let proposal_id = <NextProposalId>::get();
             let proposal = Proposal {
                 proposer: proposer.clone(),
-                content_reward,
-                storage_reward,
-                bandwidth_reward,
-                reward_interval,
-                deposit: T::ProposalDeposit::get(),
+                call,
+                deposit,
+                end,
+                tally: Tally::default(),
             };
-            
+
             <Proposals<T>>::insert(proposal_id, proposal);
             <NextProposalId>::put(proposal_id + 1);
-            
+            <ProposalsEnding<T>>::insert(end, ending);
+
             Self::deposit_event(RawEvent::ProposalCreated(proposal_id, proposer));
             Ok(())
         }
 
-        /// Enact approved reward parameters
+        /// Vote on a proposal with `balance`, which stays locked until the vote is closed
+        /// and `unlock` is called. Voting again replaces the previous vote.
         #[weight = 10_000]
-        pub fn enact_proposal(
-            origin,
-            proposal_id: u32
-        ) -> DispatchResult {
-            ensure_root(origin)?;
-            
-            let proposal = <Proposals<T>>::take(proposal_id)
-                .ok_or(Error::<T>::UnknownProposal)?;
-                
-            // Validate parameters
-            if proposal.content_reward == 0 || proposal.storage_reward == 0 || proposal.bandwidth_reward == 0 {
-                return Err(Error::<T>::InvalidParameters.into());
+        pub fn vote(origin, proposal_id: u32, aye: bool, balance: BalanceOf<T>) -> DispatchResult {
+            let voter = ensure_signed(origin)?;
+            ensure!(T::Currency::free_balance(&voter) >= balance, Error::<T>::InsufficientBalance);
+
+            let mut proposal = Self::voting_proposal(proposal_id)?;
+            let mut voting = <VotingOf<T>>::get(&voter);
+            match voting.iter_mut().find(|(id, _)| *id == proposal_id) {
+                Some(entry) => entry.1 = balance,
+                None => {
+                    ensure!((voting.len() as u32) < T::MaxVotes::get(), Error::<T>::TooManyVotes);
+                    voting.push((proposal_id, balance));
+                }
             }
-            
-            // Update PoC pallet configuration
-            proof_of_contribution::Module::<T::Poc>::set_reward_parameters(
-                proposal.content_reward,
-                proposal.storage_reward,
-                proposal.bandwidth_reward,
-                proposal.reward_interval
-            )?;
-            
-            // Return deposit
+
+            if let Some(previous) = <Votes<T>>::get(proposal_id, &voter) {
+                proposal.tally.remove(&previous);
+            }
+            let vote = Vote { aye, balance };
+            proposal.tally.add(&vote);
+
+            <Votes<T>>::insert(proposal_id, &voter, vote);
+            <Proposals<T>>::insert(proposal_id, proposal);
+            Self::update_lock(&voter, voting);
+
+            Self::deposit_event(RawEvent::Voted(proposal_id, voter, aye, balance));
+            Ok(())
+        }
+
+        /// Withdraw a vote from a proposal still open for voting.
+        #[weight = 10_000]
+        pub fn remove_vote(origin, proposal_id: u32) -> DispatchResult {
+            let voter = ensure_signed(origin)?;
+            let mut proposal = Self::voting_proposal(proposal_id)?;
+            let vote = <Votes<T>>::take(proposal_id, &voter).ok_or(Error::<T>::NotVoted)?;
+
+            proposal.tally.remove(&vote);
+            <Proposals<T>>::insert(proposal_id, proposal);
+
+            let mut voting = <VotingOf<T>>::get(&voter);
+            voting.retain(|(id, _)| *id != proposal_id);
+            Self::update_lock(&voter, voting);
+
+            Self::deposit_event(RawEvent::VoteRemoved(proposal_id, voter));
+            Ok(())
+        }
+
+        /// Release the balance `target` locked for proposals whose vote is over.
+        #[weight = 10_000]
+        pub fn unlock(origin, target: T::AccountId) -> DispatchResult {
+            ensure_signed(origin)?;
+            let mut voting = <VotingOf<T>>::get(&target);
+            voting.retain(|(id, _)| Self::voting_proposal(*id).is_ok());
+            Self::update_lock(&target, voting);
+            Ok(())
+        }
+
+        /// Cancel a proposal still open for voting and return its deposit.
+        /// Only the proposer can cancel.
+        #[weight = 10_000]
+        pub fn cancel_proposal(origin, proposal_id: u32) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let proposal = Self::voting_proposal(proposal_id)?;
+            ensure!(proposal.proposer == who, Error::<T>::NotProposer);
+
             T::Currency::unreserve(&proposal.proposer, proposal.deposit);
-            
-            Self::deposit_event(RawEvent::ProposalEnacted(proposal_id));
+            T::Preimages::drop(&proposal.call);
+            <ProposalsEnding<T>>::mutate(proposal.end, |ending| ending.retain(|id| *id != proposal_id));
+            Self::remove_proposal(proposal_id);
+
+            Self::deposit_event(RawEvent::ProposalCancelled(proposal_id));
             Ok(())
         }
     }
 }
 
-/// A parameter change proposal
+impl<T: Config> Module<T> {
+    /// The proposal `proposal_id` if it is open for voting.
+    fn voting_proposal(proposal_id: u32) -> Result<Proposal<T>, Error<T>> {
+        <Proposals<T>>::get(proposal_id).ok_or(Error::<T>::UnknownProposal)
+    }
+
+    /// Close the vote on a proposal whose voting period ended at `now`: schedule the
+    /// dispatch of its call and return the deposit if it passed, slash the deposit if it
+    /// was voted down, and return the deposit if it did not reach quorum. The preimage
+    /// request of the proposal is dropped in every case, once the scheduler holds its own.
+    fn close_vote(proposal_id: u32, now: T::BlockNumber) {
+        let proposal = match Self::voting_proposal(proposal_id) {
+            Ok(proposal) => proposal,
+            Err(_) => return,
+        };
+        Self::remove_proposal(proposal_id);
+
+        let turnout = proposal.tally.turnout();
+        if turnout < T::Quorum::get().mul_ceil(T::Currency::total_issuance()) {
+            T::Currency::unreserve(&proposal.proposer, proposal.deposit);
+            Self::deposit_event(RawEvent::ProposalExpired(proposal_id));
+        } else if !proposal.tally.ayes.is_zero()
+            && proposal.tally.ayes >= T::ApprovalThreshold::get().mul_ceil(turnout)
+        {
+            T::Currency::unreserve(&proposal.proposer, proposal.deposit);
+            let when = now.saturating_add(T::EnactmentDelay::get());
+            if Self::schedule_enactment(proposal_id, proposal.call.clone(), when).is_ok() {
+                Self::deposit_event(RawEvent::ProposalPassed(proposal_id, when));
+            } else {
+                Self::deposit_event(RawEvent::EnactmentSchedulingFailed(proposal_id));
+            }
+        } else {
+            let (imbalance, _) = T::Currency::slash_reserved(&proposal.proposer, proposal.deposit);
+            T::Slash::on_unbalanced(imbalance);
+            Self::deposit_event(RawEvent::ProposalRejected(proposal_id, proposal.deposit));
+        }
+        T::Preimages::drop(&proposal.call);
+    }
+
+    fn schedule_enactment(
+        proposal_id: u32,
+        call: Bounded<T::Proposal>,
+        when: T::BlockNumber,
+    ) -> Result<(), ()> {
+        T::Scheduler::schedule_named(
+            (GOVERNANCE_ID, proposal_id).using_encoded(sp_io::hashing::blake2_256),
+            DispatchTime::At(when),
+            None,
+            63,
+            frame_system::RawOrigin::Root.into(),
+            call,
+        )
+        .map(|_| ())
+        .map_err(|_| ())
+    }
+
+    /// Remove a closed proposal. Its votes are removed lazily by `clear_votes`.
+    fn remove_proposal(proposal_id: u32) {
+        <Proposals<T>>::remove(proposal_id);
+        <VotesToClear<T>>::insert(proposal_id, <frame_system::Pallet<T>>::block_number());
+    }
+
+    /// Remove up to `limit` votes of closed proposals. Returns the weight of the keys
+    /// actually visited and removed.
+    fn clear_votes(limit: u32) -> Weight {
+        let db_weight = <T as frame_system::Config>::DbWeight::get();
+        let closed = <VotesToClear<T>>::iter_keys().take(limit as usize).collect::<Vec<_>>();
+        let mut weight = db_weight.reads(1 + closed.len() as u64);
+        let mut budget = limit;
+
+        for proposal_id in closed {
+            if budget == 0 {
+                break;
+            }
+            // Each prefix is cleared at most once per block, so no cursor is needed
+            let removed = <Votes<T>>::clear_prefix(proposal_id, budget, None);
+            budget = budget.saturating_sub(removed.unique);
+            weight = weight.saturating_add(db_weight.reads_writes(removed.loops.into(), removed.unique.into()));
+            if removed.maybe_cursor.is_none() {
+                <VotesToClear<T>>::remove(proposal_id);
+                weight = weight.saturating_add(db_weight.writes(1));
+            }
+        }
+        weight
+    }
+
+    /// Lock the largest balance `who` votes with and store their remaining votes.
+    fn update_lock(who: &T::AccountId, voting: Vec<(u32, BalanceOf<T>)>) {
+        match voting.iter().map(|(_, balance)| *balance).max() {
+            Some(locked) => {
+                T::Currency::set_lock(GOVERNANCE_ID, who, locked, WithdrawReasons::all());
+                <VotingOf<T>>::insert(who, voting);
+            }
+            None => {
+                T::Currency::remove_lock(GOVERNANCE_ID, who);
+                <VotingOf<T>>::remove(who);
+            }
+        }
+    }
+}
+
+/// A stake-weighted vote on a proposal.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct Vote<Balance> {
+    /// Whether the vote is in favour of the proposal.
+    pub aye: bool,
+    /// Balance backing the vote, locked until the vote is over.
+    pub balance: Balance,
+}
+
+/// Balance voting for and against a proposal.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct Tally<Balance> {
+    pub ayes: Balance,
+    pub nays: Balance,
+}
+
+impl<Balance: Saturating + Copy> Tally<Balance> {
+    fn add(&mut self, vote: &Vote<Balance>) {
+        if vote.aye {
+            self.ayes = self.ayes.saturating_add(vote.balance);
+        } else {
+            self.nays = self.nays.saturating_add(vote.balance);
+        }
+    }
+
+    fn remove(&mut self, vote: &Vote<Balance>) {
+        if vote.aye {
+            self.ayes = self.ayes.saturating_sub(vote.balance);
+        } else {
+            self.nays = self.nays.saturating_sub(vote.balance);
+        }
+    }
+
+    /// Total balance that voted.
+    pub fn turnout(&self) -> Balance {
+        self.ayes.saturating_add(self.nays)
+    }
+}
+
+/// A proposal to dispatch a runtime call with the root origin
 #[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
 pub struct Proposal<T: Config> {
-    /// The account proposing the change
+    /// The account proposing the call
     pub proposer: T::AccountId,
-    /// New content reward rate
-    pub content_reward: u128,
-    /// New storage reward rate
-    pub storage_reward: u128,
-    /// New bandwidth reward rate
-    pub bandwidth_reward: u128,
-    /// New reward distribution interval (blocks)
-    pub reward_interval: u32,
+    /// The proposed call, inline or by preimage hash
+    pub call: Bounded<T::Proposal>,
     /// Deposit held for this proposal
-    pub deposit: <T::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance,
+    pub deposit: BalanceOf<T>,
+    /// Block at which the voting period ends
+    pub end: T::BlockNumber,
+    /// Balance voting for and against the proposal
+    pub tally: Tally<BalanceOf<T>>,
 }
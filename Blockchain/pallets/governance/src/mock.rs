@@ -0,0 +1,239 @@
+use crate as pallet_governance;
+use frame_support::{
+    parameter_types,
+    traits::{
+        schedule::{v3::Named as ScheduleNamed, DispatchTime, Period, Priority},
+        Bounded, Contains, Everything, OnInitialize, QueryPreimage,
+    },
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    DispatchError, Perbill,
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Module, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+        Preimage: pallet_preimage::{Module, Call, Storage, Event<T>},
+        ProofOfContribution: pallet_proof_of_contribution::{Module, Call, Storage, Event<T>},
+        Governance: pallet_governance::{Module, Call, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const ExistentialDeposit: u128 = 1;
+    pub const MaxLocks: u32 = 10;
+    pub const ContributionReward: u128 = 10;
+    pub const MaxPrunedPerBlock: u32 = 2;
+    pub const ClaimWindow: u64 = 5;
+    pub const RootCommitTimeout: u64 = 10;
+    pub const MaxContributorsPerEpoch: u32 = 100;
+    pub const ReportDeposit: u128 = 10;
+    pub const UnsignedPriority: u64 = 1 << 20;
+    pub const PreimageBaseDeposit: u128 = 0;
+    pub const PreimageByteDeposit: u128 = 0;
+    pub const ProposalDeposit: u128 = 100;
+    pub const VotingPeriod: u64 = 10;
+    pub const EnactmentDelay: u64 = 5;
+    pub const Quorum: Perbill = Perbill::from_percent(20);
+    pub const ApprovalThreshold: Perbill = Perbill::from_percent(50);
+    pub const MaxVotes: u32 = 2;
+    pub const MaxProposalsPerBlock: u32 = 2;
+    pub const MaxVotesClearedPerBlock: u32 = 2;
+}
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<u64>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u128>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = MaxLocks;
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = u128;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+impl pallet_preimage::Config for Test {
+    type Event = Event;
+    type WeightInfo = ();
+    type Currency = Balances;
+    type ManagerOrigin = EnsureRoot<u64>;
+    type BaseDeposit = PreimageBaseDeposit;
+    type ByteDeposit = PreimageByteDeposit;
+}
+
+impl pallet_proof_of_contribution::Config for Test {
+    type Event = Event;
+    type Currency = Balances;
+    type ContributionReward = ContributionReward;
+    type ContributionOracle = EnsureRoot<u64>;
+    type MaxPrunedPerBlock = MaxPrunedPerBlock;
+    type ClaimWindow = ClaimWindow;
+    type RootCommitTimeout = RootCommitTimeout;
+    type MaxContributorsPerEpoch = MaxContributorsPerEpoch;
+    type ReportDeposit = ReportDeposit;
+    type UnsignedPriority = UnsignedPriority;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+    Call: From<LocalCall>,
+{
+    type OverarchingCall = Call;
+    type Extrinsic = UncheckedExtrinsic;
+}
+
+thread_local! {
+    static SCHEDULED: RefCell<Vec<([u8; 32], u64, Bounded<Call>)>> = RefCell::new(Vec::new());
+}
+
+/// Scheduler recording named tasks for the tests to inspect and dispatch.
+pub struct TestScheduler;
+
+impl ScheduleNamed<u64, Call, OriginCaller> for TestScheduler {
+    type Address = u64;
+
+    fn schedule_named(
+        id: [u8; 32],
+        when: DispatchTime<u64>,
+        _maybe_periodic: Option<Period<u64>>,
+        _priority: Priority,
+        _origin: OriginCaller,
+        call: Bounded<Call>,
+    ) -> Result<u64, DispatchError> {
+        let when = match when {
+            DispatchTime::At(when) => when,
+            DispatchTime::After(after) => System::block_number() + after,
+        };
+        SCHEDULED.with(|scheduled| scheduled.borrow_mut().push((id, when, call)));
+        Ok(when)
+    }
+
+    fn cancel_named(id: [u8; 32]) -> Result<(), DispatchError> {
+        SCHEDULED.with(|scheduled| scheduled.borrow_mut().retain(|(task, _, _)| *task != id));
+        Ok(())
+    }
+
+    fn reschedule_named(_id: [u8; 32], _when: DispatchTime<u64>) -> Result<u64, DispatchError> {
+        Err(DispatchError::Unavailable)
+    }
+
+    fn next_dispatch_time(id: [u8; 32]) -> Result<u64, DispatchError> {
+        SCHEDULED.with(|scheduled| {
+            scheduled.borrow().iter().find(|(task, _, _)| *task == id).map(|(_, when, _)| *when)
+        })
+        .ok_or(DispatchError::Unavailable)
+    }
+}
+
+/// Calls scheduled so far, with the block they are due at.
+pub fn scheduled() -> Vec<(u64, Call)> {
+    SCHEDULED.with(|scheduled| {
+        scheduled
+            .borrow()
+            .iter()
+            .map(|(_, when, call)| (*when, Preimage::peek(call).expect("preimage available").0))
+            .collect()
+    })
+}
+
+/// Governance may only change the reward parameters and pool budgets.
+pub struct GovernanceCalls;
+
+impl Contains<Call> for GovernanceCalls {
+    fn contains(call: &Call) -> bool {
+        matches!(
+            call,
+            Call::ProofOfContribution(
+                pallet_proof_of_contribution::Call::update_reward_parameters(..)
+                    | pallet_proof_of_contribution::Call::set_pool_budget(..)
+            )
+        )
+    }
+}
+
+impl pallet_governance::Config for Test {
+    type Event = Event;
+    type Currency = Balances;
+    type ProposalDeposit = ProposalDeposit;
+    type Proposal = Call;
+    type CallFilter = GovernanceCalls;
+    type Preimages = Preimage;
+    type PalletsOrigin = OriginCaller;
+    type Scheduler = TestScheduler;
+    type VotingPeriod = VotingPeriod;
+    type EnactmentDelay = EnactmentDelay;
+    type Quorum = Quorum;
+    type ApprovalThreshold = ApprovalThreshold;
+    type MaxVotes = MaxVotes;
+    type MaxProposalsPerBlock = MaxProposalsPerBlock;
+    type MaxVotesClearedPerBlock = MaxVotesClearedPerBlock;
+    type Slash = ();
+}
+
+/// Run `on_initialize` of every block up to and including `n`.
+pub fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        let next = System::block_number() + 1;
+        System::set_block_number(next);
+        Governance::on_initialize(next);
+    }
+}
+
+// Build genesis storage according to the mock runtime.
+// Accounts 1 to 4 hold 1_000 each, so quorum is 800.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, 1_000), (2, 1_000), (3, 1_000), (4, 1_000)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        SCHEDULED.with(|scheduled| scheduled.borrow_mut().clear());
+    });
+    ext
+}
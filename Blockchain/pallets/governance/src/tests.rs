@@ -0,0 +1,256 @@
+use crate::{mock::*, Error, RawEvent, Tally, GOVERNANCE_ID};
+use codec::Encode;
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{Bounded, QueryPreimage, ReservableCurrency, StorePreimage},
+};
+use sp_runtime::traits::{BlakeTwo256, Dispatchable, Hash};
+
+fn rates_call(content_reward: u128) -> Call {
+    Call::ProofOfContribution(pallet_proof_of_contribution::Call::update_reward_parameters(
+        content_reward, 3, 2, 20,
+    ))
+}
+
+fn propose(who: u64) -> u32 {
+    let proposal_id = Governance::next_proposal_id();
+    assert_ok!(Governance::propose(Origin::signed(who), Preimage::bound(rates_call(5)).unwrap()));
+    proposal_id
+}
+
+fn locked(who: u64) -> u128 {
+    Balances::locks(&who)
+        .iter()
+        .find(|lock| lock.id == GOVERNANCE_ID)
+        .map_or(0, |lock| lock.amount)
+}
+
+#[test]
+fn proposing_reserves_the_deposit_and_opens_a_vote() {
+    new_test_ext().execute_with(|| {
+        let proposal_id = propose(1);
+        assert_eq!(Balances::reserved_balance(1), 100);
+        let proposal = Governance::proposals(proposal_id).unwrap();
+        assert_eq!(proposal.call, Preimage::bound(rates_call(5)).unwrap());
+        assert_eq!(proposal.end, 11);
+        assert_eq!(Governance::proposals_ending(11), vec![proposal_id]);
+    });
+}
+
+#[test]
+fn only_whitelisted_calls_can_be_proposed() {
+    new_test_ext().execute_with(|| {
+        let call = Call::ProofOfContribution(pallet_proof_of_contribution::Call::record_content_contribution(1, 5));
+        assert_noop!(
+            Governance::propose(Origin::signed(1), Preimage::bound(call).unwrap()),
+            Error::<Test>::CallFiltered
+        );
+    });
+}
+
+#[test]
+fn calls_can_be_proposed_by_preimage_hash() {
+    new_test_ext().execute_with(|| {
+        let encoded = rates_call(7).encode();
+        let by_hash = Bounded::Lookup { hash: BlakeTwo256::hash(&encoded), len: encoded.len() as u32 };
+        assert_noop!(
+            Governance::propose(Origin::signed(1), by_hash.clone()),
+            Error::<Test>::PreimageUnavailable
+        );
+
+        let hash = BlakeTwo256::hash(&encoded);
+        assert_ok!(Preimage::note_preimage(Origin::signed(1), encoded));
+        assert!(!<Preimage as QueryPreimage>::is_requested(&hash));
+        assert_ok!(Governance::propose(Origin::signed(1), by_hash));
+        assert!(<Preimage as QueryPreimage>::is_requested(&hash));
+        assert_ok!(Governance::vote(Origin::signed(2), 0, true, 900));
+
+        run_to_block(11);
+        assert_eq!(scheduled(), vec![(16, rates_call(7))]);
+        assert!(!<Preimage as QueryPreimage>::is_requested(&hash));
+    });
+}
+
+#[test]
+fn preimage_requests_are_dropped_with_the_proposal() {
+    new_test_ext().execute_with(|| {
+        let encoded = rates_call(7).encode();
+        let hash = BlakeTwo256::hash(&encoded);
+        let by_hash = Bounded::Lookup { hash, len: encoded.len() as u32 };
+        assert_ok!(Preimage::note_preimage(Origin::signed(1), encoded));
+
+        // Cancelled
+        assert_ok!(Governance::propose(Origin::signed(1), by_hash.clone()));
+        assert!(<Preimage as QueryPreimage>::is_requested(&hash));
+        assert_ok!(Governance::cancel_proposal(Origin::signed(1), 0));
+        assert!(!<Preimage as QueryPreimage>::is_requested(&hash));
+
+        // Expired below quorum
+        assert_ok!(Governance::propose(Origin::signed(1), by_hash.clone()));
+        run_to_block(11);
+        System::assert_last_event(RawEvent::ProposalExpired(1).into());
+        assert!(!<Preimage as QueryPreimage>::is_requested(&hash));
+
+        // Voted down
+        assert_ok!(Governance::propose(Origin::signed(1), by_hash));
+        assert_ok!(Governance::vote(Origin::signed(3), 2, false, 900));
+        run_to_block(21);
+        System::assert_last_event(RawEvent::ProposalRejected(2, 100).into());
+        assert!(!<Preimage as QueryPreimage>::is_requested(&hash));
+        assert!(<Preimage as QueryPreimage>::len(&hash).is_some());
+    });
+}
+
+#[test]
+fn passed_proposals_are_enacted_by_the_scheduler() {
+    new_test_ext().execute_with(|| {
+        let proposal_id = propose(1);
+        assert_ok!(Governance::vote(Origin::signed(2), proposal_id, true, 600));
+        assert_ok!(Governance::vote(Origin::signed(3), proposal_id, false, 400));
+
+        run_to_block(11);
+        System::assert_last_event(RawEvent::ProposalPassed(proposal_id, 16).into());
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Governance::proposals(proposal_id), None);
+        assert_eq!(Governance::votes(proposal_id, 2), None);
+        assert_noop!(
+            Governance::vote(Origin::signed(4), proposal_id, true, 100),
+            Error::<Test>::UnknownProposal
+        );
+
+        let (when, call) = scheduled().pop().unwrap();
+        assert_eq!(when, 16);
+        assert_eq!(call, rates_call(5));
+        assert_ok!(call.dispatch(Origin::root()));
+        assert_eq!(ProofOfContribution::rates().unwrap().content, 5);
+    });
+}
+
+#[test]
+fn proposals_voted_down_lose_their_deposit() {
+    new_test_ext().execute_with(|| {
+        let proposal_id = propose(1);
+        assert_ok!(Governance::vote(Origin::signed(2), proposal_id, true, 300));
+        assert_ok!(Governance::vote(Origin::signed(3), proposal_id, false, 700));
+
+        run_to_block(11);
+        System::assert_last_event(RawEvent::ProposalRejected(proposal_id, 100).into());
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 900);
+        assert_eq!(Governance::proposals(proposal_id), None);
+        assert!(scheduled().is_empty());
+    });
+}
+
+#[test]
+fn proposals_below_quorum_expire_with_their_deposit_returned() {
+    new_test_ext().execute_with(|| {
+        let proposal_id = propose(1);
+        assert_ok!(Governance::vote(Origin::signed(2), proposal_id, true, 799));
+
+        run_to_block(11);
+        System::assert_last_event(RawEvent::ProposalExpired(proposal_id).into());
+        assert_eq!(Balances::free_balance(1), 1_000);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Governance::proposals(proposal_id), None);
+        assert!(scheduled().is_empty());
+    });
+}
+
+#[test]
+fn votes_lock_balance_until_unlocked_after_the_vote() {
+    new_test_ext().execute_with(|| {
+        let first = propose(1);
+        let second = propose(1);
+        let third = propose(1);
+
+        assert_noop!(
+            Governance::vote(Origin::signed(2), first, true, 1_001),
+            Error::<Test>::InsufficientBalance
+        );
+        assert_ok!(Governance::vote(Origin::signed(2), first, true, 500));
+        assert_ok!(Governance::vote(Origin::signed(2), second, false, 300));
+        assert_eq!(locked(2), 500);
+        assert!(Balances::reserve(&2, 600).is_err());
+        assert_noop!(Governance::vote(Origin::signed(2), third, true, 1), Error::<Test>::TooManyVotes);
+
+        // Changing a vote replaces its balance in the tally and the lock
+        assert_ok!(Governance::vote(Origin::signed(2), first, false, 200));
+        assert_eq!(Governance::proposals(first).unwrap().tally, Tally { ayes: 0, nays: 200 });
+        assert_eq!(locked(2), 300);
+
+        assert_ok!(Governance::remove_vote(Origin::signed(2), second));
+        assert_eq!(Governance::proposals(second).unwrap().tally, Tally::default());
+        assert_eq!(locked(2), 200);
+        assert_noop!(Governance::remove_vote(Origin::signed(2), second), Error::<Test>::NotVoted);
+
+        // The lock outlives the vote until it is released
+        run_to_block(11);
+        assert_noop!(Governance::vote(Origin::signed(3), first, true, 100), Error::<Test>::UnknownProposal);
+        assert_noop!(Governance::remove_vote(Origin::signed(2), first), Error::<Test>::UnknownProposal);
+        assert_eq!(locked(2), 200);
+        assert_ok!(Governance::unlock(Origin::signed(3), 2));
+        assert_eq!(locked(2), 0);
+        assert!(Governance::voting_of(2).is_empty());
+    });
+}
+
+#[test]
+fn proposers_can_cancel_proposals_open_for_voting() {
+    new_test_ext().execute_with(|| {
+        let proposal_id = propose(1);
+        assert_ok!(Governance::vote(Origin::signed(2), proposal_id, true, 900));
+
+        assert_noop!(Governance::cancel_proposal(Origin::signed(2), proposal_id), Error::<Test>::NotProposer);
+        assert_ok!(Governance::cancel_proposal(Origin::signed(1), proposal_id));
+        System::assert_last_event(RawEvent::ProposalCancelled(proposal_id).into());
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Governance::proposals(proposal_id), None);
+        assert!(Governance::proposals_ending(11).is_empty());
+
+        assert_ok!(Governance::unlock(Origin::signed(2), 2));
+        assert_eq!(locked(2), 0);
+
+        run_to_block(11);
+        assert!(scheduled().is_empty());
+        assert_noop!(
+            Governance::cancel_proposal(Origin::signed(1), proposal_id),
+            Error::<Test>::UnknownProposal
+        );
+    });
+}
+
+#[test]
+fn votes_of_closed_proposals_are_removed_over_several_blocks() {
+    new_test_ext().execute_with(|| {
+        let proposal_id = propose(1);
+        for voter in 2..=4 {
+            assert_ok!(Governance::vote(Origin::signed(voter), proposal_id, true, 300));
+        }
+
+        run_to_block(11);
+        assert_eq!(Governance::proposals(proposal_id), None);
+        assert_eq!((2..=4).filter(|voter| Governance::votes(proposal_id, voter).is_some()).count(), 1);
+        assert_eq!(Governance::votes_to_clear(proposal_id), Some(11));
+
+        run_to_block(12);
+        assert!((2..=4).all(|voter| Governance::votes(proposal_id, voter).is_none()));
+        assert_eq!(Governance::votes_to_clear(proposal_id), None);
+    });
+}
+
+#[test]
+fn proposals_ending_in_the_same_block_are_bounded() {
+    new_test_ext().execute_with(|| {
+        propose(1);
+        propose(2);
+        assert_noop!(
+            Governance::propose(Origin::signed(3), Preimage::bound(rates_call(5)).unwrap()),
+            Error::<Test>::TooManyProposals
+        );
+
+        run_to_block(2);
+        let proposal_id = propose(3);
+        assert_eq!(Governance::proposals_ending(12), vec![proposal_id]);
+    });
+}
@@ -1,24 +1,83 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
-    decl_module, decl_storage, decl_event, decl_error,
+    decl_module, decl_storage, decl_event, decl_error, ensure,
     dispatch::DispatchResult,
-    traits::Get,
+    traits::{ConstU32, Get, Randomness},
+    BoundedVec, RuntimeDebug,
 };
 use frame_system::ensure_signed;
-use sp_std::prelude::*;
+use pallet_reputation::{ReputationEvent, ReputationHandler};
+use sp_runtime::traits::{SaturatedConversion, Saturating, Zero};
+use sp_std::{collections::btree_set::BTreeSet, prelude::*};
+
+/// Chunk trees are shared with the sample registry and P2P clients.
+pub use echochain_chunk_proof as storage_proof;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Chunk tree commitments of registered samples.
+pub trait ChunkRootProvider {
+    /// Root of the chunk tree of `sample_id` and its number of chunks.
+    fn chunk_root(sample_id: u32) -> Option<(storage_proof::Hash, u32)>;
+
+    /// Size of `sample_id` in bytes.
+    fn total_size(sample_id: u32) -> Option<u64>;
+}
+
+impl ChunkRootProvider for () {
+    fn chunk_root(_sample_id: u32) -> Option<(storage_proof::Hash, u32)> {
+        None
+    }
+
+    fn total_size(_sample_id: u32) -> Option<u64> {
+        None
+    }
+}
+
+/// Chunk a seeder has to prove it stores before `deadline`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct StorageChallenge<BlockNumber> {
+    pub sample_id: u32,
+    pub chunk_index: u32,
+    pub deadline: BlockNumber,
+}
 
 pub trait Config: frame_system::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
-    
-    /// The PoC pallet instance
+
+    /// The PoC pallet instance, which applies the governance-set storage and
+    /// bandwidth reward rates to the recorded contributions
     type Poc: proof_of_contribution::Config;
-    
-    /// Reward rate for storage contributions (per MB per block)
-    type StorageRewardRate: Get<u128>;
-    
-    /// Reward rate for bandwidth contributions (per MB transferred)
-    type BandwidthRewardRate: Get<u128>;
+
+    /// Source of the randomness used to pick challenged chunks
+    type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+
+    /// Chunk roots of the samples seeders claim to store
+    type ChunkRoots: ChunkRootProvider;
+
+    /// Reputation hook, noted when a seeder fails to back its storage claim
+    type Reputation: ReputationHandler<Self::AccountId>;
+
+    /// Blocks between two challenge rounds
+    type ChallengeInterval: Get<Self::BlockNumber>;
+
+    /// Blocks a seeder has to answer a challenge, at most `ChallengeInterval`
+    type ResponseWindow: Get<Self::BlockNumber>;
+
+    /// Maximum number of seeders challenged per round
+    type MaxChallengesPerRound: Get<u32>;
+
+    /// Maximum number of samples a seeder can announce
+    type MaxStoredSamples: Get<u32>;
+
+    /// Maximum size of a chunk, matching the P2P node's chunk size
+    type MaxChunkSize: Get<u32>;
 }
 
 decl_storage! {
@@ -26,19 +85,48 @@ decl_storage! {
         /// Storage contributions by account (MB-hours)
         pub StorageContributions get(fn storage_contributions):
             map hasher(blake2_128_concat) T::AccountId => u64;
-            
+
         /// Bandwidth contributions by account (MB transferred)
         pub BandwidthContributions get(fn bandwidth_contributions):
             map hasher(blake2_128_concat) T::AccountId => u64;
+
+        /// Samples each seeder claims to store
+        pub StoredSamples get(fn stored_samples):
+            map hasher(blake2_128_concat) T::AccountId => BoundedVec<u32, T::MaxStoredSamples>;
+
+        /// Block of each seeder's last storage record or announcement, from which the
+        /// next recorded duration is counted
+        pub LastStorageRecord get(fn last_storage_record):
+            map hasher(blake2_128_concat) T::AccountId => T::BlockNumber;
+
+        /// Storage contribution (MB-blocks) waiting for a passed challenge before it is
+        /// credited to the PoC storage pool
+        pub PendingStorageCredit get(fn pending_storage_credit):
+            map hasher(blake2_128_concat) T::AccountId => u64;
+
+        /// Outstanding challenge per seeder
+        pub Challenges get(fn challenges):
+            map hasher(blake2_128_concat) T::AccountId => Option<StorageChallenge<T::BlockNumber>>;
     }
 }
 
 decl_event!(
-    pub enum Event<T> where AccountId = <T as frame_system::Config>::AccountId {
-        /// New storage contribution recorded [who, amount_mb, duration_blocks]
+    pub enum Event<T> where AccountId = <T as frame_system::Config>::AccountId, BlockNumber = <T as frame_system::Config>::BlockNumber {
+        /// New storage contribution recorded, capped by the announced samples and the
+        /// blocks since the last record [who, amount_mb, duration_blocks]
         StorageContributed(AccountId, u64, u32),
         /// New bandwidth contribution recorded [who, amount_mb]
         BandwidthContributed(AccountId, u64),
+        /// Seeder announced the samples it stores [who, sample_count]
+        StoredSamplesAnnounced(AccountId, u32),
+        /// Seeder was challenged [who, sample_id, chunk_index, deadline]
+        StorageChallenged(AccountId, u32, u32, BlockNumber),
+        /// Challenge answered, pending storage credited [who, sample_id, credit]
+        ChallengePassed(AccountId, u32, u64),
+        /// Challenge missed or unanswerable, pending storage forfeited [who, sample_id, forfeited]
+        ChallengeFailed(AccountId, u32, u64),
+        /// Pending storage forfeited because the seeder announces no samples [who, forfeited]
+        UnbackedStorageForfeited(AccountId, u64),
     }
 );
 
@@ -46,6 +134,18 @@ decl_error! {
     pub enum Error for Module<T: Config> {
         /// Contribution amount overflow
         ContributionOverflow,
+        /// Storage recorded without announcing any registered sample
+        NoStoredSamples,
+        /// No challenge is outstanding for the caller
+        NoChallenge,
+        /// The response window of the challenge has passed
+        ChallengeExpired,
+        /// The announced or challenged sample has no registered chunk root
+        UnknownSample,
+        /// The chunk is larger than `MaxChunkSize`
+        ChunkTooLarge,
+        /// The chunk does not match the sample's chunk root
+        InvalidStorageProof,
     }
 }
 
@@ -55,34 +155,53 @@ decl_module! {
 
         fn deposit_event() = default;
 
-        /// Record storage contribution (called by P2P node)
-        #[weight = 10_000]
+        fn on_initialize(now: T::BlockNumber) -> frame_support::weights::Weight {
+            let interval = T::ChallengeInterval::get();
+            if interval.is_zero() || !(now % interval).is_zero() {
+                return Zero::zero();
+            }
+            let db_weight = <T as frame_system::Config>::DbWeight::get();
+            let expired = Self::expire_challenges(now);
+            let issued = Self::issue_challenges(now);
+            db_weight.reads_writes(
+                (expired + 3 * issued).into(),
+                (2 * expired + 2 * issued).into(),
+            )
+        }
+
+        /// Record storage contribution (called by P2P node). The amount is capped by the
+        /// total size of the announced samples and the duration by the blocks since the
+        /// last record. The contribution is held as pending credit until the seeder passes
+        /// a storage challenge.
+        #[weight = 10_000 + 1_000 * T::MaxStoredSamples::get() as u64]
         pub fn record_storage(
             origin,
             amount_mb: u64,
             duration_blocks: u32
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            
+            let announced_mb = Self::announced_mb(&who);
+            ensure!(!announced_mb.is_zero(), Error::<T>::NoStoredSamples);
+
+            let now = <frame_system::Module<T>>::block_number();
+            let elapsed = now.saturating_sub(<LastStorageRecord<T>>::get(&who));
+            let amount_mb = amount_mb.min(announced_mb);
+            let duration_blocks = duration_blocks.min(elapsed.saturated_into::<u32>());
+
             // Calculate and record contribution
             let contribution = amount_mb.checked_mul(duration_blocks.into())
                 .ok_or(Error::<T>::ContributionOverflow)?;
-                
-            <StorageContributions<T>>::mutate(&who, |v| {
+
+            <StorageContributions<T>>::try_mutate(&who, |v| -> DispatchResult {
                 *v = v.checked_add(contribution)
                     .ok_or(Error::<T>::ContributionOverflow)?;
                 Ok(())
             })?;
-            
-            // Convert to PoC contribution units and record
-            let poc_contribution = contribution.checked_mul(T::StorageRewardRate::get().into())
-                .ok_or(Error::<T>::ContributionOverflow)?;
-                
-            proof_of_contribution::Module::<T::Poc>::add_network_contribution(
-                &who,
-                poc_contribution.into()
-            )?;
-            
+            if !contribution.is_zero() {
+                <PendingStorageCredit<T>>::mutate(&who, |v| *v = v.saturating_add(contribution));
+            }
+            <LastStorageRecord<T>>::insert(&who, now);
+
             Self::deposit_event(RawEvent::StorageContributed(who, amount_mb, duration_blocks));
             Ok(())
         }
@@ -94,24 +213,154 @@ decl_module! {
             amount_mb: u64
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            
-            <BandwidthContributions<T>>::mutate(&who, |v| {
+
+            <BandwidthContributions<T>>::try_mutate(&who, |v| -> DispatchResult {
                 *v = v.checked_add(amount_mb)
                     .ok_or(Error::<T>::ContributionOverflow)?;
                 Ok(())
             })?;
-            
-            // Convert to PoC contribution units and record
-            let poc_contribution = amount_mb.checked_mul(T::BandwidthRewardRate::get().into())
-                .ok_or(Error::<T>::ContributionOverflow)?;
-                
-            proof_of_contribution::Module::<T::Poc>::add_network_contribution(
+
+            // Record MB transferred with the PoC bandwidth pool
+            proof_of_contribution::Module::<T::Poc>::add_bandwidth_contribution(
                 &who,
-                poc_contribution.into()
+                amount_mb.into()
             )?;
-            
+
             Self::deposit_event(RawEvent::BandwidthContributed(who, amount_mb));
             Ok(())
         }
+
+        /// Announce the samples the caller stores; challenges are drawn from this list.
+        /// Every sample needs a registered chunk root, duplicates are counted once.
+        #[weight = 10_000 + 1_000 * T::MaxStoredSamples::get() as u64]
+        pub fn announce_stored_samples(
+            origin,
+            sample_ids: BoundedVec<u32, T::MaxStoredSamples>
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let sample_ids = sample_ids.into_iter().collect::<BTreeSet<_>>();
+            ensure!(
+                sample_ids.iter().all(|sample_id| T::ChunkRoots::chunk_root(*sample_id).is_some()),
+                Error::<T>::UnknownSample
+            );
+            // Deduplicating never lengthens the list, so nothing is truncated
+            let sample_ids = BoundedVec::<u32, T::MaxStoredSamples>::truncate_from(sample_ids.into_iter().collect());
+
+            let count = sample_ids.len() as u32;
+            <StoredSamples<T>>::insert(&who, sample_ids);
+            <LastStorageRecord<T>>::insert(&who, <frame_system::Module<T>>::block_number());
+            Self::deposit_event(RawEvent::StoredSamplesAnnounced(who, count));
+            Ok(())
+        }
+
+        /// Answer the caller's outstanding challenge with the challenged chunk and its
+        /// Merkle proof against the sample's chunk root
+        #[weight = 50_000 + 1_000 * proof.len() as u64]
+        pub fn respond_to_challenge(
+            origin,
+            chunk: Vec<u8>,
+            proof: BoundedVec<storage_proof::Hash, ConstU32<{ storage_proof::MAX_PROOF_LENGTH }>>
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let challenge = <Challenges<T>>::get(&who).ok_or(Error::<T>::NoChallenge)?;
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(now <= challenge.deadline, Error::<T>::ChallengeExpired);
+            ensure!(chunk.len() <= T::MaxChunkSize::get() as usize, Error::<T>::ChunkTooLarge);
+
+            let (root, chunk_count) = T::ChunkRoots::chunk_root(challenge.sample_id)
+                .ok_or(Error::<T>::UnknownSample)?;
+            ensure!(
+                storage_proof::verify_chunk_proof(
+                    &root,
+                    chunk_count,
+                    challenge.chunk_index,
+                    storage_proof::chunk_leaf(&chunk),
+                    &proof,
+                ),
+                Error::<T>::InvalidStorageProof
+            );
+
+            // Record MB-blocks with the PoC storage pool
+            let credit = <PendingStorageCredit<T>>::get(&who);
+            proof_of_contribution::Module::<T::Poc>::add_storage_contribution(&who, credit.into())?;
+            <PendingStorageCredit<T>>::remove(&who);
+            <Challenges<T>>::remove(&who);
+
+            Self::deposit_event(RawEvent::ChallengePassed(who, challenge.sample_id, credit));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Module<T> {
+    /// Forfeit the pending credit of every seeder whose challenge went unanswered.
+    /// Returns the number of expired challenges.
+    fn expire_challenges(now: T::BlockNumber) -> u32 {
+        let expired = <Challenges<T>>::iter()
+            .filter(|(_, challenge)| challenge.deadline < now)
+            .collect::<Vec<_>>();
+        for (who, challenge) in expired.iter() {
+            <Challenges<T>>::remove(who);
+            Self::forfeit(who, Some(challenge.sample_id));
+        }
+        expired.len() as u32
+    }
+
+    /// Challenge up to `MaxChallengesPerRound` seeders holding pending storage credit.
+    /// Returns the number of seeders handled.
+    fn issue_challenges(now: T::BlockNumber) -> u32 {
+        let deadline = now.saturating_add(T::ResponseWindow::get());
+        let seeders = <PendingStorageCredit<T>>::iter_keys()
+            .filter(|who| !<Challenges<T>>::contains_key(who))
+            .take(T::MaxChallengesPerRound::get() as usize)
+            .collect::<Vec<_>>();
+
+        for who in seeders.iter() {
+            let (random, _) = T::Randomness::random(&(b"p2p/storage-challenge", who).encode());
+            let seed = sp_io::hashing::blake2_256(&random.encode());
+            let pick = |offset: usize| {
+                let mut bytes = [0u8; 4];
+                bytes.copy_from_slice(&seed[offset..offset + 4]);
+                u32::from_le_bytes(bytes)
+            };
+
+            let samples = <StoredSamples<T>>::get(who);
+            if samples.is_empty() {
+                // Storage claimed without announcing what is stored
+                Self::forfeit(who, None);
+                continue;
+            }
+            let sample_id = samples[pick(0) as usize % samples.len()];
+            match T::ChunkRoots::chunk_root(sample_id) {
+                Some((_, chunk_count)) if chunk_count > 0 => {
+                    let chunk_index = pick(4) % chunk_count;
+                    <Challenges<T>>::insert(who, StorageChallenge { sample_id, chunk_index, deadline });
+                    Self::deposit_event(RawEvent::StorageChallenged(who.clone(), sample_id, chunk_index, deadline));
+                }
+                _ => Self::forfeit(who, Some(sample_id)),
+            }
+        }
+        seeders.len() as u32
+    }
+
+    /// Size of the samples `who` announced, in MB (one chunk each).
+    fn announced_mb(who: &T::AccountId) -> u64 {
+        <StoredSamples<T>>::get(who)
+            .into_iter()
+            .filter_map(T::ChunkRoots::total_size)
+            .map(storage_proof::chunk_count)
+            .fold(0u64, |total, mb| total.saturating_add(mb))
+    }
+
+    /// Drop the pending credit of `who` and note the failed challenge against its
+    /// reputation. `sample_id` is `None` when no samples were announced to challenge.
+    fn forfeit(who: &T::AccountId, sample_id: Option<u32>) {
+        let forfeited = <PendingStorageCredit<T>>::take(who);
+        T::Reputation::note(who, ReputationEvent::SeedingUnreliable);
+        let event = match sample_id {
+            Some(sample_id) => RawEvent::ChallengeFailed(who.clone(), sample_id, forfeited),
+            None => RawEvent::UnbackedStorageForfeited(who.clone(), forfeited),
+        };
+        Self::deposit_event(event);
     }
 }
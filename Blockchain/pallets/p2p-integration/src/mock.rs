@@ -0,0 +1,172 @@
+use crate as pallet_p2p_integration;
+use crate::{storage_proof, ChunkRootProvider};
+use frame_support::{parameter_types, traits::{Everything, Randomness}};
+use frame_system::EnsureRoot;
+use pallet_reputation::{ReputationEvent, ReputationHandler};
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_std::{cell::RefCell, collections::btree_map::BTreeMap};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Module, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+        ProofOfContribution: proof_of_contribution::{Module, Call, Storage, Event<T>},
+        P2PIntegration: pallet_p2p_integration::{Module, Call, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const ExistentialDeposit: u128 = 1;
+    pub const ContributionReward: u128 = 10;
+    pub const MaxPrunedPerBlock: u32 = 2;
+    pub const ClaimWindow: u64 = 5;
+    pub const RootCommitTimeout: u64 = 10;
+    pub const MaxContributorsPerEpoch: u32 = 100;
+    pub const ReportDeposit: u128 = 10;
+    pub const UnsignedPriority: u64 = 1 << 20;
+    pub const ChallengeInterval: u64 = 10;
+    pub const ResponseWindow: u64 = 5;
+    pub const MaxChallengesPerRound: u32 = 10;
+    pub const MaxStoredSamples: u32 = 4;
+    pub const MaxChunkSize: u32 = 8;
+}
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<u64>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u128>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = u128;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+impl proof_of_contribution::Config for Test {
+    type Event = Event;
+    type Currency = Balances;
+    type ContributionReward = ContributionReward;
+    type ContributionOracle = EnsureRoot<u64>;
+    type MaxPrunedPerBlock = MaxPrunedPerBlock;
+    type ClaimWindow = ClaimWindow;
+    type RootCommitTimeout = RootCommitTimeout;
+    type MaxContributorsPerEpoch = MaxContributorsPerEpoch;
+    type ReportDeposit = ReportDeposit;
+    type UnsignedPriority = UnsignedPriority;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+    Call: From<LocalCall>,
+{
+    type OverarchingCall = Call;
+    type Extrinsic = UncheckedExtrinsic;
+}
+
+/// Randomness derived from the subject only, so every seeder gets a stable challenge.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+    fn random(subject: &[u8]) -> (H256, u64) {
+        (H256(sp_io::hashing::blake2_256(subject)), 0)
+    }
+}
+
+thread_local! {
+    static CHUNK_ROOTS: RefCell<BTreeMap<u32, (storage_proof::Hash, u32, u64)>> = RefCell::new(BTreeMap::new());
+    static REPUTATION_EVENTS: RefCell<Vec<(u64, ReputationEvent)>> = RefCell::new(Vec::new());
+}
+
+pub struct TestChunkRoots;
+
+impl TestChunkRoots {
+    pub fn register(sample_id: u32, root: storage_proof::Hash, chunk_count: u32, total_size: u64) {
+        CHUNK_ROOTS.with(|roots| roots.borrow_mut().insert(sample_id, (root, chunk_count, total_size)));
+    }
+}
+
+impl ChunkRootProvider for TestChunkRoots {
+    fn chunk_root(sample_id: u32) -> Option<(storage_proof::Hash, u32)> {
+        CHUNK_ROOTS.with(|roots| roots.borrow().get(&sample_id).map(|(root, count, _)| (*root, *count)))
+    }
+
+    fn total_size(sample_id: u32) -> Option<u64> {
+        CHUNK_ROOTS.with(|roots| roots.borrow().get(&sample_id).map(|(_, _, size)| *size))
+    }
+}
+
+/// Records the reputation events noted by the pallet.
+pub struct TestReputation;
+
+impl TestReputation {
+    pub fn events() -> Vec<(u64, ReputationEvent)> {
+        REPUTATION_EVENTS.with(|events| events.borrow().clone())
+    }
+}
+
+impl ReputationHandler<u64> for TestReputation {
+    fn note(who: &u64, event: ReputationEvent) {
+        REPUTATION_EVENTS.with(|events| events.borrow_mut().push((*who, event)));
+    }
+}
+
+impl pallet_p2p_integration::Config for Test {
+    type Event = Event;
+    type Poc = Test;
+    type Randomness = TestRandomness;
+    type ChunkRoots = TestChunkRoots;
+    type Reputation = TestReputation;
+    type ChallengeInterval = ChallengeInterval;
+    type ResponseWindow = ResponseWindow;
+    type MaxChallengesPerRound = MaxChallengesPerRound;
+    type MaxStoredSamples = MaxStoredSamples;
+    type MaxChunkSize = MaxChunkSize;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
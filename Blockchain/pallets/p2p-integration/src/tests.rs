@@ -0,0 +1,171 @@
+use crate::{mock::*, storage_proof, Error, RawEvent};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{ConstU32, OnInitialize},
+    BoundedVec,
+};
+use pallet_reputation::ReputationEvent;
+
+/// Chunks of the sample used in the challenge tests.
+const CHUNKS: [&[u8]; 3] = [b"chunk-0", b"chunk-1", b"chunk-2"];
+const SAMPLE: u32 = 7;
+/// Registered size of the sample, 2 MB.
+const SAMPLE_SIZE: u64 = 2 * storage_proof::CHUNK_SIZE;
+
+/// Root of the chunk tree over `chunks` and the proof of every chunk.
+fn chunk_tree(chunks: &[&[u8]]) -> (storage_proof::Hash, Vec<Vec<storage_proof::Hash>>) {
+    let leaves = chunks.iter().map(|chunk| storage_proof::chunk_leaf(chunk)).collect::<Vec<_>>();
+    let proofs = (0..leaves.len()).map(|index| storage_proof::chunk_proof(&leaves, index)).collect();
+    (storage_proof::chunk_root(&leaves), proofs)
+}
+
+/// `sample_ids` as an announcement.
+fn samples(sample_ids: &[u32]) -> BoundedVec<u32, MaxStoredSamples> {
+    sample_ids.to_vec().try_into().unwrap()
+}
+
+/// `siblings` as a challenge response proof.
+fn proof(siblings: &[storage_proof::Hash]) -> BoundedVec<storage_proof::Hash, ConstU32<{ storage_proof::MAX_PROOF_LENGTH }>> {
+    siblings.to_vec().try_into().unwrap()
+}
+
+/// Announce `SAMPLE` at block 1 and record 2 MB over the 3 blocks up to block 4.
+fn seeder_with_pending_storage(who: u64) {
+    let (root, _) = chunk_tree(&CHUNKS);
+    TestChunkRoots::register(SAMPLE, root, CHUNKS.len() as u32, SAMPLE_SIZE);
+    assert_ok!(P2PIntegration::announce_stored_samples(Origin::signed(who), samples(&[SAMPLE])));
+    System::set_block_number(4);
+    assert_ok!(P2PIntegration::record_storage(Origin::signed(who), 2, 3));
+}
+
+#[test]
+fn recorded_storage_is_capped_by_announced_samples_and_elapsed_blocks() {
+    new_test_ext().execute_with(|| {
+        let (root, _) = chunk_tree(&CHUNKS);
+        TestChunkRoots::register(SAMPLE, root, CHUNKS.len() as u32, SAMPLE_SIZE);
+        assert_ok!(P2PIntegration::announce_stored_samples(Origin::signed(1), samples(&[SAMPLE])));
+
+        System::set_block_number(4);
+        assert_ok!(P2PIntegration::record_storage(Origin::signed(1), 100, 50));
+        System::assert_last_event(RawEvent::StorageContributed(1, 2, 3).into());
+        assert_eq!(P2PIntegration::pending_storage_credit(1), 6);
+
+        // Nothing is left to claim within the same block
+        assert_ok!(P2PIntegration::record_storage(Origin::signed(1), 2, 3));
+        System::assert_last_event(RawEvent::StorageContributed(1, 2, 0).into());
+        assert_eq!(P2PIntegration::pending_storage_credit(1), 6);
+        assert_eq!(P2PIntegration::storage_contributions(1), 6);
+    });
+}
+
+#[test]
+fn storage_is_credited_only_after_a_passed_challenge() {
+    new_test_ext().execute_with(|| {
+        seeder_with_pending_storage(1);
+        assert_eq!(P2PIntegration::pending_storage_credit(1), 6);
+        assert_eq!(ProofOfContribution::network_contributions(0, 1), 0);
+
+        P2PIntegration::on_initialize(10);
+        let challenge = P2PIntegration::challenges(1).unwrap();
+        assert_eq!(challenge.sample_id, SAMPLE);
+        assert_eq!(challenge.deadline, 15);
+
+        let (_, proofs) = chunk_tree(&CHUNKS);
+        let index = challenge.chunk_index as usize;
+        assert_ok!(P2PIntegration::respond_to_challenge(
+            Origin::signed(1),
+            CHUNKS[index].to_vec(),
+            proof(&proofs[index])
+        ));
+        assert_eq!(P2PIntegration::pending_storage_credit(1), 0);
+        assert!(P2PIntegration::challenges(1).is_none());
+        // Six MB-blocks at the default storage rate
+        assert_eq!(ProofOfContribution::network_contributions(0, 1), 60);
+        System::assert_has_event(RawEvent::ChallengePassed(1, SAMPLE, 6).into());
+    });
+}
+
+#[test]
+fn wrong_chunk_is_rejected() {
+    new_test_ext().execute_with(|| {
+        seeder_with_pending_storage(1);
+        P2PIntegration::on_initialize(10);
+        let challenge = P2PIntegration::challenges(1).unwrap();
+
+        let (_, proofs) = chunk_tree(&CHUNKS);
+        let index = challenge.chunk_index as usize;
+        let other = (index + 1) % CHUNKS.len();
+        assert_noop!(
+            P2PIntegration::respond_to_challenge(Origin::signed(1), CHUNKS[other].to_vec(), proof(&proofs[index])),
+            Error::<Test>::InvalidStorageProof
+        );
+        assert_noop!(
+            P2PIntegration::respond_to_challenge(Origin::signed(2), CHUNKS[index].to_vec(), proof(&proofs[index])),
+            Error::<Test>::NoChallenge
+        );
+    });
+}
+
+#[test]
+fn unanswered_challenge_forfeits_pending_storage() {
+    new_test_ext().execute_with(|| {
+        seeder_with_pending_storage(1);
+        P2PIntegration::on_initialize(10);
+        let challenge = P2PIntegration::challenges(1).unwrap();
+
+        System::set_block_number(16);
+        let (_, proofs) = chunk_tree(&CHUNKS);
+        let index = challenge.chunk_index as usize;
+        assert_noop!(
+            P2PIntegration::respond_to_challenge(Origin::signed(1), CHUNKS[index].to_vec(), proof(&proofs[index])),
+            Error::<Test>::ChallengeExpired
+        );
+
+        P2PIntegration::on_initialize(20);
+        assert_eq!(P2PIntegration::pending_storage_credit(1), 0);
+        assert!(P2PIntegration::challenges(1).is_none());
+        assert_eq!(ProofOfContribution::network_contributions(0, 1), 0);
+        System::assert_has_event(RawEvent::ChallengeFailed(1, SAMPLE, 6).into());
+        assert_eq!(TestReputation::events(), vec![(1, ReputationEvent::SeedingUnreliable)]);
+    });
+}
+
+#[test]
+fn storage_without_announced_samples_is_forfeited() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            P2PIntegration::record_storage(Origin::signed(2), 5, 1),
+            Error::<Test>::NoStoredSamples
+        );
+
+        // Samples withdrawn while storage credit is pending
+        seeder_with_pending_storage(2);
+        assert_ok!(P2PIntegration::announce_stored_samples(Origin::signed(2), samples(&[])));
+        P2PIntegration::on_initialize(10);
+        assert_eq!(P2PIntegration::pending_storage_credit(2), 0);
+        assert!(P2PIntegration::challenges(2).is_none());
+        System::assert_has_event(RawEvent::UnbackedStorageForfeited(2, 6).into());
+        assert_eq!(TestReputation::events(), vec![(2, ReputationEvent::SeedingUnreliable)]);
+    });
+}
+
+#[test]
+fn announced_samples_are_deduplicated_and_must_be_registered() {
+    new_test_ext().execute_with(|| {
+        let (root, _) = chunk_tree(&CHUNKS);
+        TestChunkRoots::register(SAMPLE, root, CHUNKS.len() as u32, SAMPLE_SIZE);
+        assert_noop!(
+            P2PIntegration::announce_stored_samples(Origin::signed(1), samples(&[SAMPLE, 8])),
+            Error::<Test>::UnknownSample
+        );
+
+        assert_ok!(P2PIntegration::announce_stored_samples(Origin::signed(1), samples(&[SAMPLE, SAMPLE, SAMPLE, SAMPLE])));
+        System::assert_last_event(RawEvent::StoredSamplesAnnounced(1, 1).into());
+        assert_eq!(P2PIntegration::stored_samples(1).into_inner(), vec![SAMPLE]);
+
+        // Counted once when capping the recorded storage
+        System::set_block_number(2);
+        assert_ok!(P2PIntegration::record_storage(Origin::signed(1), 100, 1));
+        System::assert_last_event(RawEvent::StorageContributed(1, 2, 1).into());
+    });
+}
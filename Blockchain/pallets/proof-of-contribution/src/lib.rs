@@ -1,50 +1,231 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
-    decl_module, decl_storage, decl_event, decl_error,
+    decl_module, decl_storage, decl_event, decl_error, ensure,
     dispatch::DispatchResult,
-    traits::{Currency, Get}, // Import Get trait
+    traits::{Currency, EnsureOrigin, Get, ReservableCurrency}, // Import Get trait
+    unsigned::ValidateUnsigned,
+    PalletId, RuntimeDebug,
 };
-use sp_std::prelude::*;
+use frame_system::{
+    ensure_none, ensure_root,
+    offchain::{SendTransactionTypes, SubmitTransaction},
+};
+use sp_runtime::{
+    helpers_128bit::multiply_by_rational_with_rounding,
+    traits::{AccountIdConversion, UniqueSaturatedInto},
+    transaction_validity::{
+        InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity, ValidTransaction,
+    },
+    Rounding,
+};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 use frame_system::ensure_signed; // Import ensure_signed
 
-pub trait Config: frame_system::Config {
+pub mod merkle;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+pub trait Config: SendTransactionTypes<Call<Self>> + frame_system::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
-    type Currency: Currency<<Self as frame_system::Config>::AccountId>;
+    type Currency: ReservableCurrency<<Self as frame_system::Config>::AccountId>;
 
-    /// The amount of tokens rewarded for each unit of contribution.
+    /// The default amount of tokens rewarded for each unit of contribution,
+    /// used until governance sets explicit rates through `set_reward_parameters`.
     type ContributionReward: Get<u128>;
+
+    /// Origin allowed to credit contributions to an account and to attest
+    /// self-reported contributions (e.g. the backend oracle or Root).
+    type ContributionOracle: EnsureOrigin<Self::Origin>;
+
+    /// Maximum number of contribution entries removed in a single block while
+    /// pruning epochs whose claim window has closed.
+    type MaxPrunedPerBlock: Get<u32>;
+
+    /// Number of blocks after an epoch's root is committed during which its rewards can
+    /// be claimed. Claim proofs are rebuilt from the epoch's contribution entries, so the
+    /// entries are kept until the window closes.
+    type ClaimWindow: Get<Self::BlockNumber>;
+
+    /// Number of blocks after an epoch is closed within which its root must be committed.
+    /// Epochs without a root by then expire and are pruned with nothing claimed.
+    type RootCommitTimeout: Get<Self::BlockNumber>;
+
+    /// Maximum number of distinct contributors per epoch, bounding the on-chain
+    /// recomputation of the epoch's root when it is committed.
+    type MaxContributorsPerEpoch: Get<u32>;
+
+    /// Deposit reserved from an account while it has a self-reported contribution pending.
+    type ReportDeposit: Get<BalanceOf<Self>>;
+
+    /// Priority of the unsigned transactions committing epoch roots.
+    type UnsignedPriority: Get<TransactionPriority>;
+}
+
+/// Unique identifier of the account holding the reward pool.
+const PALLET_ID: PalletId = PalletId(*b"echo/poc");
+
+/// Index of a reward epoch.
+pub type EpochIndex = u32;
+
+/// The kind of contribution being credited or reported.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ContributionKind {
+    /// Sample uploads, metadata quality and other content work.
+    Content,
+    /// Storage provided to the P2P network.
+    Storage,
+    /// Bandwidth served to the P2P network.
+    Bandwidth,
+}
+
+impl ContributionKind {
+    /// The reward pool this kind of contribution is paid from.
+    pub fn pool(&self) -> RewardPool {
+        match self {
+            ContributionKind::Content => RewardPool::Content,
+            ContributionKind::Storage | ContributionKind::Bandwidth => RewardPool::Network,
+        }
+    }
+}
+
+/// A reward pool with its own per-epoch budget and accounting.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum RewardPool {
+    /// Rewards for content contributions.
+    Content,
+    /// Rewards for storage and bandwidth contributions.
+    Network,
+}
+
+/// Reward points credited per unit of each contribution kind.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct RewardRates {
+    pub content: u128,
+    pub storage: u128,
+    pub bandwidth: u128,
+}
+
+/// How much a reward pool earned and pays out in a closed epoch.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct PoolSettlement {
+    /// Reward points credited to the pool during the epoch.
+    pub points: u128,
+    /// Amount paid out, i.e. the points capped at the pool's budget.
+    pub payout: u128,
+}
+
+/// A closed reward epoch. Entitlements are computed off-chain and committed as a Merkle root.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct EpochInfo<BlockNumber> {
+    /// Block at which the epoch was closed.
+    pub closed_at: BlockNumber,
+    /// Settlement of the content pool.
+    pub content: PoolSettlement,
+    /// Settlement of the network pool.
+    pub network: PoolSettlement,
+    /// Number of accounts that contributed during the epoch.
+    pub contributors: u32,
+    /// Total reward owed for the epoch, the sum of both pool payouts.
+    pub total_rewards: u128,
+    /// Merkle root over `(epoch, account, amount)` leaves, once committed.
+    pub root: Option<merkle::Hash>,
+    /// Block at which the root was committed, opening the claim window.
+    pub committed_at: Option<BlockNumber>,
+    /// Total of the committed leaves; at most `total_rewards` because pool shares round down.
+    pub committed_total: u128,
+    /// Amount claimed so far against this epoch.
+    pub claimed: u128,
 }
 
 decl_storage! {
     trait Store for Module<T: Config> as ProofOfContribution {
-        /// Storage for tracking content contributions (e.g., sample uploads, metadata quality)
+        /// Content reward points per epoch (e.g., sample uploads, metadata quality)
         pub ContentContributions get(fn content_contributions):
-            map hasher(blake2_128_concat) T::AccountId => u128; // Changed to u128 for larger values
+            double_map hasher(twox_64_concat) EpochIndex, hasher(blake2_128_concat) T::AccountId => u128;
         
-        /// Storage for tracking network contributions (e.g., storage provided, bandwidth used)
+        /// Network reward points per epoch (e.g., storage provided, bandwidth used)
         pub NetworkContributions get(fn network_contributions):
-            map hasher(blake2_128_concat) T::AccountId => u128; // Changed to u128 for larger values
+            double_map hasher(twox_64_concat) EpochIndex, hasher(blake2_128_concat) T::AccountId => u128;
 
-        /// Total accumulated contributions that are yet to be claimed as rewards.
+        /// Total rewards of closed epochs that are yet to be claimed.
         pub TotalUnclaimedRewards get(fn total_unclaimed_rewards): u128;
+
+        /// Running sum of reward points per pool in the current epoch, kept up to date
+        /// so an epoch can be closed without iterating every contributor.
+        pub PoolPoints get(fn pool_points): map hasher(twox_64_concat) RewardPool => u128;
+
+        /// Number of distinct contributors in the current epoch.
+        pub EpochContributors get(fn epoch_contributors): u32;
+
+        /// Maximum payout of each pool per epoch. Pools without a budget pay out every point.
+        pub PoolBudgets get(fn pool_budget): map hasher(twox_64_concat) RewardPool => Option<u128>;
+
+        /// Reward rates set by governance; `ContributionReward` applies until they are set.
+        pub Rates get(fn rates): Option<RewardRates>;
+
+        /// The epoch currently accepting contributions.
+        pub CurrentEpoch get(fn current_epoch): EpochIndex;
+
+        /// Closed epochs and their committed reward roots.
+        pub Epochs get(fn epochs):
+            map hasher(twox_64_concat) EpochIndex => Option<EpochInfo<T::BlockNumber>>;
+
+        /// Accounts that have already claimed their reward for an epoch.
+        pub RewardClaimed get(fn reward_claimed):
+            double_map hasher(twox_64_concat) EpochIndex, hasher(blake2_128_concat) T::AccountId => bool;
+
+        /// Oldest epoch whose contribution entries have not been pruned yet.
+        /// Entries are pruned once the epoch's claim window has closed.
+        pub NextEpochToPrune get(fn next_epoch_to_prune): EpochIndex;
         
         /// Last block number when rewards were distributed
         pub LastRewardBlock get(fn last_reward_block): T::BlockNumber;
         
         /// Reward distribution interval in blocks
         pub RewardInterval get(fn reward_interval): T::BlockNumber;
+
+        /// Self-reported contributions awaiting attestation by the `ContributionOracle`.
+        /// Each entry holds `ReportDeposit` of the account in reserve.
+        pub PendingContributions get(fn pending_contributions):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) ContributionKind => u128;
     }
 }
 
 decl_event!(
     pub enum Event<T> where AccountId = <T as frame_system::Config>::AccountId {
-        /// A user has contributed content. [who, amount]
+        /// A user has contributed content. [who, points]
         ContentContributed(AccountId, u128),
-        /// A user has contributed to the network. [who, amount]
+        /// A user has contributed to the network. [who, points]
         NetworkContributed(AccountId, u128),
-        /// A user has claimed rewards for their contributions. [who, amount]
-        RewardsClaimed(AccountId, u128),
+        /// A user has claimed rewards for their contributions. [who, epoch, amount]
+        RewardsClaimed(AccountId, EpochIndex, u128),
+        /// A user has self-reported a contribution awaiting attestation. [who, kind, amount]
+        ContributionReported(AccountId, ContributionKind, u128),
+        /// A self-reported contribution has been attested and credited. [who, kind, amount]
+        ContributionAttested(AccountId, ContributionKind, u128),
+        /// A self-reported contribution has been rejected and discarded. [who, kind, amount]
+        ContributionRejected(AccountId, ContributionKind, u128),
+        /// A reward epoch has been closed. [epoch, total_rewards]
+        EpochClosed(EpochIndex, u128),
+        /// A reward pool has been settled for a closed epoch. [epoch, pool, points, payout]
+        PoolSettled(EpochIndex, RewardPool, u128, u128),
+        /// Reward rates have been updated. [content, storage, bandwidth]
+        RewardRatesUpdated(u128, u128, u128),
+        /// A pool's per-epoch budget has been updated. [pool, budget]
+        PoolBudgetUpdated(RewardPool, Option<u128>),
+        /// The Merkle root of an epoch's entitlements has been committed. [epoch, root]
+        EpochRootCommitted(EpochIndex, merkle::Hash),
+        /// The claim window of an epoch has closed, or its root was never committed, and
+        /// the epoch has been pruned. Unclaimed rewards stay in the pool. [epoch, unclaimed]
+        EpochPruned(EpochIndex, u128),
     }
 );
 
@@ -56,6 +237,32 @@ decl_error! {
         RewardCalculationOverflow,
         /// Insufficient funds in the module's account to pay out rewards.
         InsufficientModuleBalance,
+        /// There is no pending self-reported contribution of this kind for the account.
+        NoPendingContribution,
+        /// The epoch does not exist or has not been closed yet.
+        UnknownEpoch,
+        /// A root has already been committed for this epoch.
+        EpochRootAlreadyCommitted,
+        /// No root has been committed for this epoch yet.
+        EpochRootNotCommitted,
+        /// The root does not match the one computed from the epoch's contributions.
+        EpochRootMismatch,
+        /// Reward rates must be non-zero.
+        InvalidRewardParameters,
+        /// The Merkle proof does not match the epoch root.
+        InvalidMerkleProof,
+        /// The reward for this epoch has already been claimed.
+        AlreadyClaimed,
+        /// The claim would pay out more than the epoch's reward total.
+        ClaimExceedsEpochBudget,
+        /// The claim window of the epoch has closed.
+        ClaimWindowClosed,
+        /// The epoch's root was not committed within `RootCommitTimeout` blocks of its close.
+        EpochExpired,
+        /// The epoch already has `MaxContributorsPerEpoch` contributors.
+        TooManyContributors,
+        /// Self-reported contributions must be non-zero.
+        EmptyReport,
     }
 }
 
@@ -66,86 +273,179 @@ decl_module! {
         fn deposit_event() = default;
 
         fn on_initialize(now: T::BlockNumber) -> frame_support::weights::Weight {
-            let reward_interval = <RewardInterval>::get();
-            let last_reward_block = <LastRewardBlock>::get();
-            
+            let db_weight = <T as frame_system::Config>::DbWeight::get();
+            let reward_interval = <RewardInterval<T>>::get();
+            let last_reward_block = <LastRewardBlock<T>>::get();
+            let mut weight = db_weight.reads(2);
+
             if now >= last_reward_block + reward_interval {
-                // Distribute rewards automatically
-                if let Err(e) = Self::distribute_rewards() {
-                    log::error!("Failed to distribute rewards: {:?}", e);
+                weight = weight.saturating_add(Self::close_epoch(now));
+                <LastRewardBlock<T>>::put(now);
+                weight = weight.saturating_add(db_weight.writes(1));
+            }
+
+            // Clear a bounded batch of contribution entries from epochs past their claim window
+            weight.saturating_add(Self::prune_epochs(now, T::MaxPrunedPerBlock::get()))
+        }
+
+        /// Compute the entitlements of the most recently closed epoch and submit their root
+        /// through an unsigned `commit_epoch_root`, until it is committed or the epoch expires.
+        fn offchain_worker(now: T::BlockNumber) {
+            let current_epoch = <CurrentEpoch>::get();
+            if current_epoch == 0 {
+                return;
+            }
+            let epoch = current_epoch - 1;
+            match <Epochs<T>>::get(epoch) {
+                Some(info) if info.root.is_none() && !Self::epoch_expired(&info, now) => {
+                    let (root, _) = Self::compute_epoch_root(epoch);
+                    let call = Call::commit_epoch_root(epoch, root);
+                    if SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into()).is_err() {
+                        log::warn!(target: "proof-of-contribution", "failed to submit the root of epoch {}", epoch);
+                    }
                 }
-                <LastRewardBlock>::put(now);
+                _ => {}
             }
-            
-            // Return minimal weight since this is a simple check
-            <T as frame_system::Config>::DbWeight::get().reads_writes(2, 1)
         }
 
         /// Record a content contribution for a given account.
-        /// This function would typically be called by other pallets or smart contracts
-        /// when a user performs an action deemed a "content contribution".
+        /// Only the `ContributionOracle` may credit contributions directly; users
+        /// must go through `report_contribution` and wait for attestation.
+        #[weight = 10_000]
+        pub fn record_content_contribution(origin, who: T::AccountId, amount: u128) -> DispatchResult {
+            T::ContributionOracle::ensure_origin(origin)?;
+            Self::add_content_contribution(&who, amount)
+        }
+
+        /// Record a storage contribution for a given account.
+        /// Only the `ContributionOracle` may credit contributions directly, e.g. after
+        /// verifying storage reported by the P2P network.
+        #[weight = 10_000]
+        pub fn record_storage_contribution(origin, who: T::AccountId, amount: u128) -> DispatchResult {
+            T::ContributionOracle::ensure_origin(origin)?;
+            Self::add_storage_contribution(&who, amount)
+        }
+
+        /// Record a bandwidth contribution for a given account.
+        /// Only the `ContributionOracle` may credit contributions directly, e.g. after
+        /// verifying bandwidth reported by the P2P network.
+        #[weight = 10_000]
+        pub fn record_bandwidth_contribution(origin, who: T::AccountId, amount: u128) -> DispatchResult {
+            T::ContributionOracle::ensure_origin(origin)?;
+            Self::add_bandwidth_contribution(&who, amount)
+        }
+
+        /// Self-report a contribution. The amount is held as pending and is only
+        /// credited once the `ContributionOracle` attests it. `ReportDeposit` is reserved
+        /// while the report is pending and returned once it is attested or rejected.
         #[weight = 10_000]
-        pub fn record_content_contribution(origin, amount: u128) -> DispatchResult {
+        pub fn report_contribution(origin, kind: ContributionKind, amount: u128) -> DispatchResult {
             let sender = ensure_signed(origin)?;
-            <ContentContributions<T>>::mutate(sender.clone(), |contributions| {
-                *contributions = contributions.checked_add(amount).ok_or(Error::<T>::RewardCalculationOverflow)?;
-            });
-            Self::deposit_event(RawEvent::ContentContributed(sender, amount));
+            ensure!(amount > 0, Error::<T>::EmptyReport);
+            if <PendingContributions<T>>::get(&sender, kind) == 0 {
+                T::Currency::reserve(&sender, T::ReportDeposit::get())?;
+            }
+            <PendingContributions<T>>::try_mutate(&sender, kind, |pending| -> DispatchResult {
+                *pending = pending.checked_add(amount).ok_or(Error::<T>::RewardCalculationOverflow)?;
+                Ok(())
+            })?;
+            Self::deposit_event(RawEvent::ContributionReported(sender, kind, amount));
             Ok(())
         }
 
-        /// Record a network contribution for a given account.
-        /// This function would typically be called by other pallets or network modules
-        /// when a user provides storage, bandwidth, or other network resources.
+        /// Attest a pending self-reported contribution and credit it to the account.
         #[weight = 10_000]
-        pub fn record_network_contribution(origin, amount: u128) -> DispatchResult {
-            let sender = ensure_signed(origin)?;
-            <NetworkContributions<T>>::mutate(sender.clone(), |contributions| {
-                *contributions = contributions.checked_add(amount).ok_or(Error::<T>::RewardCalculationOverflow)?;
-            });
-            Self::deposit_event(RawEvent::NetworkContributed(sender, amount));
+        pub fn attest_contribution(origin, who: T::AccountId, kind: ContributionKind) -> DispatchResult {
+            T::ContributionOracle::ensure_origin(origin)?;
+            let amount = Self::take_pending(&who, kind)?;
+            Self::add_contribution(&who, kind, amount)?;
+            Self::deposit_event(RawEvent::ContributionAttested(who, kind, amount));
             Ok(())
         }
 
-        /// Allows a user to claim rewards based on their accumulated contributions.
-        /// The reward calculation is based on a simple formula:
-        /// (ContentContributions + NetworkContributions) * ContributionReward
+        /// Reject a pending self-reported contribution without crediting it.
         #[weight = 10_000]
-        pub fn claim_rewards(origin) -> DispatchResult {
-            let sender = ensure_signed(origin)?;
+        pub fn reject_contribution(origin, who: T::AccountId, kind: ContributionKind) -> DispatchResult {
+            T::ContributionOracle::ensure_origin(origin)?;
+            let amount = Self::take_pending(&who, kind)?;
+            Self::deposit_event(RawEvent::ContributionRejected(who, kind, amount));
+            Ok(())
+        }
 
-            let content_contrib = <ContentContributions<T>>::take(&sender);
-            let network_contrib = <NetworkContributions<T>>::take(&sender);
+        /// Set the reward rates and distribution interval, e.g. through a governance proposal.
+        #[weight = 10_000]
+        pub fn update_reward_parameters(
+            origin,
+            content_reward: u128,
+            storage_reward: u128,
+            bandwidth_reward: u128,
+            reward_interval: u32
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::set_reward_parameters(content_reward, storage_reward, bandwidth_reward, reward_interval)
+        }
 
-            let total_contributions = content_contrib.checked_add(network_contrib)
-                .ok_or(Error::<T>::RewardCalculationOverflow)?;
+        /// Set the per-epoch budget of a reward pool, or remove it with `None`.
+        #[weight = 10_000]
+        pub fn set_pool_budget(origin, pool: RewardPool, budget: Option<u128>) -> DispatchResult {
+            ensure_root(origin)?;
+            <PoolBudgets>::set(pool, budget);
+            Self::deposit_event(RawEvent::PoolBudgetUpdated(pool, budget));
+            Ok(())
+        }
 
-            if total_contributions == 0 {
-                return Err(Error::<T>::NoContributionsToClaim.into());
-            }
+        /// Commit the Merkle root of a closed epoch's entitlements, opening its claim window.
+        /// Submitted unsigned by the offchain worker. The root is recomputed on-chain from
+        /// the epoch's contribution entries, at most `MaxContributorsPerEpoch` of them, and
+        /// accepted only if it matches.
+        #[weight = 10_000 + 10_000 * T::MaxContributorsPerEpoch::get() as u64]
+        pub fn commit_epoch_root(origin, epoch: EpochIndex, root: merkle::Hash) -> DispatchResult {
+            ensure_none(origin)?;
+            let (mut info, total) = Self::check_epoch_root(epoch, &root)?;
+            info.root = Some(root);
+            info.committed_at = Some(<frame_system::Module<T>>::block_number());
+            info.committed_total = total;
+            <Epochs<T>>::insert(epoch, info);
+            Self::deposit_event(RawEvent::EpochRootCommitted(epoch, root));
+            Ok(())
+        }
 
-            let reward_per_unit = T::ContributionReward::get();
-            let reward_amount = total_contributions.checked_mul(reward_per_unit)
+        /// Claim the reward owed for an epoch by proving `(epoch, sender, amount)`
+        /// is a leaf of the epoch's committed Merkle root.
+        #[weight = 10_000 + 1_000 * proof.len() as u64]
+        pub fn claim(origin, epoch: EpochIndex, amount: u128, proof: Vec<merkle::Hash>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            ensure!(proof.len() <= merkle::MAX_PROOF_DEPTH, Error::<T>::InvalidMerkleProof);
+            ensure!(amount > 0, Error::<T>::NoContributionsToClaim);
+            ensure!(!<RewardClaimed<T>>::get(epoch, &sender), Error::<T>::AlreadyClaimed);
+
+            let mut info = <Epochs<T>>::get(epoch).ok_or(Error::<T>::UnknownEpoch)?;
+            let root = info.root.ok_or(Error::<T>::EpochRootNotCommitted)?;
+            ensure!(
+                !Self::epoch_expired(&info, <frame_system::Module<T>>::block_number()),
+                Error::<T>::ClaimWindowClosed
+            );
+            let leaf = merkle::leaf_hash(epoch, &sender, amount);
+            ensure!(merkle::verify_proof(&root, leaf, &proof), Error::<T>::InvalidMerkleProof);
+
+            let claimed = info.claimed.checked_add(amount)
                 .ok_or(Error::<T>::RewardCalculationOverflow)?;
-
-            if reward_amount == 0 {
-                return Err(Error::<T>::NoContributionsToClaim.into());
-            }
+            ensure!(claimed <= info.committed_total, Error::<T>::ClaimExceedsEpochBudget);
 
             // Transfer the reward from the module's account to the sender's account
             T::Currency::transfer(
                 &Self::account_id(), // The module's account
                 &sender,
-                reward_amount.into(), // Convert u128 to BalanceOf<T::Currency>
+                amount.into(), // Convert u128 to BalanceOf<T::Currency>
                 frame_support::traits::ExistenceRequirement::AllowDeath,
             )?;
 
-            // Update total unclaimed rewards
-            <TotalUnclaimedRewards>::mutate(|total| {
-                *total = total.checked_sub(reward_amount).unwrap_or_default();
-            });
+            info.claimed = claimed;
+            <Epochs<T>>::insert(epoch, info);
+            <RewardClaimed<T>>::insert(epoch, &sender, true);
+            <TotalUnclaimedRewards>::mutate(|total| *total = total.saturating_sub(amount));
 
-            Self::deposit_event(RawEvent::RewardsClaimed(sender, reward_amount));
+            Self::deposit_event(RawEvent::RewardsClaimed(sender, epoch, amount));
             Ok(())
         }
     }
@@ -156,223 +456,273 @@ impl<T: Config> Module<T> {
     /// This is used to hold and distribute rewards.
     pub fn account_id() -> T::AccountId {
         // This is a common pattern for generating a unique pallet account ID.
-        // The `b"poc_mod"` is a unique byte string for this pallet.
-        <pallet_balances::Module<T> as frame_support::traits::Module>::module_account_id()
+        PALLET_ID.into_account_truncating()
     }
 
     /// Public function to add content contributions (callable by other pallets/runtime)
     pub fn add_content_contribution(who: &T::AccountId, amount: u128) -> DispatchResult {
-        <ContentContributions<T>>::mutate(who.clone(), |contributions| {
-            *contributions = contributions.checked_add(amount).ok_or(Error::<T>::RewardCalculationOverflow)?;
-        });
-        <TotalUnclaimedRewards>::mutate(|total| {
-            *total = total.checked_add(amount.checked_mul(T::ContributionReward::get()).ok_or(Error::<T>::RewardCalculationOverflow)?).ok_or(Error::<T>::RewardCalculationOverflow)?;
-        });
-        Self::deposit_event(RawEvent::ContentContributed(who.clone(), amount));
-        Ok(())
+        Self::add_contribution(who, ContributionKind::Content, amount)
     }
 
-    /// Public function to add network contributions (callable by other pallets/runtime)
-    pub fn add_network_contribution(who: &T::AccountId, amount: u128) -> DispatchResult {
-        <NetworkContributions<T>>::mutate(who.clone(), |contributions| {
-            *contributions = contributions.checked_add(amount).ok_or(Error::<T>::RewardCalculationOverflow)?;
-        });
-        <TotalUnclaimedRewards>::mutate(|total| {
-            *total = total.checked_add(amount.checked_mul(T::ContributionReward::get()).ok_or(Error::<T>::RewardCalculationOverflow)?).ok_or(Error::<T>::RewardCalculationOverflow)?;
-        });
-        Self::deposit_event(RawEvent::NetworkContributed(who.clone(), amount));
+    /// Public function to add storage contributions (callable by other pallets/runtime)
+    pub fn add_storage_contribution(who: &T::AccountId, amount: u128) -> DispatchResult {
+        Self::add_contribution(who, ContributionKind::Storage, amount)
+    }
+
+    /// Public function to add bandwidth contributions (callable by other pallets/runtime)
+    pub fn add_bandwidth_contribution(who: &T::AccountId, amount: u128) -> DispatchResult {
+        Self::add_contribution(who, ContributionKind::Bandwidth, amount)
+    }
+
+    /// The reward points credited per unit of `kind`.
+    pub fn reward_rate(kind: ContributionKind) -> u128 {
+        match <Rates>::get() {
+            Some(rates) => match kind {
+                ContributionKind::Content => rates.content,
+                ContributionKind::Storage => rates.storage,
+                ContributionKind::Bandwidth => rates.bandwidth,
+            },
+            None => T::ContributionReward::get(),
+        }
+    }
+
+    /// Convert `amount` units of `kind` into reward points and credit them to the
+    /// account's entry in the matching pool for the current epoch.
+    fn add_contribution(who: &T::AccountId, kind: ContributionKind, amount: u128) -> DispatchResult {
+        let epoch = <CurrentEpoch>::get();
+        let pool = kind.pool();
+        let points = amount.checked_mul(Self::reward_rate(kind))
+            .ok_or(Error::<T>::RewardCalculationOverflow)?;
+
+        let is_new_contributor = <ContentContributions<T>>::get(epoch, who) == 0
+            && <NetworkContributions<T>>::get(epoch, who) == 0;
+        if is_new_contributor && points > 0 {
+            ensure!(
+                <EpochContributors>::get() < T::MaxContributorsPerEpoch::get(),
+                Error::<T>::TooManyContributors
+            );
+        }
+        let credit = |contributions: &mut u128| -> DispatchResult {
+            *contributions = contributions.checked_add(points).ok_or(Error::<T>::RewardCalculationOverflow)?;
+            Ok(())
+        };
+        match pool {
+            RewardPool::Content => <ContentContributions<T>>::try_mutate(epoch, who, credit)?,
+            RewardPool::Network => <NetworkContributions<T>>::try_mutate(epoch, who, credit)?,
+        }
+        <PoolPoints>::try_mutate(pool, credit)?;
+        if is_new_contributor && points > 0 {
+            <EpochContributors>::mutate(|count| *count = count.saturating_add(1));
+        }
+
+        match pool {
+            RewardPool::Content => Self::deposit_event(RawEvent::ContentContributed(who.clone(), points)),
+            RewardPool::Network => Self::deposit_event(RawEvent::NetworkContributed(who.clone(), points)),
+        }
         Ok(())
     }
 
-    /// Update reward parameters (called by governance pallet)
+    /// Remove and return a pending self-reported contribution, returning its deposit.
+    fn take_pending(who: &T::AccountId, kind: ContributionKind) -> Result<u128, Error<T>> {
+        let amount = <PendingContributions<T>>::take(who, kind);
+        if amount == 0 {
+            return Err(Error::<T>::NoPendingContribution);
+        }
+        T::Currency::unreserve(who, T::ReportDeposit::get());
+        Ok(amount)
+    }
+
+    /// Update reward parameters
     pub fn set_reward_parameters(
         content_reward: u128,
         storage_reward: u128,
         bandwidth_reward: u128,
         reward_interval: u32
     ) -> DispatchResult {
-        // In a real implementation, we would update the pallet's configuration here
-        // For now we'll just validate the parameters
         if content_reward == 0 || storage_reward == 0 || bandwidth_reward == 0 {
-            return Err(Error::<T>::RewardCalculationOverflow.into());
+            return Err(Error::<T>::InvalidRewardParameters.into());
         }
-        
-        // Update reward interval
-        <RewardInterval>::put(reward_interval);
-        
+
+        // Rates apply to contributions credited from now on
+        <Rates>::put(RewardRates {
+            content: content_reward,
+            storage: storage_reward,
+            bandwidth: bandwidth_reward,
+        });
+        <RewardInterval<T>>::put(T::BlockNumber::from(reward_interval));
+
+        Self::deposit_event(RawEvent::RewardRatesUpdated(content_reward, storage_reward, bandwidth_reward));
         Ok(())
     }
 
-    /// Distribute rewards to all contributors based on their contributions
-    fn distribute_rewards() -> DispatchResult {
-        let reward_per_unit = T::ContributionReward::get();
-        let total_rewards = <TotalUnclaimedRewards>::get();
-        
-        if total_rewards == 0 {
-            return Ok(()); // Nothing to distribute
+    /// Close the current epoch if it has any contributions and start the next one.
+    /// Each pool pays out its points, capped at the pool's budget.
+    fn close_epoch(now: T::BlockNumber) -> frame_support::weights::Weight {
+        let db_weight = <T as frame_system::Config>::DbWeight::get();
+        let settle = |pool: RewardPool| {
+            let points = <PoolPoints>::get(pool);
+            let payout = <PoolBudgets>::get(pool).map_or(points, |budget| points.min(budget));
+            PoolSettlement { points, payout }
+        };
+        let content = settle(RewardPool::Content);
+        let network = settle(RewardPool::Network);
+        if content.points == 0 && network.points == 0 {
+            return db_weight.reads(4); // Nothing to reward
         }
 
-        // Calculate total contributions across all users
-        let mut total_contributions = 0u128;
-        <ContentContributions<T>>::iter().for_each(|(_, amount)| {
-            total_contributions = total_contributions.saturating_add(amount);
-        });
-        <NetworkContributions<T>>::iter().for_each(|(_, amount)| {
-            total_contributions = total_contributions.saturating_add(amount);
+        let epoch = <CurrentEpoch>::get();
+        let total_rewards = content.payout.saturating_add(network.payout);
+        <Epochs<T>>::insert(epoch, EpochInfo {
+            closed_at: now,
+            content,
+            network,
+            contributors: <EpochContributors>::take(),
+            total_rewards,
+            root: None,
+            committed_at: None,
+            committed_total: 0,
+            claimed: 0,
         });
+        <CurrentEpoch>::put(epoch.saturating_add(1));
+        <PoolPoints>::remove(RewardPool::Content);
+        <PoolPoints>::remove(RewardPool::Network);
+        <TotalUnclaimedRewards>::mutate(|total| *total = total.saturating_add(total_rewards));
+
+        Self::deposit_event(RawEvent::PoolSettled(epoch, RewardPool::Content, content.points, content.payout));
+        Self::deposit_event(RawEvent::PoolSettled(epoch, RewardPool::Network, network.points, network.payout));
+        Self::deposit_event(RawEvent::EpochClosed(epoch, total_rewards));
+        db_weight.reads_writes(7, 6)
+    }
 
-        if total_contributions == 0 {
-            return Ok(()); // No contributions to reward
+    /// Whether an epoch is over at `now`: its claim window has closed or, if its root was
+    /// never committed, `RootCommitTimeout` has passed since it was closed.
+    fn epoch_expired(info: &EpochInfo<T::BlockNumber>, now: T::BlockNumber) -> bool {
+        match info.committed_at {
+            Some(committed_at) => now >= committed_at + T::ClaimWindow::get(),
+            None => now >= info.closed_at + T::RootCommitTimeout::get(),
         }
+    }
 
-        // Distribute rewards proportionally
-        let mut distributed = 0u128;
-        let mut errors = Vec::new();
-
-        // Distribute content rewards
-        <ContentContributions<T>>::iter().try_for_each(|(who, amount)| {
-            let reward = amount.checked_mul(reward_per_unit)
-                .and_then(|r| r.checked_mul(total_rewards))
-                .and_then(|r| r.checked_div(total_contributions))
-                .ok_or(Error::<T>::RewardCalculationOverflow)?;
-
-            if reward > 0 {
-                T::Currency::transfer(
-                    &Self::account_id(),
-                    &who,
-                    reward.into(),
-                    frame_support::traits::ExistenceRequirement::AllowDeath,
-                )?;
-                distributed = distributed.checked_add(reward)
-                    .ok_or(Error::<T>::RewardCalculationOverflow)?;
-                Self::deposit_event(RawEvent::RewardsClaimed(who, reward));
-            }
-            Ok(())
-        }).map_err(|e| {
-            errors.push(e);
-            e
-        })?;
-
-        // Distribute network rewards
-        <NetworkContributions<T>>::iter().try_for_each(|(who, amount)| {
-            let reward = amount.checked_mul(reward_per_unit)
-                .and_then(|r| r.checked_mul(total_rewards))
-                .and_then(|r| r.checked_div(total_contributions))
-                .ok_or(Error::<T>::RewardCalculationOverflow)?;
+    /// The epoch to commit `root` for, and the total of its entitlements, if `root` is the
+    /// one computed from the epoch's contribution entries and the epoch awaits it.
+    fn check_epoch_root(
+        epoch: EpochIndex,
+        root: &merkle::Hash,
+    ) -> Result<(EpochInfo<T::BlockNumber>, u128), Error<T>> {
+        let info = <Epochs<T>>::get(epoch).ok_or(Error::<T>::UnknownEpoch)?;
+        ensure!(info.root.is_none(), Error::<T>::EpochRootAlreadyCommitted);
+        ensure!(
+            !Self::epoch_expired(&info, <frame_system::Module<T>>::block_number()),
+            Error::<T>::EpochExpired
+        );
+        let (expected, total) = Self::compute_epoch_root(epoch);
+        ensure!(*root == expected, Error::<T>::EpochRootMismatch);
+        Ok((info, total))
+    }
 
-            if reward > 0 {
-                T::Currency::transfer(
-                    &Self::account_id(),
-                    &who,
-                    reward.into(),
-                    frame_support::traits::ExistenceRequirement::AllowDeath,
-                )?;
-                distributed = distributed.checked_add(reward)
-                    .ok_or(Error::<T>::RewardCalculationOverflow)?;
-                Self::deposit_event(RawEvent::RewardsClaimed(who, reward));
+    /// Remove at most `limit` contribution and claim entries of the oldest epoch that has
+    /// expired, then the epoch itself. Proofs can no longer be built for the epoch once its
+    /// entries are gone.
+    fn prune_epochs(now: T::BlockNumber, limit: u32) -> frame_support::weights::Weight {
+        let db_weight = <T as frame_system::Config>::DbWeight::get();
+        let epoch = <NextEpochToPrune>::get();
+        let info = match <Epochs<T>>::get(epoch) {
+            Some(info) if Self::epoch_expired(&info, now) => info,
+            _ => return db_weight.reads(2),
+        };
+
+        let mut weight = db_weight.reads(2);
+        let mut removed = 0u32;
+        while removed < limit {
+            let drained = <ContentContributions<T>>::drain_prefix(epoch).next().is_some()
+                || <NetworkContributions<T>>::drain_prefix(epoch).next().is_some()
+                || <RewardClaimed<T>>::drain_prefix(epoch).next().is_some();
+            if !drained {
+                // Rewards nobody claimed in time are no longer owed
+                let unclaimed = info.total_rewards.saturating_sub(info.claimed);
+                <TotalUnclaimedRewards>::mutate(|total| *total = total.saturating_sub(unclaimed));
+                <Epochs<T>>::remove(epoch);
+                <NextEpochToPrune>::put(epoch.saturating_add(1));
+                Self::deposit_event(RawEvent::EpochPruned(epoch, unclaimed));
+                return weight.saturating_add(db_weight.reads_writes(1, 3));
             }
-            Ok(())
-        }).map_err(|e| {
-            errors.push(e);
-            e
-        })?;
-
-        // Update total unclaimed rewards
-        <TotalUnclaimedRewards>::mutate(|total| {
-            *total = total.saturating_sub(distributed);
-        });
-
-        // Clear all contributions after distribution
-        <ContentContributions<T>>::remove_all();
-        <NetworkContributions<T>>::remove_all();
-
-        if !errors.is_empty() {
-            log::error!("Errors during reward distribution: {:?}", errors);
-            return Err(Error::<T>::RewardCalculationOverflow.into());
+            removed += 1;
+            weight = weight.saturating_add(db_weight.reads_writes(1, 1));
         }
+        weight
+    }
 
-        Ok(())
+    /// Reward owed to each contributor of a closed `epoch`, ordered by account.
+    /// Each pool pays its contributors a share of the pool payout proportional to their points.
+    /// This is the leaf set the offchain worker commits to and that claim proofs are built from.
+    pub fn epoch_entitlements(epoch: EpochIndex) -> Vec<(T::AccountId, u128)> {
+        let info = match <Epochs<T>>::get(epoch) {
+            Some(info) => info,
+            None => return Vec::new(),
+        };
+
+        let mut entitlements: BTreeMap<T::AccountId, u128> = BTreeMap::new();
+        let content = <ContentContributions<T>>::iter_prefix(epoch)
+            .map(|(who, points)| (who, Self::pool_share(points, &info.content)));
+        let network = <NetworkContributions<T>>::iter_prefix(epoch)
+            .map(|(who, points)| (who, Self::pool_share(points, &info.network)));
+        for (who, share) in content.chain(network) {
+            let entry = entitlements.entry(who).or_default();
+            *entry = entry.saturating_add(share);
         }
+        entitlements.into_iter().filter(|(_, amount)| *amount > 0).collect()
+    }
 
-        // Calculate total contributions across all users
-        let mut total_contributions = 0u128;
-        <ContentContributions<T>>::iter().for_each(|(_, amount)| {
-            total_contributions = total_contributions.saturating_add(amount);
-        });
-        <NetworkContributions<T>>::iter().for_each(|(_, amount)| {
-            total_contributions = total_contributions.saturating_add(amount);
-        });
-
-        if total_contributions == 0 {
-            return Ok(()); // No contributions to reward
+    /// A contributor's share of a pool's payout, rounded down.
+    fn pool_share(points: u128, settlement: &PoolSettlement) -> u128 {
+        if settlement.points == 0 || settlement.payout == settlement.points {
+            return points.min(settlement.payout);
         }
+        multiply_by_rational_with_rounding(points, settlement.payout, settlement.points, Rounding::Down)
+            .unwrap_or(0)
+    }
 
-        // Distribute rewards proportionally
-        let mut distributed = 0u128;
-        let mut errors = Vec::new();
+    /// Merkle root and total of the entitlements of `epoch`.
+    pub fn compute_epoch_root(epoch: EpochIndex) -> (merkle::Hash, u128) {
+        let entitlements = Self::epoch_entitlements(epoch);
+        let total = entitlements.iter().fold(0u128, |acc, (_, amount)| acc.saturating_add(*amount));
+        let leaves = entitlements.iter()
+            .map(|(who, amount)| merkle::leaf_hash(epoch, who, *amount))
+            .collect::<Vec<_>>();
+        (merkle::root(&leaves), total)
+    }
 
-        // Distribute content rewards
-        <ContentContributions<T>>::iter().try_for_each(|(who, amount)| {
-            let reward = amount.checked_mul(reward_per_unit)
-                .and_then(|r| r.checked_mul(total_rewards))
-                .and_then(|r| r.checked_div(total_contributions))
-                .ok_or(Error::<T>::RewardCalculationOverflow)?;
+    /// The amount owed to `who` for `epoch` together with its Merkle proof, used by
+    /// clients to build `claim` calls. Available until the epoch's claim window closes.
+    pub fn epoch_claim_proof(epoch: EpochIndex, who: &T::AccountId) -> Option<(u128, Vec<merkle::Hash>)> {
+        let entitlements = Self::epoch_entitlements(epoch);
+        let index = entitlements.iter().position(|(account, _)| account == who)?;
+        let leaves = entitlements.iter()
+            .map(|(account, amount)| merkle::leaf_hash(epoch, account, *amount))
+            .collect::<Vec<_>>();
+        Some((entitlements[index].1, merkle::proof(&leaves, index)))
+    }
+}
 
-            if reward > 0 {
-                T::Currency::transfer(
-                    &Self::account_id(),
-                    &who,
-                    reward.into(),
-                    frame_support::traits::ExistenceRequirement::AllowDeath,
-                )?;
-                distributed = distributed.checked_add(reward)
-                    .ok_or(Error::<T>::RewardCalculationOverflow)?;
-                Self::deposit_event(RawEvent::RewardsClaimed(who, reward));
-            }
-            Ok(())
-        }).map_err(|e| {
-            errors.push(e);
-            e
-        })?;
-
-        // Distribute network rewards
-        <NetworkContributions<T>>::iter().try_for_each(|(who, amount)| {
-            let reward = amount.checked_mul(reward_per_unit)
-                .and_then(|r| r.checked_mul(total_rewards))
-                .and_then(|r| r.checked_div(total_contributions))
-                .ok_or(Error::<T>::RewardCalculationOverflow)?;
+impl<T: Config> ValidateUnsigned for Module<T> {
+    type Call = Call<T>;
 
-            if reward > 0 {
-                T::Currency::transfer(
-                    &Self::account_id(),
-                    &who,
-                    reward.into(),
-                    frame_support::traits::ExistenceRequirement::AllowDeath,
-                )?;
-                distributed = distributed.checked_add(reward)
-                    .ok_or(Error::<T>::RewardCalculationOverflow)?;
-                Self::deposit_event(RawEvent::RewardsClaimed(who, reward));
+    /// Only roots matching the epoch's contributions enter the pool, so that a wrong root
+    /// cannot take a block's weight without fees.
+    fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+        match call {
+            Call::commit_epoch_root(epoch, root) => {
+                if Self::check_epoch_root(*epoch, root).is_err() {
+                    return InvalidTransaction::Stale.into();
+                }
+                ValidTransaction::with_tag_prefix("ProofOfContributionEpochRoot")
+                    .priority(T::UnsignedPriority::get())
+                    .and_provides(epoch)
+                    .longevity(T::RootCommitTimeout::get().unique_saturated_into())
+                    .propagate(true)
+                    .build()
             }
-            Ok(())
-        }).map_err(|e| {
-            errors.push(e);
-            e
-        })?;
-
-        // Update total unclaimed rewards
-        <TotalUnclaimedRewards>::mutate(|total| {
-            *total = total.saturating_sub(distributed);
-        });
-
-        // Clear all contributions after distribution
-        <ContentContributions<T>>::remove_all();
-        <NetworkContributions<T>>::remove_all();
-
-        if !errors.is_empty() {
-            log::error!("Errors during reward distribution: {:?}", errors);
-            return Err(Error::<T>::RewardCalculationOverflow.into());
+            _ => InvalidTransaction::Call.into(),
         }
-
-        Ok(())
     }
 }
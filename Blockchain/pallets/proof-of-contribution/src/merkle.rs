@@ -0,0 +1,75 @@
+//! Binary Merkle tree used to commit per-epoch reward entitlements.
+//!
+//! Leaves are `blake2_256(SCALE(epoch, account, amount))`. Inner nodes hash the
+//! two children in sorted order, so proofs are a plain list of sibling hashes
+//! without left/right flags. An unpaired node is promoted to the next level as is.
+
+use codec::Encode;
+use sp_io::hashing::blake2_256;
+use sp_std::prelude::*;
+
+/// A node of the tree.
+pub type Hash = [u8; 32];
+
+/// Maximum accepted proof length, enough for 2^32 leaves.
+pub const MAX_PROOF_DEPTH: usize = 32;
+
+/// Hash of the leaf committing `amount` owed to `who` for `epoch`.
+pub fn leaf_hash<AccountId: Encode>(epoch: u32, who: &AccountId, amount: u128) -> Hash {
+    blake2_256(&(epoch, who, amount).encode())
+}
+
+fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left);
+    buf[32..].copy_from_slice(right);
+    blake2_256(&buf)
+}
+
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+    level.chunks(2)
+        .map(|pair| match pair {
+            [a, b] => hash_pair(a, b),
+            [a] => *a,
+            _ => unreachable!("chunks(2) yields one or two nodes; qed"),
+        })
+        .collect()
+}
+
+/// Root of the tree over `leaves`. The root of an empty tree is all zeroes.
+pub fn root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Sibling hashes proving that `leaves[index]` is part of `root(leaves)`.
+pub fn proof(leaves: &[Hash], index: usize) -> Vec<Hash> {
+    let mut proof = Vec::new();
+    if index >= leaves.len() {
+        return proof;
+    }
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+/// Check that `leaf` together with `proof` hashes up to `root`.
+pub fn verify_proof(root: &Hash, leaf: Hash, proof: &[Hash]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    &computed == root
+}
@@ -0,0 +1,107 @@
+use crate as pallet_proof_of_contribution;
+use frame_support::{parameter_types, traits::Everything};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Module, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+        ProofOfContribution: pallet_proof_of_contribution::{Module, Call, Storage, Event<T>, ValidateUnsigned},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const ExistentialDeposit: u128 = 1;
+    pub const ContributionReward: u128 = 10;
+    pub const MaxPrunedPerBlock: u32 = 2;
+    pub const ClaimWindow: u64 = 5;
+    pub const RootCommitTimeout: u64 = 10;
+    pub const MaxContributorsPerEpoch: u32 = 4;
+    pub const ReportDeposit: u128 = 10;
+    pub const UnsignedPriority: u64 = 1 << 20;
+}
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<u64>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u128>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = u128;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+impl pallet_proof_of_contribution::Config for Test {
+    type Event = Event;
+    type Currency = Balances;
+    type ContributionReward = ContributionReward;
+    type ContributionOracle = EnsureRoot<u64>;
+    type MaxPrunedPerBlock = MaxPrunedPerBlock;
+    type ClaimWindow = ClaimWindow;
+    type RootCommitTimeout = RootCommitTimeout;
+    type MaxContributorsPerEpoch = MaxContributorsPerEpoch;
+    type ReportDeposit = ReportDeposit;
+    type UnsignedPriority = UnsignedPriority;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+    Call: From<LocalCall>,
+{
+    type OverarchingCall = Call;
+    type Extrinsic = UncheckedExtrinsic;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        // Fund the reward pool
+        Balances::make_free_balance_be(&ProofOfContribution::account_id(), 1_000_000);
+    });
+    ext
+}
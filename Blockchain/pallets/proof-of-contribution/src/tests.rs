@@ -0,0 +1,365 @@
+use crate::{
+    merkle, mock::*, ContentContributions, ContributionKind, Error, NetworkContributions, RawEvent,
+    RewardClaimed, RewardPool,
+};
+use codec::Decode;
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{OffchainWorker, OnInitialize},
+    unsigned::ValidateUnsigned,
+};
+use sp_core::offchain::{testing::TestTransactionPoolExt, TransactionPoolExt};
+use sp_runtime::{traits::BadOrigin, transaction_validity::TransactionSource};
+
+#[test]
+fn oracle_can_record_contributions() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), 1, 5));
+        assert_ok!(ProofOfContribution::record_storage_contribution(Origin::root(), 1, 2));
+        assert_ok!(ProofOfContribution::record_bandwidth_contribution(Origin::root(), 1, 1));
+        assert_eq!(ProofOfContribution::content_contributions(0, 1), 50);
+        assert_eq!(ProofOfContribution::network_contributions(0, 1), 30);
+        assert_eq!(ProofOfContribution::pool_points(RewardPool::Content), 50);
+        assert_eq!(ProofOfContribution::pool_points(RewardPool::Network), 30);
+    });
+}
+
+#[test]
+fn unauthorized_callers_cannot_record_contributions() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ProofOfContribution::record_content_contribution(Origin::signed(1), 1, 1_000_000),
+            BadOrigin
+        );
+        assert_noop!(
+            ProofOfContribution::record_storage_contribution(Origin::signed(1), 1, 1_000_000),
+            BadOrigin
+        );
+        assert_noop!(
+            ProofOfContribution::record_bandwidth_contribution(Origin::signed(1), 1, 1_000_000),
+            BadOrigin
+        );
+        assert_noop!(
+            ProofOfContribution::record_content_contribution(Origin::none(), 1, 1_000_000),
+            BadOrigin
+        );
+        assert_eq!(ProofOfContribution::pool_points(RewardPool::Content), 0);
+        assert_eq!(ProofOfContribution::pool_points(RewardPool::Network), 0);
+    });
+}
+
+#[test]
+fn self_reported_contribution_is_credited_only_after_attestation() {
+    new_test_ext().execute_with(|| {
+        Balances::make_free_balance_be(&2, 100);
+        assert_noop!(
+            ProofOfContribution::report_contribution(Origin::signed(2), ContributionKind::Storage, 0),
+            Error::<Test>::EmptyReport
+        );
+        assert_ok!(ProofOfContribution::report_contribution(Origin::signed(2), ContributionKind::Storage, 3));
+        assert_ok!(ProofOfContribution::report_contribution(Origin::signed(2), ContributionKind::Storage, 4));
+        assert_eq!(ProofOfContribution::pending_contributions(2, ContributionKind::Storage), 7);
+        assert_eq!(ProofOfContribution::network_contributions(0, 2), 0);
+        // One deposit per pending report
+        assert_eq!(Balances::reserved_balance(2), 10);
+
+        assert_ok!(ProofOfContribution::attest_contribution(Origin::root(), 2, ContributionKind::Storage));
+        assert_eq!(ProofOfContribution::pending_contributions(2, ContributionKind::Storage), 0);
+        assert_eq!(ProofOfContribution::network_contributions(0, 2), 70);
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn reports_require_a_deposit() {
+    new_test_ext().execute_with(|| {
+        assert!(ProofOfContribution::report_contribution(Origin::signed(2), ContributionKind::Content, 7).is_err());
+        assert_eq!(ProofOfContribution::pending_contributions(2, ContributionKind::Content), 0);
+    });
+}
+
+#[test]
+fn users_cannot_attest_their_own_reports() {
+    new_test_ext().execute_with(|| {
+        Balances::make_free_balance_be(&2, 100);
+        assert_ok!(ProofOfContribution::report_contribution(Origin::signed(2), ContributionKind::Content, 7));
+        assert_noop!(
+            ProofOfContribution::attest_contribution(Origin::signed(2), 2, ContributionKind::Content),
+            BadOrigin
+        );
+        assert_noop!(
+            ProofOfContribution::reject_contribution(Origin::signed(2), 2, ContributionKind::Content),
+            BadOrigin
+        );
+        assert_eq!(ProofOfContribution::content_contributions(0, 2), 0);
+    });
+}
+
+#[test]
+fn rejected_report_is_discarded() {
+    new_test_ext().execute_with(|| {
+        Balances::make_free_balance_be(&2, 100);
+        assert_ok!(ProofOfContribution::report_contribution(Origin::signed(2), ContributionKind::Content, 7));
+        assert_ok!(ProofOfContribution::reject_contribution(Origin::root(), 2, ContributionKind::Content));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(ProofOfContribution::pending_contributions(2, ContributionKind::Content), 0);
+        assert_eq!(ProofOfContribution::content_contributions(0, 2), 0);
+        assert_noop!(
+            ProofOfContribution::attest_contribution(Origin::root(), 2, ContributionKind::Content),
+            Error::<Test>::NoPendingContribution
+        );
+    });
+}
+
+/// Record some contributions in epoch 0, close it and commit its root.
+fn close_and_commit_first_epoch() {
+    assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), 1, 5));
+    assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), 2, 2));
+    assert_ok!(ProofOfContribution::record_storage_contribution(Origin::root(), 2, 1));
+    assert_ok!(ProofOfContribution::record_bandwidth_contribution(Origin::root(), 3, 4));
+
+    ProofOfContribution::on_initialize(1);
+    assert_eq!(ProofOfContribution::current_epoch(), 1);
+    assert_eq!(ProofOfContribution::pool_points(RewardPool::Network), 0);
+    assert_eq!(ProofOfContribution::total_unclaimed_rewards(), 120);
+
+    let (root, total) = ProofOfContribution::compute_epoch_root(0);
+    assert_eq!(total, 120);
+    assert_ok!(ProofOfContribution::commit_epoch_root(Origin::none(), 0, root));
+    assert_eq!(ProofOfContribution::epochs(0).unwrap().committed_total, 120);
+}
+
+#[test]
+fn epoch_root_must_match_the_contributions() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), 1, 5));
+        ProofOfContribution::on_initialize(1);
+
+        let (root, _) = ProofOfContribution::compute_epoch_root(0);
+        // Every reward paid to another account
+        let forged = merkle::root(&[merkle::leaf_hash(0, &4u64, 50)]);
+        assert_noop!(ProofOfContribution::commit_epoch_root(Origin::root(), 0, root), BadOrigin);
+        assert_noop!(
+            ProofOfContribution::commit_epoch_root(Origin::none(), 0, forged),
+            Error::<Test>::EpochRootMismatch
+        );
+        assert!(ProofOfContribution::validate_unsigned(
+            TransactionSource::External,
+            &crate::Call::commit_epoch_root(0, forged)
+        )
+        .is_err());
+        assert_noop!(
+            ProofOfContribution::commit_epoch_root(Origin::none(), 1, root),
+            Error::<Test>::UnknownEpoch
+        );
+
+        assert!(ProofOfContribution::validate_unsigned(
+            TransactionSource::External,
+            &crate::Call::commit_epoch_root(0, root)
+        )
+        .is_ok());
+        assert_ok!(ProofOfContribution::commit_epoch_root(Origin::none(), 0, root));
+        assert_noop!(
+            ProofOfContribution::commit_epoch_root(Origin::none(), 0, root),
+            Error::<Test>::EpochRootAlreadyCommitted
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_submits_the_epoch_root() {
+    let mut ext = new_test_ext();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    ext.register_extension(TransactionPoolExt::new(pool));
+    ext.execute_with(|| {
+        assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), 1, 5));
+        ProofOfContribution::on_initialize(1);
+
+        ProofOfContribution::offchain_worker(2);
+        let transaction = pool_state.write().transactions.pop().unwrap();
+        let transaction = UncheckedExtrinsic::decode(&mut &*transaction).unwrap();
+        assert_eq!(transaction.signature, None);
+        let (root, _) = ProofOfContribution::compute_epoch_root(0);
+        assert_eq!(transaction.function, Call::ProofOfContribution(crate::Call::commit_epoch_root(0, root)));
+    });
+}
+
+#[test]
+fn contributors_per_epoch_are_bounded() {
+    new_test_ext().execute_with(|| {
+        for who in 1..=4 {
+            assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), who, 1));
+        }
+        assert_noop!(
+            ProofOfContribution::record_storage_contribution(Origin::root(), 5, 1),
+            Error::<Test>::TooManyContributors
+        );
+        // Existing contributors can still contribute
+        assert_ok!(ProofOfContribution::record_storage_contribution(Origin::root(), 4, 1));
+        assert_eq!(ProofOfContribution::epoch_contributors(), 4);
+    });
+}
+
+#[test]
+fn uncommitted_epochs_expire_and_are_pruned() {
+    new_test_ext().execute_with(|| {
+        // Closed at block 1, the root must be committed before block 11
+        assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), 1, 5));
+        ProofOfContribution::on_initialize(1);
+        let (root, _) = ProofOfContribution::compute_epoch_root(0);
+
+        ProofOfContribution::on_initialize(10);
+        assert_eq!(ProofOfContribution::next_epoch_to_prune(), 0);
+
+        System::set_block_number(11);
+        assert_noop!(
+            ProofOfContribution::commit_epoch_root(Origin::none(), 0, root),
+            Error::<Test>::EpochExpired
+        );
+        ProofOfContribution::on_initialize(11);
+        System::assert_last_event(RawEvent::EpochPruned(0, 50).into());
+        assert_eq!(ProofOfContribution::next_epoch_to_prune(), 1);
+        assert_eq!(ProofOfContribution::epochs(0), None);
+        assert_eq!(ContentContributions::<Test>::iter_prefix(0).count(), 0);
+        assert_eq!(ProofOfContribution::total_unclaimed_rewards(), 0);
+    });
+}
+
+#[test]
+fn contributors_claim_with_merkle_proof() {
+    new_test_ext().execute_with(|| {
+        close_and_commit_first_epoch();
+
+        let (amount, proof) = ProofOfContribution::epoch_claim_proof(0, &2).unwrap();
+        assert_eq!(amount, 30);
+        assert_ok!(ProofOfContribution::claim(Origin::signed(2), 0, amount, proof.clone()));
+        assert_eq!(Balances::free_balance(2), 30);
+        assert_eq!(ProofOfContribution::epochs(0).unwrap().claimed, 30);
+
+        assert_noop!(
+            ProofOfContribution::claim(Origin::signed(2), 0, amount, proof),
+            Error::<Test>::AlreadyClaimed
+        );
+    });
+}
+
+#[test]
+fn claim_with_wrong_amount_or_proof_fails() {
+    new_test_ext().execute_with(|| {
+        close_and_commit_first_epoch();
+
+        let (amount, proof) = ProofOfContribution::epoch_claim_proof(0, &1).unwrap();
+        assert_noop!(
+            ProofOfContribution::claim(Origin::signed(1), 0, amount + 1, proof.clone()),
+            Error::<Test>::InvalidMerkleProof
+        );
+        // Another account cannot reuse the proof
+        assert_noop!(
+            ProofOfContribution::claim(Origin::signed(4), 0, amount, proof),
+            Error::<Test>::InvalidMerkleProof
+        );
+    });
+}
+
+#[test]
+fn entries_are_pruned_once_the_claim_window_closes() {
+    new_test_ext().execute_with(|| {
+        // Committed at block 1, claimable until block 6
+        close_and_commit_first_epoch();
+
+        // Pruning runs every block but keeps the entries proofs are built from
+        for now in 2..6 {
+            System::set_block_number(now);
+            ProofOfContribution::on_initialize(now);
+        }
+        assert_eq!(ProofOfContribution::next_epoch_to_prune(), 0);
+        let (amount, proof) = ProofOfContribution::epoch_claim_proof(0, &2).unwrap();
+        assert_ok!(ProofOfContribution::claim(Origin::signed(2), 0, amount, proof));
+        assert_eq!(Balances::free_balance(2), 30);
+
+        System::set_block_number(6);
+        let (amount, proof) = ProofOfContribution::epoch_claim_proof(0, &1).unwrap();
+        assert_noop!(
+            ProofOfContribution::claim(Origin::signed(1), 0, amount, proof),
+            Error::<Test>::ClaimWindowClosed
+        );
+
+        // Four contribution entries and one claim in epoch 0, at most `MaxPrunedPerBlock`
+        // removed per block
+        ProofOfContribution::on_initialize(6);
+        assert_eq!(ProofOfContribution::next_epoch_to_prune(), 0);
+        ProofOfContribution::on_initialize(7);
+        assert_eq!(ContentContributions::<Test>::iter_prefix(0).count(), 0);
+        assert_eq!(NetworkContributions::<Test>::iter_prefix(0).count(), 0);
+        assert_eq!(RewardClaimed::<Test>::iter_prefix(0).count(), 1);
+        ProofOfContribution::on_initialize(8);
+        assert_eq!(ProofOfContribution::next_epoch_to_prune(), 1);
+        assert_eq!(RewardClaimed::<Test>::iter_prefix(0).count(), 0);
+        assert_eq!(ProofOfContribution::epochs(0), None);
+        assert_eq!(ProofOfContribution::epoch_claim_proof(0, &1), None);
+
+        // The 90 nobody claimed is no longer owed
+        System::assert_last_event(RawEvent::EpochPruned(0, 90).into());
+        assert_eq!(ProofOfContribution::total_unclaimed_rewards(), 0);
+    });
+}
+
+#[test]
+fn governance_rates_apply_per_contribution_kind() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ProofOfContribution::set_reward_parameters(0, 1, 1, 10),
+            Error::<Test>::InvalidRewardParameters
+        );
+        assert_noop!(
+            ProofOfContribution::update_reward_parameters(Origin::signed(1), 3, 2, 1, 10),
+            BadOrigin
+        );
+        assert_ok!(ProofOfContribution::update_reward_parameters(Origin::root(), 3, 2, 1, 10));
+        assert_eq!(ProofOfContribution::reward_interval(), 10);
+
+        assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), 1, 10));
+        assert_ok!(ProofOfContribution::record_storage_contribution(Origin::root(), 1, 10));
+        assert_ok!(ProofOfContribution::record_bandwidth_contribution(Origin::root(), 1, 10));
+        assert_eq!(ProofOfContribution::content_contributions(0, 1), 30);
+        assert_eq!(ProofOfContribution::network_contributions(0, 1), 30);
+    });
+}
+
+#[test]
+fn pool_budgets_cap_each_pool_independently() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ProofOfContribution::set_pool_budget(Origin::signed(1), RewardPool::Network, Some(10)),
+            BadOrigin
+        );
+        assert_ok!(ProofOfContribution::set_pool_budget(Origin::root(), RewardPool::Network, Some(30)));
+
+        assert_ok!(ProofOfContribution::record_content_contribution(Origin::root(), 1, 5));
+        assert_ok!(ProofOfContribution::record_storage_contribution(Origin::root(), 2, 3));
+        assert_ok!(ProofOfContribution::record_bandwidth_contribution(Origin::root(), 3, 3));
+        ProofOfContribution::on_initialize(1);
+
+        let info = ProofOfContribution::epochs(0).unwrap();
+        assert_eq!((info.content.points, info.content.payout), (50, 50));
+        assert_eq!((info.network.points, info.network.payout), (60, 30));
+        assert_eq!(info.total_rewards, 80);
+        assert_eq!(info.contributors, 3);
+        System::assert_has_event(RawEvent::PoolSettled(0, RewardPool::Network, 60, 30).into());
+
+        // The network pool is split pro rata between its contributors
+        assert_eq!(ProofOfContribution::epoch_entitlements(0), vec![(1, 50), (2, 15), (3, 15)]);
+        let (root, _) = ProofOfContribution::compute_epoch_root(0);
+        assert_ok!(ProofOfContribution::commit_epoch_root(Origin::none(), 0, root));
+    });
+}
+
+#[test]
+fn merkle_proofs_verify_for_every_leaf() {
+    let leaves = (0u64..7).map(|who| merkle::leaf_hash(0, &who, 10)).collect::<Vec<_>>();
+    let root = merkle::root(&leaves);
+    for (index, leaf) in leaves.iter().enumerate() {
+        assert!(merkle::verify_proof(&root, *leaf, &merkle::proof(&leaves, index)));
+    }
+    assert!(!merkle::verify_proof(&root, merkle::leaf_hash(0, &7u64, 10), &merkle::proof(&leaves, 0)));
+}
@@ -1,54 +1,198 @@
 use chrono::prelude::*;
+use codec::{Decode, Encode};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::sync::atomic::AtomicBool;
 
+use crate::pow::{self, MiningStats, Target};
 use crate::transaction::Transaction;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Block {
+/// SHA-256 hash of a block header or transaction.
+pub type Hash = [u8; 32];
+
+/// Lowercase hex representation of `hash`.
+pub fn to_hex(hash: &Hash) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Part of a block covered by its hash. Transactions are committed to through their
+/// Merkle root, so the header has a fixed size.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct BlockHeader {
     pub index: u32,
+    /// Seconds since the Unix epoch.
     pub timestamp: i64,
-    pub transactions: Vec<Transaction>,
-    pub previous_hash: String,
-    pub hash: String,
+    pub previous_hash: Hash,
+    /// Root of the Merkle tree over the hashes of the transactions, see [`Block::transactions_root`].
+    pub transactions_root: Hash,
+    /// Target the hash of the header must meet.
+    pub target: Target,
     pub nonce: u64,
 }
 
+impl BlockHeader {
+    /// SHA-256 of the SCALE encoding of the header.
+    pub fn hash(&self) -> Hash {
+        Sha256::digest(self.encode()).into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub transactions: Vec<Transaction>,
+    /// Hash of the header, as found by mining.
+    pub hash: Hash,
+}
+
 impl Block {
-    pub fn new(index: u32, transactions: Vec<Transaction>, previous_hash: String) -> Block {
-        let timestamp = Utc::now().timestamp();
-        let mut block = Block {
+    pub fn new(index: u32, transactions: Vec<Transaction>, previous_hash: Hash) -> Block {
+        Block::new_at(index, Utc::now().timestamp(), transactions, previous_hash)
+    }
+
+    /// Block created at `timestamp`, in seconds since the Unix epoch. Its target is
+    /// [`Target::MAX`] until it is mined.
+    pub fn new_at(index: u32, timestamp: i64, transactions: Vec<Transaction>, previous_hash: Hash) -> Block {
+        let header = BlockHeader {
             index,
             timestamp,
-            transactions,
             previous_hash,
-            hash: String::new(),
+            transactions_root: Block::transactions_root(&transactions),
+            target: Target::MAX,
             nonce: 0,
         };
-        block.hash = block.calculate_hash();
-        block
-    }
-
-    pub fn calculate_hash(&self) -> String {
-        let mut hasher = Sha256::new();
-        let input = format!(
-            "{}{}{}{}{}",
-            self.index,
-            self.timestamp,
-            self.previous_hash,
-            serde_json::to_string(&self.transactions).expect("Failed to serialize transactions"), // Ensure transactions are serialized
-            self.nonce
+        let hash = header.hash();
+        Block { header, transactions, hash }
+    }
+
+    /// Merkle root over the transaction hashes, built like the chunk roots of samples:
+    /// inner nodes are `sha256(left ++ right)` and an unpaired node is promoted as is.
+    /// The root of no transactions is all zeroes.
+    pub fn transactions_root(transactions: &[Transaction]) -> Hash {
+        let leaves = transactions.iter().map(Transaction::hash).collect::<Vec<_>>();
+        echochain_chunk_proof::chunk_root(&leaves)
+    }
+
+    pub fn calculate_hash(&self) -> Hash {
+        self.header.hash()
+    }
+
+    /// Whether the header commits to the transactions of the block.
+    pub fn has_valid_transactions_root(&self) -> bool {
+        self.header.transactions_root == Block::transactions_root(&self.transactions)
+    }
+
+    /// Whether the hash meets the target of the header.
+    pub fn meets_target(&self) -> bool {
+        self.header.target.is_met_by(&self.hash)
+    }
+
+    /// Mine the block for `target` on all rayon threads. Returns `None`, leaving the block
+    /// unsolved, if `cancel` is set before a solution is found.
+    pub fn mine_block(&mut self, target: Target, cancel: &AtomicBool) -> Option<MiningStats> {
+        self.header.target = target;
+        self.header.nonce = 0;
+        self.hash = self.calculate_hash();
+        let (hash, stats) = pow::search_nonce(&self.header, cancel)?;
+        self.header.nonce = stats.nonce;
+        self.hash = hash;
+        Some(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transactions() -> Vec<Transaction> {
+        vec![
+            Transaction::reward([7; 32], 50, 1),
+            Transaction { from: Some([1; 32]), to: [2; 32], amount: 10, fee: 1, nonce: 0, signature: vec![0xaa; 64] },
+            Transaction { from: Some([2; 32]), to: [3; 32], amount: 5, fee: 0, nonce: 3, signature: vec![0xbb; 64] },
+        ]
+    }
+
+    #[test]
+    fn transactions_have_a_canonical_encoding() {
+        let reward = Transaction::reward([7; 32], 50, 1);
+        assert_eq!(
+            hex(&reward.encode()),
+            "00".to_owned() + &"07".repeat(32) + "3200000000000000" + "0000000000000000" + "0100000000000000" + "00"
+        );
+        assert_eq!(
+            to_hex(&reward.hash()),
+            "a685a311044f7910d1f457250babdc392cbc595e5eddfd68bb648f164822c5a2"
+        );
+        assert_eq!(Transaction::decode(&mut &reward.encode()[..]), Ok(reward));
+    }
+
+    #[test]
+    fn headers_have_a_canonical_encoding() {
+        let header = BlockHeader {
+            index: 1,
+            timestamp: 1_700_000_000,
+            previous_hash: [0x11; 32],
+            transactions_root: [0x22; 32],
+            target: Target::from_difficulty(256.0),
+            nonce: 42,
+        };
+        assert_eq!(
+            hex(&header.encode()),
+            "01000000".to_owned()
+                + "00f1536500000000"
+                + &"11".repeat(32)
+                + &"22".repeat(32)
+                + "00"
+                + &"ff".repeat(31)
+                + "2a00000000000000"
+        );
+        assert_eq!(
+            to_hex(&header.hash()),
+            "cdb10397157b355939e89a25b6bf21c9c1a2b4cdff4af66947ab573790c9b456"
+        );
+    }
+
+    #[test]
+    fn transactions_root_matches_test_vectors() {
+        let transactions = transactions();
+        assert_eq!(Block::transactions_root(&[]), [0; 32]);
+        assert_eq!(Block::transactions_root(&transactions[..1]), transactions[0].hash());
+        assert_eq!(
+            to_hex(&Block::transactions_root(&transactions)),
+            "9db48c33a32c594f42a6a4c258862ec980c9baedf0909b38bf0a90217d6a3098"
         );
-        hasher.update(input.as_bytes());
-        format!("{:x}", hasher.finalize())
     }
 
-    pub fn mine_block(&mut self, difficulty: usize) {
-        let target = "0".repeat(difficulty);
-        while !self.hash.starts_with(&target) {
-            self.nonce += 1;
-            self.hash = self.calculate_hash();
-        }
-        println!("Block mined: {}", self.hash);
+    #[test]
+    fn the_hash_covers_every_transaction() {
+        let mut block = Block::new_at(1, 1_700_000_000, transactions(), [0x11; 32]);
+        assert!(block.has_valid_transactions_root());
+        assert_eq!(block.hash, block.calculate_hash());
+
+        block.transactions[2].amount += 1;
+        assert!(!block.has_valid_transactions_root());
+        block.transactions[2].amount -= 1;
+        block.transactions.swap(1, 2);
+        assert!(!block.has_valid_transactions_root());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn mining_meets_the_target_and_can_be_cancelled() {
+        let mut block = Block::new_at(1, 0, transactions(), [0; 32]);
+        let target = Target::from_difficulty(256.0);
+        let stats = block.mine_block(target, &AtomicBool::new(false)).unwrap();
+        assert_eq!(block.header.target, target);
+        assert_eq!(block.header.nonce, stats.nonce);
+        assert_eq!(block.hash, block.calculate_hash());
+        assert!(block.meets_target());
+        assert_eq!(block.hash[0], 0);
+
+        let mut block = Block::new_at(2, 0, vec![], block.hash);
+        assert_eq!(block.mine_block(Target([0; 32]), &AtomicBool::new(true)), None);
+        assert!(!block.meets_target());
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
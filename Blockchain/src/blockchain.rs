@@ -0,0 +1,580 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::AtomicBool;
+
+use chrono::prelude::*;
+
+use crate::block::{Block, Hash};
+use crate::pow::{MiningStats, Target};
+use crate::transaction::{Address, Transaction};
+
+/// Parameters of a chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainConfig {
+    /// Difficulty of the blocks until the first adjustment, i.e. the expected number of
+    /// hashes to mine one. It may be fractional; 1 accepts any hash.
+    pub initial_difficulty: f64,
+    /// Minted to the miner of every block, on top of the fees.
+    pub block_reward: u64,
+    /// Targeted time between blocks, in seconds.
+    pub target_block_time: i64,
+    /// Number of blocks between difficulty adjustments.
+    pub adjustment_interval: u32,
+    /// Maximum number of seconds a block's timestamp may be ahead of its parent's, so that
+    /// miners cannot make the chain look slower than it is to lower the difficulty.
+    pub max_timestamp_gap: i64,
+    /// Maximum number of transfers in a block.
+    pub max_block_transactions: usize,
+}
+
+impl Default for ChainConfig {
+    fn default() -> ChainConfig {
+        ChainConfig {
+            initial_difficulty: 256.0,
+            block_reward: 50,
+            target_block_time: 10,
+            adjustment_interval: 10,
+            max_timestamp_gap: 30,
+            max_block_transactions: 100,
+        }
+    }
+}
+
+/// Reasons a transaction or block is refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    /// The transaction is not signed by its sender.
+    InvalidSignature,
+    /// Rewards can only be minted by miners.
+    UnexpectedReward,
+    /// The nonce is not the next one of the sender.
+    InvalidNonce { expected: u64, found: u64 },
+    /// The sender cannot pay the amount and fee.
+    InsufficientBalance,
+    /// The block does not follow the previous one.
+    InvalidIndex,
+    InvalidPreviousHash,
+    InvalidTimestamp,
+    /// The timestamp is more than `max_timestamp_gap` after its parent's.
+    FutureTimestamp,
+    /// The hash of the block does not match its header.
+    InvalidHash,
+    /// The target of the header is not the one required at its height.
+    InvalidTarget,
+    /// The hash of the block does not meet its target.
+    InsufficientWork,
+    /// The transactions root of the header does not match the transactions.
+    InvalidTransactionsRoot,
+    /// The block does not start with a reward of the block reward plus fees.
+    InvalidReward,
+    TooManyTransactions,
+    /// The genesis block was changed.
+    InvalidGenesis,
+    /// Mining was cancelled before a solution was found.
+    MiningCancelled,
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::InvalidSignature => write!(f, "invalid transaction signature"),
+            ChainError::UnexpectedReward => write!(f, "rewards cannot be submitted"),
+            ChainError::InvalidNonce { expected, found } => {
+                write!(f, "invalid nonce: expected {}, found {}", expected, found)
+            }
+            ChainError::InsufficientBalance => write!(f, "insufficient balance"),
+            ChainError::InvalidIndex => write!(f, "invalid block index"),
+            ChainError::InvalidPreviousHash => write!(f, "invalid previous hash"),
+            ChainError::InvalidTimestamp => write!(f, "block timestamp before its parent"),
+            ChainError::FutureTimestamp => write!(f, "block timestamp too far after its parent"),
+            ChainError::InvalidHash => write!(f, "block hash does not match its header"),
+            ChainError::InvalidTransactionsRoot => write!(f, "transactions root does not match the transactions"),
+            ChainError::InvalidTarget => write!(f, "invalid block target"),
+            ChainError::InsufficientWork => write!(f, "block hash does not meet its target"),
+            ChainError::InvalidReward => write!(f, "invalid block reward"),
+            ChainError::TooManyTransactions => write!(f, "too many transactions in block"),
+            ChainError::InvalidGenesis => write!(f, "invalid genesis block"),
+            ChainError::MiningCancelled => write!(f, "mining cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+/// Balances and nonces of the accounts.
+#[derive(Debug, Clone, Default)]
+struct Ledger {
+    balances: HashMap<Address, u64>,
+    nonces: HashMap<Address, u64>,
+}
+
+impl Ledger {
+    fn balance(&self, who: &Address) -> u64 {
+        self.balances.get(who).copied().unwrap_or(0)
+    }
+
+    fn nonce(&self, who: &Address) -> u64 {
+        self.nonces.get(who).copied().unwrap_or(0)
+    }
+
+    fn credit(&mut self, who: Address, amount: u64) {
+        let balance = self.balances.entry(who).or_insert(0);
+        *balance = balance.saturating_add(amount);
+    }
+
+    /// Apply a transfer, returning its fee for the miner.
+    fn apply_transfer(&mut self, transaction: &Transaction) -> Result<u64, ChainError> {
+        let from = transaction.from.ok_or(ChainError::UnexpectedReward)?;
+        if !transaction.verify_signature() {
+            return Err(ChainError::InvalidSignature);
+        }
+        let expected = self.nonce(&from);
+        if transaction.nonce != expected {
+            return Err(ChainError::InvalidNonce { expected, found: transaction.nonce });
+        }
+        let cost = transaction.amount.checked_add(transaction.fee).ok_or(ChainError::InsufficientBalance)?;
+        let remaining = self.balance(&from).checked_sub(cost).ok_or(ChainError::InsufficientBalance)?;
+
+        self.balances.insert(from, remaining);
+        self.nonces.insert(from, expected + 1);
+        self.credit(transaction.to, transaction.amount);
+        Ok(transaction.fee)
+    }
+
+    /// Apply the transactions of a mined block: a reward of `block_reward` plus the fees,
+    /// followed by transfers. Nothing is applied if the block is invalid.
+    fn apply_block(&mut self, block: &Block, block_reward: u64) -> Result<(), ChainError> {
+        let (reward, transfers) = block.transactions.split_first().ok_or(ChainError::InvalidReward)?;
+        if !reward.is_reward() || !reward.signature.is_empty() || reward.nonce != u64::from(block.header.index) {
+            return Err(ChainError::InvalidReward);
+        }
+
+        let mut ledger = self.clone();
+        let mut fees = 0u64;
+        for transaction in transfers {
+            fees = fees.saturating_add(ledger.apply_transfer(transaction)?);
+        }
+        if reward.amount != block_reward.saturating_add(fees) {
+            return Err(ChainError::InvalidReward);
+        }
+        ledger.credit(reward.to, reward.amount);
+
+        *self = ledger;
+        Ok(())
+    }
+}
+
+/// A proof-of-work chain with its ledger and mempool.
+#[derive(Debug, Clone)]
+pub struct Blockchain {
+    config: ChainConfig,
+    chain: Vec<Block>,
+    mempool: Vec<Transaction>,
+    ledger: Ledger,
+    target: Target,
+}
+
+impl Blockchain {
+    /// Chain whose genesis block mints `allocations`.
+    pub fn new(config: ChainConfig, allocations: &[(Address, u64)]) -> Blockchain {
+        let transactions = allocations.iter().map(|(who, amount)| Transaction::reward(*who, *amount, 0)).collect();
+        let genesis = Block::new(0, transactions, Hash::default());
+
+        let mut ledger = Ledger::default();
+        for (who, amount) in allocations {
+            ledger.credit(*who, *amount);
+        }
+        let target = Target::from_difficulty(config.initial_difficulty);
+        Blockchain { config, chain: vec![genesis], mempool: Vec::new(), ledger, target }
+    }
+
+    pub fn config(&self) -> &ChainConfig {
+        &self.config
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.chain
+    }
+
+    pub fn latest_block(&self) -> &Block {
+        self.chain.last().expect("the chain starts with the genesis block; qed")
+    }
+
+    /// Transactions waiting to be mined, in submission order.
+    pub fn mempool(&self) -> &[Transaction] {
+        &self.mempool
+    }
+
+    /// Target the next block must meet.
+    pub fn target(&self) -> Target {
+        self.target
+    }
+
+    /// Difficulty of the next block, see [`Target::difficulty`].
+    pub fn difficulty(&self) -> f64 {
+        self.target.difficulty()
+    }
+
+    pub fn balance(&self, who: &Address) -> u64 {
+        self.ledger.balance(who)
+    }
+
+    /// Number of transactions `who` sent in mined blocks.
+    pub fn nonce(&self, who: &Address) -> u64 {
+        self.ledger.nonce(who)
+    }
+
+    /// Nonce of the next transaction of `who`, counting the ones in the mempool.
+    pub fn next_nonce(&self, who: &Address) -> u64 {
+        let pending = self.mempool.iter().filter(|transaction| transaction.from.as_ref() == Some(who)).count();
+        self.nonce(who) + pending as u64
+    }
+
+    /// Add a signed transfer to the mempool. It must follow the sender's pending
+    /// transactions, and the sender must afford all of them.
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> Result<(), ChainError> {
+        let from = transaction.from.ok_or(ChainError::UnexpectedReward)?;
+        let mut ledger = self.ledger.clone();
+        for pending in self.mempool.iter().filter(|pending| pending.from == Some(from)) {
+            ledger.apply_transfer(pending)?;
+        }
+        ledger.apply_transfer(&transaction)?;
+
+        self.mempool.push(transaction);
+        Ok(())
+    }
+
+    /// Mine the transactions of the mempool into a new block rewarding `miner`. The block is
+    /// stamped with the current time, kept within `max_timestamp_gap` of its parent.
+    pub fn mine_block(&mut self, miner: Address) -> Result<MiningStats, ChainError> {
+        let parent = self.latest_block().header.timestamp;
+        let timestamp = Utc::now().timestamp().min(parent.saturating_add(self.config.max_timestamp_gap)).max(parent);
+        self.mine_block_at(miner, timestamp)
+    }
+
+    /// Mine a block created at `timestamp`, e.g. to simulate block times.
+    pub fn mine_block_at(&mut self, miner: Address, timestamp: i64) -> Result<MiningStats, ChainError> {
+        self.mine_block_with(miner, timestamp, &AtomicBool::new(false))
+    }
+
+    /// Mine a block created at `timestamp`, giving up with [`ChainError::MiningCancelled`]
+    /// once `cancel` is set. Transactions that are no longer valid are dropped from the
+    /// mempool when the block is added.
+    pub fn mine_block_with(
+        &mut self,
+        miner: Address,
+        timestamp: i64,
+        cancel: &AtomicBool,
+    ) -> Result<MiningStats, ChainError> {
+        let height = self.latest_block().header.index + 1;
+        let mut ledger = self.ledger.clone();
+        let mut transfers = Vec::new();
+        let mut fees = 0u64;
+        for transaction in &self.mempool {
+            if transfers.len() == self.config.max_block_transactions {
+                break;
+            }
+            if let Ok(fee) = ledger.apply_transfer(transaction) {
+                fees = fees.saturating_add(fee);
+                transfers.push(transaction.clone());
+            }
+        }
+
+        let mut transactions = vec![Transaction::reward(miner, self.config.block_reward.saturating_add(fees), height)];
+        transactions.extend(transfers);
+        let mut block = Block::new_at(height, timestamp, transactions, self.latest_block().hash);
+        let stats = block.mine_block(self.target, cancel).ok_or(ChainError::MiningCancelled)?;
+
+        self.add_block(block)?;
+        Ok(stats)
+    }
+
+    /// Append a block mined elsewhere, checking it against the tip of the chain.
+    pub fn add_block(&mut self, block: Block) -> Result<(), ChainError> {
+        Self::check_block(&self.config, self.latest_block(), &block, self.target)?;
+        self.ledger.apply_block(&block, self.config.block_reward)?;
+
+        self.chain.push(block);
+        let ledger = &self.ledger;
+        self.mempool.retain(|transaction| {
+            transaction.from.is_some_and(|from| transaction.nonce >= ledger.nonce(&from))
+        });
+        self.target = Self::next_target(&self.config, &self.chain, self.target);
+        Ok(())
+    }
+
+    /// Check every block and transaction from the genesis block on.
+    pub fn validate_chain(&self) -> Result<(), ChainError> {
+        let genesis = &self.chain[0];
+        if genesis.header.index != 0 || genesis.hash != genesis.calculate_hash() || !genesis.has_valid_transactions_root() {
+            return Err(ChainError::InvalidGenesis);
+        }
+        let mut ledger = Ledger::default();
+        for allocation in &genesis.transactions {
+            if !allocation.is_reward() {
+                return Err(ChainError::InvalidGenesis);
+            }
+            ledger.credit(allocation.to, allocation.amount);
+        }
+
+        let mut target = Target::from_difficulty(self.config.initial_difficulty);
+        for height in 1..self.chain.len() {
+            let block = &self.chain[height];
+            Self::check_block(&self.config, &self.chain[height - 1], block, target)?;
+            ledger.apply_block(block, self.config.block_reward)?;
+            target = Self::next_target(&self.config, &self.chain[..=height], target);
+        }
+        Ok(())
+    }
+
+    fn check_block(config: &ChainConfig, previous: &Block, block: &Block, target: Target) -> Result<(), ChainError> {
+        if block.header.index != previous.header.index + 1 {
+            return Err(ChainError::InvalidIndex);
+        }
+        if block.header.previous_hash != previous.hash {
+            return Err(ChainError::InvalidPreviousHash);
+        }
+        if block.header.timestamp < previous.header.timestamp {
+            return Err(ChainError::InvalidTimestamp);
+        }
+        if block.header.timestamp > previous.header.timestamp.saturating_add(config.max_timestamp_gap) {
+            return Err(ChainError::FutureTimestamp);
+        }
+        if block.hash != block.calculate_hash() {
+            return Err(ChainError::InvalidHash);
+        }
+        if !block.has_valid_transactions_root() {
+            return Err(ChainError::InvalidTransactionsRoot);
+        }
+        if block.header.target != target {
+            return Err(ChainError::InvalidTarget);
+        }
+        if !block.meets_target() {
+            return Err(ChainError::InsufficientWork);
+        }
+        if block.transactions.len() > config.max_block_transactions + 1 {
+            return Err(ChainError::TooManyTransactions);
+        }
+        Ok(())
+    }
+
+    /// Target of the block following `chain`. Every `adjustment_interval` blocks, it is
+    /// scaled by the time the last interval took over the target time, by at most a factor
+    /// of 4 either way.
+    fn next_target(config: &ChainConfig, chain: &[Block], target: Target) -> Target {
+        let interval = config.adjustment_interval as usize;
+        let height = chain.len();
+        if height.checked_rem(interval) != Some(0) || height <= interval {
+            return target;
+        }
+
+        let elapsed = chain[height - 1].header.timestamp - chain[height - 1 - interval].header.timestamp;
+        let expected = config.target_block_time * interval as i64;
+        target.retarget(elapsed, expected, Target::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::address;
+    use ed25519_dalek::SigningKey;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn config() -> ChainConfig {
+        ChainConfig { initial_difficulty: 16.0, adjustment_interval: 4, ..ChainConfig::default() }
+    }
+
+    /// Mine `block` again for the target it already commits to.
+    fn remine(block: &mut Block) {
+        block.mine_block(block.header.target, &AtomicBool::new(false)).unwrap();
+    }
+
+    fn assert_difficulty(chain: &Blockchain, expected: f64) {
+        assert!((chain.difficulty() - expected).abs() < 1e-6, "difficulty {} != {}", chain.difficulty(), expected);
+    }
+
+    #[test]
+    fn mined_transfers_move_balances_and_pay_fees() {
+        let (alice, bob, miner) = (key(1), key(2), address(&key(3)));
+        let mut chain = Blockchain::new(config(), &[(address(&alice), 100)]);
+
+        chain.submit_transaction(Transaction::new(&alice, address(&bob), 30, 2, 0)).unwrap();
+        chain.submit_transaction(Transaction::new(&alice, address(&bob), 20, 1, 1)).unwrap();
+        assert_eq!(chain.next_nonce(&address(&alice)), 2);
+
+        let stats = chain.mine_block(miner).unwrap();
+        let block = chain.latest_block();
+        assert_eq!(block.transactions.len(), 3);
+        assert_eq!(block.header.nonce, stats.nonce);
+        assert_eq!(block.header.target, Target::from_difficulty(16.0));
+        assert!(block.meets_target());
+        assert!(stats.hashes >= 1);
+        assert_eq!(chain.balance(&address(&alice)), 47);
+        assert_eq!(chain.balance(&address(&bob)), 50);
+        assert_eq!(chain.balance(&miner), 53);
+        assert_eq!(chain.nonce(&address(&alice)), 2);
+        assert!(chain.mempool().is_empty());
+        assert_eq!(chain.validate_chain(), Ok(()));
+    }
+
+    #[test]
+    fn mempool_refuses_invalid_transactions() {
+        let (alice, bob) = (key(1), key(2));
+        let mut chain = Blockchain::new(config(), &[(address(&alice), 100)]);
+
+        assert_eq!(
+            chain.submit_transaction(Transaction::reward(address(&bob), 10, 1)),
+            Err(ChainError::UnexpectedReward)
+        );
+        assert_eq!(
+            chain.submit_transaction(Transaction::new(&alice, address(&bob), 10, 0, 1)),
+            Err(ChainError::InvalidNonce { expected: 0, found: 1 })
+        );
+        let mut tampered = Transaction::new(&alice, address(&bob), 10, 0, 0);
+        tampered.amount = 90;
+        assert_eq!(chain.submit_transaction(tampered), Err(ChainError::InvalidSignature));
+
+        chain.submit_transaction(Transaction::new(&alice, address(&bob), 60, 0, 0)).unwrap();
+        assert_eq!(
+            chain.submit_transaction(Transaction::new(&alice, address(&bob), 40, 1, 1)),
+            Err(ChainError::InsufficientBalance)
+        );
+        assert_eq!(chain.mempool().len(), 1);
+    }
+
+    #[test]
+    fn blocks_are_capped_and_leave_the_rest_pending() {
+        let alice = key(1);
+        let mut chain = Blockchain::new(ChainConfig { max_block_transactions: 2, ..config() }, &[(address(&alice), 100)]);
+        for nonce in 0..3 {
+            chain.submit_transaction(Transaction::new(&alice, [9; 32], 1, 0, nonce)).unwrap();
+        }
+
+        chain.mine_block([7; 32]).unwrap();
+        assert_eq!(chain.mempool().len(), 1);
+        chain.mine_block([7; 32]).unwrap();
+        assert!(chain.mempool().is_empty());
+        assert_eq!(chain.balance(&[9; 32]), 3);
+    }
+
+    #[test]
+    fn tampered_chains_fail_validation() {
+        let (alice, bob) = (key(1), key(2));
+        let mut chain = Blockchain::new(config(), &[(address(&alice), 100)]);
+        chain.submit_transaction(Transaction::new(&alice, address(&bob), 30, 0, 0)).unwrap();
+        chain.mine_block(address(&bob)).unwrap();
+        chain.mine_block(address(&bob)).unwrap();
+
+        let mut forged = chain.clone();
+        forged.chain[1].transactions[1].amount = 100;
+        assert_eq!(forged.validate_chain(), Err(ChainError::InvalidTransactionsRoot));
+
+        // Recommitting and re-mining the forged block still breaks the signature
+        forged.chain[1].header.transactions_root = Block::transactions_root(&forged.chain[1].transactions);
+        remine(&mut forged.chain[1]);
+        assert_eq!(forged.validate_chain(), Err(ChainError::InvalidSignature));
+
+        let mut rehashed = chain.clone();
+        rehashed.chain[1].header.nonce += 1;
+        assert_eq!(rehashed.validate_chain(), Err(ChainError::InvalidHash));
+
+        let mut inflated = chain.clone();
+        inflated.chain[2].transactions[0].amount += 1;
+        inflated.chain[2].header.transactions_root = Block::transactions_root(&inflated.chain[2].transactions);
+        remine(&mut inflated.chain[2]);
+        assert_eq!(inflated.validate_chain(), Err(ChainError::InvalidReward));
+
+        let mut easier = chain.clone();
+        easier.chain[2].mine_block(Target::MAX, &AtomicBool::new(false)).unwrap();
+        assert_eq!(easier.validate_chain(), Err(ChainError::InvalidTarget));
+
+        let mut unsolved = chain.clone();
+        unsolved.chain[2].header.nonce = (0..).find(|nonce| {
+            let mut header = unsolved.chain[2].header.clone();
+            header.nonce = *nonce;
+            !header.target.is_met_by(&header.hash())
+        }).unwrap();
+        unsolved.chain[2].hash = unsolved.chain[2].calculate_hash();
+        assert_eq!(unsolved.validate_chain(), Err(ChainError::InsufficientWork));
+
+        let mut genesis = chain;
+        genesis.chain[0].transactions[0].amount = 1_000;
+        assert_eq!(genesis.validate_chain(), Err(ChainError::InvalidGenesis));
+    }
+
+    #[test]
+    fn blocks_must_extend_the_tip() {
+        let mut chain = Blockchain::new(config(), &[]);
+        let genesis = chain.latest_block().clone();
+
+        let mut orphan = Block::new_at(1, genesis.header.timestamp, vec![Transaction::reward([7; 32], 50, 1)], [1; 32]);
+        orphan.mine_block(chain.target(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(chain.add_block(orphan), Err(ChainError::InvalidPreviousHash));
+
+        let mut early = Block::new_at(1, genesis.header.timestamp - 1, vec![Transaction::reward([7; 32], 50, 1)], genesis.hash);
+        early.mine_block(chain.target(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(chain.add_block(early), Err(ChainError::InvalidTimestamp));
+
+        let mut late = Block::new_at(1, genesis.header.timestamp + 31, vec![Transaction::reward([7; 32], 50, 1)], genesis.hash);
+        late.mine_block(chain.target(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(chain.add_block(late), Err(ChainError::FutureTimestamp));
+
+        let mut greedy = Block::new_at(1, genesis.header.timestamp, vec![Transaction::reward([7; 32], 51, 1)], genesis.hash);
+        greedy.mine_block(chain.target(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(chain.add_block(greedy), Err(ChainError::InvalidReward));
+
+        let mut valid = Block::new_at(1, genesis.header.timestamp, vec![Transaction::reward([7; 32], 50, 1)], genesis.hash);
+        valid.mine_block(chain.target(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(chain.add_block(valid), Ok(()));
+        assert_eq!(chain.balance(&[7; 32]), 50);
+    }
+
+    #[test]
+    fn cancelled_mining_leaves_the_chain_unchanged() {
+        let alice = key(1);
+        let mut chain = Blockchain::new(ChainConfig { initial_difficulty: 1e30, ..config() }, &[(address(&alice), 100)]);
+        chain.submit_transaction(Transaction::new(&alice, [9; 32], 1, 0, 0)).unwrap();
+
+        let timestamp = chain.latest_block().header.timestamp;
+        assert_eq!(
+            chain.mine_block_with([7; 32], timestamp, &AtomicBool::new(true)),
+            Err(ChainError::MiningCancelled)
+        );
+        assert_eq!(chain.blocks().len(), 1);
+        assert_eq!(chain.mempool().len(), 1);
+    }
+
+    #[test]
+    fn difficulty_follows_block_times() {
+        let mut chain = Blockchain::new(config(), &[]);
+        let mut timestamp = chain.latest_block().header.timestamp;
+        let mut mine = |chain: &mut Blockchain, blocks: u32, spacing: i64| {
+            for _ in 0..blocks {
+                timestamp += spacing;
+                chain.mine_block_at([7; 32], timestamp).unwrap();
+            }
+        };
+
+        // Blocks 4 to 7 at the target time
+        mine(&mut chain, 7, 10);
+        assert_difficulty(&chain, 16.0);
+
+        // Blocks 8 to 11 one second apart: ten times too fast, limited to four times harder
+        mine(&mut chain, 4, 1);
+        assert_difficulty(&chain, 64.0);
+
+        // Blocks 12 to 15 fifteen seconds apart: 1.5 times easier
+        mine(&mut chain, 4, 15);
+        assert_difficulty(&chain, 64.0 / 1.5);
+
+        // Blocks 16 to 19 thirty seconds apart, the most allowed: three times easier
+        mine(&mut chain, 4, 30);
+        assert_difficulty(&chain, 64.0 / 4.5);
+        assert_eq!(chain.blocks()[12].header.target, Target::from_difficulty(64.0));
+        assert_eq!(chain.validate_chain(), Ok(()));
+    }
+}
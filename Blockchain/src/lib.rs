@@ -1,4 +1,26 @@
+//! Standalone toy chain of EchoChain.
+//!
+//! A self-contained proof-of-work chain with signed transfers, a mempool and difficulty
+//! adjustment. It does not depend on a Substrate node, so reward logic can be simulated in
+//! plain unit tests:
+//!
+//! ```
+//! use echochain_node::{blockchain::{Blockchain, ChainConfig}, transaction::{address, Transaction}};
+//! use ed25519_dalek::SigningKey;
+//!
+//! let alice = SigningKey::from_bytes(&[1; 32]);
+//! let bob = SigningKey::from_bytes(&[2; 32]);
+//! let mut chain = Blockchain::new(ChainConfig::default(), &[(address(&alice), 100)]);
+//!
+//! chain.submit_transaction(Transaction::new(&alice, address(&bob), 40, 1, 0)).unwrap();
+//! chain.mine_block(address(&bob)).unwrap();
+//! assert_eq!(chain.balance(&address(&alice)), 59);
+//! assert_eq!(chain.balance(&address(&bob)), 40 + ChainConfig::default().block_reward + 1);
+//! assert!(chain.validate_chain().is_ok());
+//! ```
+
 pub mod block;
+pub mod pow;
 pub mod blockchain;
 pub mod transaction;
-pub mod smart_contract;
\ No newline at end of file
+pub mod smart_contract;
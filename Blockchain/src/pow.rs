@@ -0,0 +1,256 @@
+//! Proof of work: 256-bit targets and the parallel nonce search.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use codec::{Decode, Encode};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::block::{BlockHeader, Hash};
+
+/// Hashes computed by a thread between two checks for a solution or cancellation.
+const BATCH: u64 = 1024;
+
+/// A 256-bit proof-of-work target, big-endian. A hash meets the target if, read as a
+/// big-endian integer, it is at most the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Encode, Decode)]
+pub struct Target(pub [u8; 32]);
+
+impl Target {
+    /// Easiest target, of difficulty 1: every hash meets it.
+    pub const MAX: Target = Target([0xff; 32]);
+
+    /// Target of `difficulty`, i.e. `MAX / difficulty` rounded down, computed exactly for any
+    /// finite difficulty. Anything below 1 is clamped to `MAX`; an infinite difficulty gives
+    /// the zero target.
+    pub fn from_difficulty(difficulty: f64) -> Target {
+        if difficulty.is_nan() || difficulty <= 1.0 {
+            return Target::MAX;
+        }
+        if difficulty.is_infinite() {
+            return Target([0; 32]);
+        }
+        // difficulty = mantissa * 2^exponent, with an odd mantissa below 2^53
+        let bits = difficulty.to_bits();
+        let mut mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let mut exponent = ((bits >> 52) & 0x7ff) as i32 - 1075;
+        let zeros = mantissa.trailing_zeros();
+        mantissa >>= zeros;
+        exponent += zeros as i32;
+        if exponent >= 0 {
+            Target::MAX.shr(exponent as u32).mul_div(1, mantissa)
+        } else {
+            // A difficulty above 1 has an exponent of at least -52
+            Target::MAX.mul_div(1 << -exponent, mantissa)
+        }
+    }
+
+    /// Expected number of hashes to meet the target, relative to `MAX`.
+    pub fn difficulty(&self) -> f64 {
+        Target::MAX.to_f64() / self.to_f64().max(1.0)
+    }
+
+    pub fn is_met_by(&self, hash: &Hash) -> bool {
+        // Big-endian byte arrays compare like the integers they encode
+        *hash <= self.0
+    }
+
+    /// `self * numerator / denominator`, rounded down and capped at `MAX`.
+    pub fn mul_div(&self, numerator: u64, denominator: u64) -> Target {
+        assert!(denominator != 0, "division by zero");
+        // Little-endian 64-bit limbs, with a fifth limb for the overflow of the product
+        let mut limbs = [0u64; 5];
+        let mut carry = 0u128;
+        for (i, limb) in limbs.iter_mut().take(4).enumerate() {
+            let start = 24 - 8 * i;
+            let word = u64::from_be_bytes(self.0[start..start + 8].try_into().expect("8 bytes; qed"));
+            let product = u128::from(word) * u128::from(numerator) + carry;
+            *limb = product as u64;
+            carry = product >> 64;
+        }
+        limbs[4] = carry as u64;
+
+        let mut remainder = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let dividend = (remainder << 64) | u128::from(*limb);
+            *limb = (dividend / u128::from(denominator)) as u64;
+            remainder = dividend % u128::from(denominator);
+        }
+        if limbs[4] != 0 {
+            return Target::MAX;
+        }
+
+        let mut bytes = [0u8; 32];
+        for (i, limb) in limbs.iter().take(4).enumerate() {
+            let start = 24 - 8 * i;
+            bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        Target(bytes)
+    }
+
+    /// Target after an adjustment window that took `actual` seconds instead of `expected`.
+    /// The change is limited to a factor of 4 either way, and the target never exceeds `limit`.
+    pub fn retarget(&self, actual: i64, expected: i64, limit: Target) -> Target {
+        let expected = expected.max(1) as u64;
+        let actual = (actual.max(0) as u64).clamp(expected.div_ceil(4), expected.saturating_mul(4));
+        self.mul_div(actual, expected).min(limit)
+    }
+
+    /// `self >> bits`, zero once every bit is shifted out.
+    fn shr(&self, bits: u32) -> Target {
+        let (bytes, bits) = ((bits / 8) as usize, bits % 8);
+        let mut shifted = [0u8; 32];
+        for (i, byte) in shifted.iter_mut().enumerate().skip(bytes) {
+            *byte = self.0[i - bytes] >> bits;
+            if bits > 0 && i > bytes {
+                *byte |= self.0[i - bytes - 1] << (8 - bits);
+            }
+        }
+        Target(shifted)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0.iter().fold(0.0, |value, byte| value * 256.0 + f64::from(*byte))
+    }
+}
+
+/// Statistics of a successful nonce search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MiningStats {
+    /// Nonce of the solution.
+    pub nonce: u64,
+    /// Hashes computed by all threads, including the ones after the solution was found.
+    pub hashes: u64,
+    pub elapsed: Duration,
+    pub threads: usize,
+}
+
+impl MiningStats {
+    /// Hashes per second.
+    pub fn hash_rate(&self) -> f64 {
+        self.hashes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Search nonces on all rayon threads, from `header.nonce` on, until one gives a hash
+/// meeting `header.target`. Returns the solution and the statistics of the search, or `None`
+/// once `cancel` is set, e.g. because another miner extended the chain.
+pub fn search_nonce(header: &BlockHeader, cancel: &AtomicBool) -> Option<(Hash, MiningStats)> {
+    let started = Instant::now();
+    let threads = rayon::current_num_threads();
+    let hashes = AtomicU64::new(0);
+    let found = AtomicBool::new(false);
+
+    let solution = (0..threads as u64).into_par_iter().find_map_any(|thread| {
+        let mut candidate = header.clone();
+        candidate.nonce = header.nonce.wrapping_add(thread);
+        loop {
+            for done in 1..=BATCH {
+                let hash = candidate.hash();
+                if header.target.is_met_by(&hash) {
+                    found.store(true, Ordering::Relaxed);
+                    hashes.fetch_add(done, Ordering::Relaxed);
+                    return Some((candidate.nonce, hash));
+                }
+                candidate.nonce = candidate.nonce.wrapping_add(threads as u64);
+            }
+            hashes.fetch_add(BATCH, Ordering::Relaxed);
+            if found.load(Ordering::Relaxed) || cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+        }
+    })?;
+
+    let (nonce, hash) = solution;
+    let stats = MiningStats { nonce, hashes: hashes.load(Ordering::Relaxed), elapsed: started.elapsed(), threads };
+    Some((hash, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(hex: &str) -> Target {
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        Target(bytes)
+    }
+
+    #[test]
+    fn difficulty_and_target_are_inverse() {
+        assert_eq!(Target::from_difficulty(1.0), Target::MAX);
+        assert_eq!(Target::from_difficulty(0.5), Target::MAX);
+        assert_eq!(
+            Target::from_difficulty(16.0),
+            target("0fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")
+        );
+        assert_eq!(
+            Target::from_difficulty(2.5),
+            target("6666666666666666666666666666666666666666666666666666666666666666")
+        );
+        // Above 2^32
+        assert_eq!(
+            Target::from_difficulty(1_099_511_627_776.0),
+            target("0000000000ffffffffffffffffffffffffffffffffffffffffffffffffffffff")
+        );
+        assert_eq!(
+            Target::from_difficulty(3.0 * 2f64.powi(40)),
+            target("0000000000555555555555555555555555555555555555555555555555555555")
+        );
+        assert_eq!(Target::from_difficulty(2f64.powi(256)), Target([0; 32]));
+        assert_eq!(Target::from_difficulty(f64::INFINITY), Target([0; 32]));
+        for difficulty in [1.0, 1.5, 16.0, 1000.25, 1e9, 1e12, 1e30] {
+            assert!((Target::from_difficulty(difficulty).difficulty() / difficulty - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn hashes_meet_targets_numerically() {
+        let target = target("00ffff0000000000000000000000000000000000000000000000000000000000");
+        let mut hash = target.0;
+        assert!(target.is_met_by(&hash));
+        hash[31] = 1;
+        assert!(!target.is_met_by(&hash));
+        hash = [0; 32];
+        hash[1] = 0xff;
+        hash[2] = 0xfe;
+        hash[3] = 0xff;
+        assert!(target.is_met_by(&hash));
+    }
+
+    #[test]
+    fn retargeting_is_bounded() {
+        let start = Target::from_difficulty(64.0);
+        assert_eq!(start.retarget(40, 40, Target::MAX), start);
+        assert!((start.retarget(20, 40, Target::MAX).difficulty() - 128.0).abs() < 1e-6);
+        assert!((start.retarget(1, 40, Target::MAX).difficulty() - 256.0).abs() < 1e-6);
+        assert!((start.retarget(1_000, 40, Target::MAX).difficulty() - 16.0).abs() < 1e-6);
+        assert_eq!(Target::MAX.retarget(80, 40, Target::MAX), Target::MAX);
+        let limit = Target::from_difficulty(32.0);
+        assert_eq!(start.retarget(1_000, 40, limit), limit);
+    }
+
+    #[test]
+    fn nonce_search_finds_solutions_and_stops_on_cancel() {
+        let mut header = BlockHeader {
+            index: 1,
+            timestamp: 0,
+            previous_hash: [0; 32],
+            transactions_root: [0; 32],
+            target: Target::from_difficulty(4096.0),
+            nonce: 0,
+        };
+        let (hash, stats) = search_nonce(&header, &AtomicBool::new(false)).unwrap();
+        header.nonce = stats.nonce;
+        assert_eq!(header.hash(), hash);
+        assert!(header.target.is_met_by(&hash));
+        assert!(stats.hashes >= 1);
+        assert_eq!(stats.threads, rayon::current_num_threads());
+
+        header.target = Target([0; 32]);
+        assert_eq!(search_nonce(&header, &AtomicBool::new(true)), None);
+    }
+}
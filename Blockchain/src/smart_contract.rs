@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use ed25519_dalek::SigningKey;
+
+use crate::blockchain::{Blockchain, ChainError};
+use crate::transaction::{address, Address, Transaction};
+
+/// Content reward contract of the Proof-of-Contribution model: every creator with at least
+/// `min_samples` approved samples in a period earns `reward` from the reward pool, as
+/// `pallet-proof-of-contribution` does on chain.
+pub struct ContentRewardContract {
+    pool: SigningKey,
+    pub min_samples: u32,
+    pub reward: u64,
+}
+
+impl ContentRewardContract {
+    /// Contract paying from the account of `pool`.
+    pub fn new(pool: SigningKey, min_samples: u32, reward: u64) -> ContentRewardContract {
+        ContentRewardContract { pool, min_samples, reward }
+    }
+
+    pub fn pool(&self) -> Address {
+        address(&self.pool)
+    }
+
+    /// Creators eligible for a reward, given their approved samples in the period.
+    pub fn eligible(&self, approved_samples: &BTreeMap<Address, u32>) -> Vec<Address> {
+        approved_samples
+            .iter()
+            .filter(|(_, count)| **count >= self.min_samples)
+            .map(|(creator, _)| *creator)
+            .collect()
+    }
+
+    /// Submit the rewards of a period to the mempool of `chain`, returning the creators
+    /// paid. Creators are skipped once the pool runs dry.
+    pub fn execute(
+        &self,
+        chain: &mut Blockchain,
+        approved_samples: &BTreeMap<Address, u32>,
+    ) -> Result<Vec<Address>, ChainError> {
+        let mut rewarded = Vec::new();
+        for creator in self.eligible(approved_samples) {
+            let nonce = chain.next_nonce(&self.pool());
+            match chain.submit_transaction(Transaction::new(&self.pool, creator, self.reward, 0, nonce)) {
+                Ok(()) => rewarded.push(creator),
+                Err(ChainError::InsufficientBalance) => break,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(rewarded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::ChainConfig;
+
+    #[test]
+    fn rewards_creators_with_enough_approved_samples() {
+        let contract = ContentRewardContract::new(SigningKey::from_bytes(&[1; 32]), 5, 100);
+        let mut chain = Blockchain::new(
+            ChainConfig { initial_difficulty: 16.0, ..ChainConfig::default() },
+            &[(contract.pool(), 250)],
+        );
+        let approved_samples = BTreeMap::from([([2; 32], 5), ([3; 32], 4), ([4; 32], 9), ([5; 32], 6)]);
+
+        assert_eq!(contract.eligible(&approved_samples), vec![[2; 32], [4; 32], [5; 32]]);
+        assert_eq!(contract.execute(&mut chain, &approved_samples), Ok(vec![[2; 32], [4; 32]]));
+
+        chain.mine_block([9; 32]).unwrap();
+        assert_eq!(chain.balance(&[2; 32]), 100);
+        assert_eq!(chain.balance(&[3; 32]), 0);
+        assert_eq!(chain.balance(&[4; 32]), 100);
+        assert_eq!(chain.balance(&[5; 32]), 0);
+        assert_eq!(chain.balance(&contract.pool()), 50);
+    }
+}
@@ -0,0 +1,123 @@
+use codec::{Decode, Encode};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::block::Hash;
+
+/// Account of the toy chain: an ed25519 public key.
+pub type Address = [u8; 32];
+
+/// Address of the account controlled by `key`.
+pub fn address(key: &SigningKey) -> Address {
+    key.verifying_key().to_bytes()
+}
+
+/// A transfer between two accounts, or a reward minted by the chain.
+///
+/// The SCALE encoding is canonical: fields in declaration order, integers little-endian,
+/// `from` prefixed by a `0`/`1` option byte and `signature` by its compact length.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct Transaction {
+    /// Sender, `None` for rewards.
+    pub from: Option<Address>,
+    pub to: Address,
+    pub amount: u64,
+    /// Paid to the miner of the block including the transaction.
+    pub fee: u64,
+    /// Number of transactions sent by `from` before this one. The height of the block for rewards.
+    pub nonce: u64,
+    /// Signature of `signing_payload` by `from`, empty for rewards.
+    pub signature: Vec<u8>,
+}
+
+impl Transaction {
+    /// Transfer of `amount` from the account of `key` to `to`, signed by `key`.
+    pub fn new(key: &SigningKey, to: Address, amount: u64, fee: u64, nonce: u64) -> Transaction {
+        let mut transaction = Transaction {
+            from: Some(address(key)),
+            to,
+            amount,
+            fee,
+            nonce,
+            signature: Vec::new(),
+        };
+        transaction.signature = key.sign(&transaction.signing_payload()).to_bytes().to_vec();
+        transaction
+    }
+
+    /// Reward of `amount` minted to `to` in the block at `height`.
+    pub fn reward(to: Address, amount: u64, height: u32) -> Transaction {
+        Transaction {
+            from: None,
+            to,
+            amount,
+            fee: 0,
+            nonce: u64::from(height),
+            signature: Vec::new(),
+        }
+    }
+
+    pub fn is_reward(&self) -> bool {
+        self.from.is_none()
+    }
+
+    /// Bytes signed by the sender: the SCALE encoding of every field but the signature.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        (&self.from, &self.to, self.amount, self.fee, self.nonce).encode()
+    }
+
+    /// SHA-256 of the SCALE encoding, signature included. Leaf of the transactions root.
+    pub fn hash(&self) -> Hash {
+        Sha256::digest(self.encode()).into()
+    }
+
+    /// Whether the transaction is signed by its sender. Rewards carry no signature.
+    pub fn verify_signature(&self) -> bool {
+        let from = match &self.from {
+            Some(from) => from,
+            None => return self.signature.is_empty(),
+        };
+        let signature = match Signature::from_slice(&self.signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        VerifyingKey::from_bytes(from)
+            .map(|key| key.verify(&self.signing_payload(), &signature).is_ok())
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_cover_every_field() {
+        let alice = SigningKey::from_bytes(&[1; 32]);
+        let transaction = Transaction::new(&alice, [2; 32], 10, 1, 0);
+        assert!(transaction.verify_signature());
+
+        let mut tampered = transaction.clone();
+        tampered.amount = 11;
+        assert!(!tampered.verify_signature());
+
+        let mut forged = transaction.clone();
+        forged.from = Some(address(&SigningKey::from_bytes(&[3; 32])));
+        assert!(!forged.verify_signature());
+
+        let mut truncated = transaction;
+        truncated.signature.pop();
+        assert!(!truncated.verify_signature());
+    }
+
+    #[test]
+    fn rewards_are_unsigned() {
+        let mut reward = Transaction::reward([2; 32], 50, 1);
+        assert!(reward.is_reward());
+        assert!(reward.verify_signature());
+
+        reward.signature = vec![0; 64];
+        assert!(!reward.verify_signature());
+    }
+}
@@ -1,13 +1,30 @@
-from fastapi import FastAPI, UploadFile, File
+from typing import Optional
+
+from fastapi import FastAPI, File, Form, HTTPException, UploadFile
 from fastapi.responses import JSONResponse
 
 app = FastAPI()
 
+# Analysis results by IPFS CID, polled by the sample registry's offchain worker
+results = {}
+
 @app.post('/analyze')
-async def analyze_audio(file: UploadFile = File(...)):
+async def analyze_audio(file: UploadFile = File(...), cid: Optional[str] = Form(None)):
     # TODO: Implement BPM/key detection using aubio/essentia
-    return JSONResponse({
+    result = {
         'bpm': None,
         'key': None,
+        'quality': None,
         'message': 'Audio analysis not yet implemented.'
-    }) 
\ No newline at end of file
+    }
+    if cid is not None:
+        results[cid] = result
+    return JSONResponse(result)
+
+@app.get('/analysis/{cid}')
+async def get_analysis(cid: str):
+    # 404 until the sample has a quality score, so the offchain worker retries later
+    result = results.get(cid)
+    if result is None or result['quality'] is None:
+        raise HTTPException(status_code=404, detail='No analysis for this sample yet.')
+    return JSONResponse({'bpm': result['bpm'], 'key': result['key'], 'quality': result['quality']})
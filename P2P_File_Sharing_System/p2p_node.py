@@ -152,6 +152,23 @@ class P2PNode:
                 hasher.update(chunk)
         return hasher.hexdigest()
 
+    @staticmethod
+    def _chunk_root(chunks):
+        """Merkle root over the SHA-256 chunk hashes, as registered with the sample on chain.
+
+        Inner nodes are sha256(left + right); an unpaired node is promoted unchanged.
+        Must match `echochain_chunk_proof::chunk_root` in the runtime.
+        """
+        level = [hashlib.sha256(chunk).digest() for chunk in chunks]
+        if not level:
+            return bytes(32)
+        while len(level) > 1:
+            level = [
+                hashlib.sha256(level[i] + level[i + 1]).digest() if i + 1 < len(level) else level[i]
+                for i in range(0, len(level), 2)
+            ]
+        return level[0]
+
     def add_file(self, filepath):
         if not os.path.exists(filepath):
             print(f"File not found: {filepath}")
@@ -175,7 +192,8 @@ class P2PNode:
         self.files[file_hash] = {
             'filename': filename,
             'size': file_size,
-            'chunks': file_chunks
+            'chunks': file_chunks,
+            'chunk_root': self._chunk_root(file_chunks.values()).hex()
         }
         print(f"Added file {filename} with hash {file_hash} and {len(file_chunks)} chunks.")
         return file_hash
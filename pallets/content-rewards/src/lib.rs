@@ -6,18 +6,45 @@ pub use pallet::*;
 pub mod pallet {
     use frame_support::{
         pallet_prelude::*,
-        traits::{Currency, ExistenceRequirement, Get},
-        BoundedVec,
+        storage::with_storage_layer,
+        traits::{Contains, Currency, ExistenceRequirement, Get},
+        BoundedVec, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
+    };
+    use frame_system::{
+        offchain::{AppCrypto, CreateSignedTransaction, SendUnsignedTransaction, SignedPayload, SigningTypes},
+        pallet_prelude::*,
+    };
+    use sp_runtime::{
+        traits::{Saturating, UniqueSaturatedInto, Zero},
+        Perbill,
     };
-    use frame_system::pallet_prelude::*;
-    use sp_runtime::traits::Saturating;
     use sp_std::vec::Vec;
+    use pallet_reputation::ReputationProvider;
+    use pallet_reward_vesting::RewardVesting;
 
     type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+    /// Users rewarded by one distribution.
+    pub type EligibleUsers<T> = BoundedVec<<T as frame_system::Config>::AccountId, <T as Config>::MaxRewardsPerPeriod>;
+
+    /// Payload an offchain worker signs to reward `eligible_users` for `period`.
+    #[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, TypeInfo)]
+    #[scale_info(skip_type_params(T))]
+    pub struct RewardsPayload<T: Config> {
+        pub period: u32,
+        pub eligible_users: EligibleUsers<T>,
+        pub public: T::Public,
+    }
+
+    impl<T: Config> SignedPayload<T> for RewardsPayload<T> {
+        fn public(&self) -> T::Public {
+            self.public.clone()
+        }
+    }
+
     #[pallet::config]
-    pub trait Config: frame_system::Config {
+    pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
         type Currency: Currency<Self::AccountId>;
         #[pallet::constant]
@@ -26,10 +53,26 @@ pub mod pallet {
         type RewardAmount: Get<BalanceOf<Self>>;
         #[pallet::constant]
         type RewardPoolAccount: Get<Self::AccountId>;
+        /// Portion of every reward locked in a vesting schedule instead of paid out liquid.
+        #[pallet::constant]
+        type VestedPortion: Get<Perbill>;
+        type Vesting: RewardVesting<Self::AccountId, BalanceOf<Self>>;
         type SampleMetadata: SampleMetadataProvider<Self::AccountId>;
+        /// Scales each user's reward by their reputation.
+        type Reputation: ReputationProvider<Self::AccountId>;
         /// Number of blocks between reward distributions (e.g., ~1 month)
         #[pallet::constant]
         type RewardPeriod: Get<Self::BlockNumber>;
+        /// Key type offchain workers sign reward payloads with.
+        type AuthorityId: AppCrypto<Self::Public, <Self as SigningTypes>::Signature>;
+        /// Offchain-worker keys allowed to submit reward payloads.
+        type Workers: Contains<Self::Public>;
+        /// Priority of unsigned reward transactions.
+        #[pallet::constant]
+        type UnsignedPriority: Get<TransactionPriority>;
+        /// Maximum number of users rewarded per period.
+        #[pallet::constant]
+        type MaxRewardsPerPeriod: Get<u32>;
     }
 
     // Cross-pallet trait for querying approved sample count
@@ -45,40 +88,91 @@ pub mod pallet {
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
-        RewardDistributed { user: T::AccountId, amount: BalanceOf<T> },
+        RewardDistributed { user: T::AccountId, amount: BalanceOf<T>, vested: BalanceOf<T> },
+        /// Paying `user` failed; the reward stays in the pool.
+        RewardFailed { user: T::AccountId, amount: BalanceOf<T>, error: DispatchError },
     }
 
     #[pallet::error]
     pub enum Error<T> {
         AlreadyRewardedThisPeriod,
         RewardPoolEmpty,
+        /// The signed payload is not for the current period.
+        WrongRewardPeriod,
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Distribute rewards to eligible users (root or off-chain worker only)
-        #[pallet::weight(10_000)]
-        pub fn distribute_rewards(origin: OriginFor<T>, eligible_users: Vec<T::AccountId>) -> DispatchResult {
-            ensure_none(origin.clone()).or_else(|_| ensure_root(origin))?;
+        /// Distribute rewards to eligible users (root only)
+        #[pallet::weight(Pallet::<T>::distribution_weight(eligible_users.len() as u32))]
+        pub fn distribute_rewards(origin: OriginFor<T>, eligible_users: EligibleUsers<T>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::do_distribute(eligible_users)
+        }
+
+        /// Same as `distribute_rewards`, submitted by an offchain worker as an unsigned
+        /// transaction carrying a payload signed by an authorized worker key. The signature
+        /// is checked in `validate_unsigned`.
+        #[pallet::weight(Pallet::<T>::distribution_weight(payload.eligible_users.len() as u32))]
+        pub fn distribute_rewards_unsigned(
+            origin: OriginFor<T>,
+            payload: RewardsPayload<T>,
+            _signature: <T as SigningTypes>::Signature,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            ensure!(payload.period == Self::current_period(), Error::<T>::WrongRewardPeriod);
+            Self::do_distribute(payload.eligible_users)
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Index of the reward period containing `block`.
+        fn period_of(block: T::BlockNumber) -> u32 {
+            (block / T::RewardPeriod::get()).unique_saturated_into()
+        }
+
+        /// Index of the reward period containing the current block.
+        pub fn current_period() -> u32 {
+            Self::period_of(<frame_system::Pallet<T>>::block_number())
+        }
 
+        /// Whether rewards were already distributed during `period`.
+        fn rewarded_in(period: u32) -> bool {
+            let last = Self::last_reward_block();
+            !last.is_zero() && Self::period_of(last) >= period
+        }
+
+        /// Weight of distributing rewards to `users` users: per user, reading the sample count
+        /// and the reputation and paying the reward into the account and its vesting schedules.
+        pub fn distribution_weight(users: u32) -> Weight {
+            let users = u64::from(users);
+            T::DbWeight::get()
+                .reads_writes(1u64.saturating_add(users.saturating_mul(6)), 1u64.saturating_add(users.saturating_mul(5)))
+                .saturating_add(Weight::from_parts(10_000u64.saturating_add(users.saturating_mul(50_000)), 0))
+        }
+
+        fn do_distribute(eligible_users: EligibleUsers<T>) -> DispatchResult {
             let now = <frame_system::Pallet<T>>::block_number();
-            if let Some(last) = Self::last_reward_block() {
-                ensure!(now > last, Error::<T>::AlreadyRewardedThisPeriod);
-            }
+            ensure!(!Self::rewarded_in(Self::current_period()), Error::<T>::AlreadyRewardedThisPeriod);
+
+            // Every user is rewarded at most once
+            let mut eligible_users = eligible_users.into_inner();
+            eligible_users.sort();
+            eligible_users.dedup();
 
             let mut distributed = false;
-            for user in eligible_users.iter() {
-                let count = T::SampleMetadata::approved_sample_count(user);
+            for user in eligible_users {
+                let count = T::SampleMetadata::approved_sample_count(&user);
                 if count >= T::MinApprovedSamples::get() {
-                    let amount = T::RewardAmount::get();
-                    T::Currency::transfer(
-                        &T::RewardPoolAccount::get(),
-                        user,
-                        amount,
-                        ExistenceRequirement::KeepAlive,
-                    )?;
-                    Self::deposit_event(Event::RewardDistributed { user: user.clone(), amount });
-                    distributed = true;
+                    let amount = T::Reputation::reward_multiplier(&user) * T::RewardAmount::get();
+                    // A failed payout must not hold back the other users
+                    match with_storage_layer(|| Self::pay_reward(&user, amount)) {
+                        Ok(vested) => {
+                            Self::deposit_event(Event::RewardDistributed { user, amount, vested });
+                            distributed = true;
+                        }
+                        Err(error) => Self::deposit_event(Event::RewardFailed { user, amount, error }),
+                    }
                 }
             }
             if distributed {
@@ -86,6 +180,42 @@ pub mod pallet {
             }
             Ok(())
         }
+
+        /// Checks an offchain worker's reward payload before it enters the pool.
+        fn validate_rewards(
+            payload: &RewardsPayload<T>,
+            signature: &<T as SigningTypes>::Signature,
+        ) -> TransactionValidity {
+            if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+                return InvalidTransaction::BadProof.into();
+            }
+            if !T::Workers::contains(&payload.public) {
+                return InvalidTransaction::BadSigner.into();
+            }
+            let current = Self::current_period();
+            if payload.period < current || Self::rewarded_in(payload.period) {
+                return InvalidTransaction::Stale.into();
+            }
+            if payload.period > current {
+                return InvalidTransaction::Future.into();
+            }
+
+            ValidTransaction::with_tag_prefix("ContentRewardsDistribution")
+                .priority(T::UnsignedPriority::get())
+                .and_provides(payload.period)
+                .longevity(T::RewardPeriod::get().unique_saturated_into())
+                .propagate(true)
+                .build()
+        }
+
+        /// Pay `amount` out of the reward pool, routing `VestedPortion` of it into vesting.
+        fn pay_reward(user: &T::AccountId, amount: BalanceOf<T>) -> Result<BalanceOf<T>, DispatchError> {
+            let pool = T::RewardPoolAccount::get();
+            let vested = T::VestedPortion::get() * amount;
+            T::Currency::transfer(&pool, user, amount.saturating_sub(vested), ExistenceRequirement::KeepAlive)?;
+            T::Vesting::vest(&pool, user, vested)?;
+            Ok(vested)
+        }
     }
 
     #[pallet::hooks]
@@ -93,19 +223,40 @@ pub mod pallet {
         fn offchain_worker(now: T::BlockNumber) {
             // Only run at the start of a new period
             if (now % T::RewardPeriod::get()).is_zero() {
-                if let Some(last) = Self::last_reward_block() {
-                    if now <= last { return; }
+                let period = Self::current_period();
+                if Self::rewarded_in(period) {
+                    return;
                 }
-                // Find eligible users (off-chain, for scalability)
-                let eligible_users = T::SampleMetadata::all_users()
-                    .into_iter()
-                    .filter(|user| T::SampleMetadata::approved_sample_count(user) >= T::MinApprovedSamples::get())
-                    .collect::<Vec<_>>();
+                // Find eligible users (off-chain, for scalability), at most `MaxRewardsPerPeriod`
+                let eligible_users = EligibleUsers::<T>::truncate_from(
+                    T::SampleMetadata::all_users()
+                        .into_iter()
+                        .filter(|user| T::SampleMetadata::approved_sample_count(user) >= T::MinApprovedSamples::get())
+                        .collect::<Vec<_>>(),
+                );
                 if !eligible_users.is_empty() {
-                    let call = Call::<T>::distribute_rewards { eligible_users };
-                    let _ = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into());
+                    let _ = frame_system::offchain::Signer::<T, T::AuthorityId>::any_account().send_unsigned_transaction(
+                        |account| RewardsPayload {
+                            period,
+                            eligible_users: eligible_users.clone(),
+                            public: account.public.clone(),
+                        },
+                        |payload, signature| Call::distribute_rewards_unsigned { payload, signature },
+                    );
                 }
             }
         }
     }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            match call {
+                Call::distribute_rewards_unsigned { payload, signature } => Self::validate_rewards(payload, signature),
+                _ => InvalidTransaction::Call.into(),
+            }
+        }
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,256 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Exposes contributor bonds to other pallets, e.g. to only trust peers with stake at risk.
+pub trait BondedStake<AccountId, Balance> {
+    /// Total slashable stake of `who`.
+    fn bonded(who: &AccountId) -> Balance;
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{BalanceStatus, Currency, Get, NamedReservableCurrency},
+    };
+    use frame_system::pallet_prelude::*;
+    use super::BondedStake;
+    use pallet_reputation::{ReputationEvent, ReputationHandler};
+    use pallet_reward_vesting::RewardClawback;
+    use sp_runtime::{
+        traits::{Saturating, Zero},
+        Perbill,
+    };
+    use sp_std::vec::Vec;
+
+    type BalanceOf<T> =
+        <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+    pub type SlashIndex = u32;
+
+    /// Named reserve holding contributor bonds, so slashes never touch other reserves.
+    pub const BOND_RESERVE_ID: [u8; 8] = *b"echobond";
+
+    /// Stake a contributor has put up as a guarantee for their contributions.
+    #[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct Bond<Balance, BlockNumber> {
+        /// Bonded amount backing current contributions.
+        pub active: Balance,
+        /// Amount being unbonded. It can still be slashed until `unlock_at`.
+        pub unbonding: Balance,
+        pub unlock_at: BlockNumber,
+    }
+
+    /// Audit record of a slash.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(MaxCidLength))]
+    pub struct SlashRecord<AccountId, Balance, BlockNumber, MaxCidLength: Get<u32>> {
+        pub who: AccountId,
+        pub bond_slashed: Balance,
+        pub clawed_back: Balance,
+        pub evidence_cid: BoundedVec<u8, MaxCidLength>,
+        pub at: BlockNumber,
+    }
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        type Currency: NamedReservableCurrency<Self::AccountId, ReserveIdentifier = [u8; 8]>;
+        /// Origin allowed to slash contributors, normally governance.
+        type SlashOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Receives slashed bonds and clawed back rewards.
+        #[pallet::constant]
+        type TreasuryAccount: Get<Self::AccountId>;
+        /// Number of blocks unbonded stake stays slashable.
+        #[pallet::constant]
+        type UnbondingPeriod: Get<Self::BlockNumber>;
+        /// Maximum length of an evidence CID.
+        #[pallet::constant]
+        type MaxCidLength: Get<u32>;
+        type Clawback: RewardClawback<Self::AccountId, BalanceOf<Self>>;
+        type Reputation: ReputationHandler<Self::AccountId>;
+    }
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::storage]
+    #[pallet::getter(fn bonds)]
+    pub type Bonds<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Bond<BalanceOf<T>, T::BlockNumber>,
+        OptionQuery
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn slashes)]
+    pub type Slashes<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        SlashIndex,
+        SlashRecord<T::AccountId, BalanceOf<T>, T::BlockNumber, T::MaxCidLength>,
+        OptionQuery
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn next_slash_index)]
+    pub type NextSlashIndex<T: Config> = StorageValue<_, SlashIndex, ValueQuery>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        Bonded { who: T::AccountId, amount: BalanceOf<T> },
+        Unbonding { who: T::AccountId, amount: BalanceOf<T>, unlock_at: T::BlockNumber },
+        Withdrawn { who: T::AccountId, amount: BalanceOf<T> },
+        ContributorSlashed {
+            index: SlashIndex,
+            who: T::AccountId,
+            bond_slashed: BalanceOf<T>,
+            clawed_back: BalanceOf<T>,
+            evidence_cid: BoundedVec<u8, T::MaxCidLength>,
+        },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        ZeroBond,
+        NotBonded,
+        InsufficientBond,
+        StillUnbonding,
+        CidTooLong,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Reserve `amount` of the caller's balance as a contribution bond.
+        #[pallet::weight(10_000)]
+        pub fn bond(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::ZeroBond);
+            T::Currency::reserve_named(&BOND_RESERVE_ID, &who, amount)?;
+            Bonds::<T>::mutate(&who, |bond| {
+                let bond = bond.get_or_insert_with(Default::default);
+                bond.active = bond.active.saturating_add(amount);
+            });
+            Self::deposit_event(Event::Bonded { who, amount });
+            Ok(())
+        }
+
+        /// Start unbonding `amount`. It remains slashable for `UnbondingPeriod` blocks.
+        #[pallet::weight(10_000)]
+        pub fn unbond(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::ZeroBond);
+            let unlock_at = <frame_system::Pallet<T>>::block_number().saturating_add(T::UnbondingPeriod::get());
+            Bonds::<T>::try_mutate(&who, |bond| -> DispatchResult {
+                let bond = bond.as_mut().ok_or(Error::<T>::NotBonded)?;
+                ensure!(bond.active >= amount, Error::<T>::InsufficientBond);
+                bond.active -= amount;
+                bond.unbonding = bond.unbonding.saturating_add(amount);
+                bond.unlock_at = unlock_at;
+                Ok(())
+            })?;
+            Self::deposit_event(Event::Unbonding { who, amount, unlock_at });
+            Ok(())
+        }
+
+        /// Release stake whose unbonding period has passed.
+        #[pallet::weight(10_000)]
+        pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let now = <frame_system::Pallet<T>>::block_number();
+            let mut bond = Bonds::<T>::get(&who).ok_or(Error::<T>::NotBonded)?;
+            ensure!(now >= bond.unlock_at, Error::<T>::StillUnbonding);
+
+            let amount = sp_std::mem::take(&mut bond.unbonding);
+            T::Currency::unreserve_named(&BOND_RESERVE_ID, &who, amount);
+            if bond.active.is_zero() {
+                Bonds::<T>::remove(&who);
+            } else {
+                Bonds::<T>::insert(&who, bond);
+            }
+            Self::deposit_event(Event::Withdrawn { who, amount });
+            Ok(())
+        }
+
+        /// Slash `bond_fraction` of `who`'s bond and claw back their unvested rewards into the
+        /// treasury. `evidence_cid` points to the evidence backing the decision.
+        #[pallet::weight(10_000)]
+        pub fn slash_contributor(
+            origin: OriginFor<T>,
+            who: T::AccountId,
+            bond_fraction: Perbill,
+            evidence_cid: Vec<u8>,
+        ) -> DispatchResult {
+            T::SlashOrigin::ensure_origin(origin)?;
+            let evidence_cid: BoundedVec<u8, T::MaxCidLength> =
+                evidence_cid.try_into().map_err(|_| Error::<T>::CidTooLong)?;
+            let treasury = T::TreasuryAccount::get();
+
+            let bond_slashed = match Bonds::<T>::get(&who) {
+                Some(mut bond) => {
+                    let target = bond_fraction * bond.active.saturating_add(bond.unbonding);
+                    let unslashed = T::Currency::repatriate_reserved_named(
+                        &BOND_RESERVE_ID,
+                        &who,
+                        &treasury,
+                        target,
+                        BalanceStatus::Free,
+                    )?;
+                    // Only what was actually moved leaves the bond. Unbonding stake is slashed
+                    // first, then the active bond.
+                    let slashed = target.saturating_sub(unslashed);
+                    let from_unbonding = slashed.min(bond.unbonding);
+                    bond.unbonding -= from_unbonding;
+                    bond.active = bond.active.saturating_sub(slashed - from_unbonding);
+                    if bond.active.is_zero() && bond.unbonding.is_zero() {
+                        Bonds::<T>::remove(&who);
+                    } else {
+                        Bonds::<T>::insert(&who, bond);
+                    }
+                    slashed
+                }
+                None => Zero::zero(),
+            };
+            let clawed_back = T::Clawback::clawback(&who, &treasury)?;
+            T::Reputation::note(&who, ReputationEvent::ReportUpheld);
+
+            let index = NextSlashIndex::<T>::mutate(|index| {
+                let current = *index;
+                *index = index.saturating_add(1);
+                current
+            });
+            Slashes::<T>::insert(index, SlashRecord {
+                who: who.clone(),
+                bond_slashed,
+                clawed_back,
+                evidence_cid: evidence_cid.clone(),
+                at: <frame_system::Pallet<T>>::block_number(),
+            });
+            Self::deposit_event(Event::ContributorSlashed { index, who, bond_slashed, clawed_back, evidence_cid });
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Total slashable stake of `who`.
+        pub fn bonded(who: &T::AccountId) -> BalanceOf<T> {
+            Self::bonds(who).map(|bond| bond.active.saturating_add(bond.unbonding)).unwrap_or_else(Zero::zero)
+        }
+    }
+
+    impl<T: Config> BondedStake<T::AccountId, BalanceOf<T>> for Pallet<T> {
+        fn bonded(who: &T::AccountId) -> BalanceOf<T> {
+            Self::bonded(who)
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{mock::*, Bond, Error, Event, BOND_RESERVE_ID};
+use frame_support::{assert_noop, assert_ok, traits::{NamedReservableCurrency, ReservableCurrency}};
+use pallet_reward_vesting::RewardVesting as _;
+use sp_runtime::{traits::BadOrigin, Perbill};
+
+#[test]
+fn bonds_are_unbonded_and_withdrawn_after_the_unbonding_period() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(ContributorBonds::bond(RuntimeOrigin::signed(1), 0), Error::<Test>::ZeroBond);
+        assert_ok!(ContributorBonds::bond(RuntimeOrigin::signed(1), 100));
+        System::assert_last_event(Event::Bonded { who: 1, amount: 100 }.into());
+        assert_eq!(Balances::reserved_balance(1), 100);
+
+        assert_noop!(ContributorBonds::unbond(RuntimeOrigin::signed(2), 10), Error::<Test>::NotBonded);
+        assert_noop!(ContributorBonds::unbond(RuntimeOrigin::signed(1), 0), Error::<Test>::ZeroBond);
+        assert_noop!(ContributorBonds::unbond(RuntimeOrigin::signed(1), 101), Error::<Test>::InsufficientBond);
+        assert_ok!(ContributorBonds::unbond(RuntimeOrigin::signed(1), 40));
+        System::assert_last_event(Event::Unbonding { who: 1, amount: 40, unlock_at: 11 }.into());
+        assert_eq!(ContributorBonds::bonds(1), Some(Bond { active: 60, unbonding: 40, unlock_at: 11 }));
+        assert_eq!(ContributorBonds::bonded(&1), 100);
+
+        System::set_block_number(10);
+        assert_noop!(ContributorBonds::withdraw_unbonded(RuntimeOrigin::signed(1)), Error::<Test>::StillUnbonding);
+        System::set_block_number(11);
+        assert_ok!(ContributorBonds::withdraw_unbonded(RuntimeOrigin::signed(1)));
+        System::assert_last_event(Event::Withdrawn { who: 1, amount: 40 }.into());
+        assert_eq!(Balances::reserved_balance(1), 60);
+
+        // Withdrawing the last of the bond removes it
+        assert_ok!(ContributorBonds::unbond(RuntimeOrigin::signed(1), 60));
+        System::set_block_number(21);
+        assert_ok!(ContributorBonds::withdraw_unbonded(RuntimeOrigin::signed(1)));
+        assert_eq!(ContributorBonds::bonds(1), None);
+        assert_eq!(Balances::free_balance(1), 1_000);
+    });
+}
+
+#[test]
+fn slashing_takes_unbonding_stake_first_and_claws_back_rewards() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ContributorBonds::bond(RuntimeOrigin::signed(1), 100));
+        assert_ok!(ContributorBonds::unbond(RuntimeOrigin::signed(1), 30));
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 1_000));
+
+        System::set_block_number(51);
+        assert_noop!(
+            ContributorBonds::slash_contributor(RuntimeOrigin::signed(2), 1, Perbill::from_percent(50), vec![]),
+            BadOrigin
+        );
+        assert_noop!(
+            ContributorBonds::slash_contributor(RuntimeOrigin::root(), 1, Perbill::from_percent(50), vec![0; 17]),
+            Error::<Test>::CidTooLong
+        );
+        assert_ok!(ContributorBonds::slash_contributor(
+            RuntimeOrigin::root(),
+            1,
+            Perbill::from_percent(50),
+            b"QmEvidence".to_vec()
+        ));
+
+        // 30 from the unbonding stake, 20 from the active bond, and the 500 still locked
+        assert_eq!(ContributorBonds::bonds(1), Some(Bond { active: 50, unbonding: 0, unlock_at: 11 }));
+        assert_eq!(Balances::reserved_balance(1), 50);
+        assert_eq!(Balances::free_balance(TREASURY), 1_550);
+        let record = ContributorBonds::slashes(0).unwrap();
+        assert_eq!((record.who, record.bond_slashed, record.clawed_back, record.at), (1, 50, 500, 51));
+        System::assert_last_event(
+            Event::ContributorSlashed {
+                index: 0,
+                who: 1,
+                bond_slashed: 50,
+                clawed_back: 500,
+                evidence_cid: b"QmEvidence".to_vec().try_into().unwrap(),
+            }
+            .into(),
+        );
+        assert_eq!(ContributorBonds::next_slash_index(), 1);
+    });
+}
+
+#[test]
+fn bonds_only_lose_what_was_actually_slashed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ContributorBonds::bond(RuntimeOrigin::signed(1), 100));
+        // Another pallet took 70 of the reserve
+        let _ = Balances::slash_reserved(&1, 70);
+
+        assert_ok!(ContributorBonds::slash_contributor(RuntimeOrigin::root(), 1, Perbill::from_percent(50), vec![]));
+        assert_eq!(ContributorBonds::slashes(0).unwrap().bond_slashed, 30);
+        assert_eq!(ContributorBonds::bonds(1), Some(Bond { active: 70, unbonding: 0, unlock_at: 0 }));
+        assert_eq!(Balances::free_balance(TREASURY), 1_030);
+    });
+}
+
+#[test]
+fn slashing_leaves_other_reserves_alone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ContributorBonds::bond(RuntimeOrigin::signed(1), 100));
+        // Reserved by another pallet, e.g. as a deposit
+        assert_ok!(Balances::reserve(&1, 500));
+
+        assert_ok!(ContributorBonds::slash_contributor(RuntimeOrigin::root(), 1, Perbill::one(), vec![]));
+        assert_eq!(ContributorBonds::slashes(0).unwrap().bond_slashed, 100);
+        assert_eq!(ContributorBonds::bonds(1), None);
+        assert_eq!(Balances::reserved_balance(1), 500);
+        assert_eq!(Balances::reserved_balance_named(&BOND_RESERVE_ID, &1), 0);
+        assert_eq!(Balances::free_balance(TREASURY), 1_100);
+    });
+}
@@ -0,0 +1,162 @@
+//! Reward curves turning the upload volume of each eligible seeder into its share of the
+//! period's reward pool.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::ConstU32, BoundedVec, RuntimeDebug};
+use scale_info::TypeInfo;
+use sp_runtime::{
+    traits::{IntegerSquareRoot, Zero},
+    Perbill,
+};
+use sp_std::vec::Vec;
+
+/// Maximum number of tiers of a `RewardCurveKind::Tiered` curve.
+pub type MaxTiers = ConstU32<8>;
+
+/// Splits a reward pool between seeders according to their contributions.
+pub trait RewardCurve {
+    /// Share of `pool` for each entry of `contributions`, in the same order. The shares
+    /// never sum to more than `pool`; rounding leftovers stay in the pool.
+    fn shares(&self, contributions: &[u64], pool: u128) -> Vec<u128>;
+}
+
+/// Split `pool` proportionally to `weights`, rounding every share down.
+fn proportional(weights: &[u64], pool: u128) -> Vec<u128> {
+    // Summed in u128 so that no combination of weights can saturate
+    let total = weights.iter().map(|w| *w as u128).sum::<u128>();
+    weights
+        .iter()
+        .map(|w| if total == 0 { 0 } else { Perbill::from_rational(*w as u128, total).mul_floor(pool) })
+        .collect()
+}
+
+/// Shares proportional to the bytes uploaded.
+pub struct Linear;
+
+impl RewardCurve for Linear {
+    fn shares(&self, contributions: &[u64], pool: u128) -> Vec<u128> {
+        proportional(contributions, pool)
+    }
+}
+
+/// Shares proportional to the square root of the bytes uploaded, so a seeder uploading
+/// four times as much earns only twice as much.
+pub struct SquareRoot;
+
+impl RewardCurve for SquareRoot {
+    fn shares(&self, contributions: &[u64], pool: u128) -> Vec<u128> {
+        let weights = contributions.iter().map(|c| c.integer_sqrt()).collect::<Vec<_>>();
+        proportional(&weights, pool)
+    }
+}
+
+/// Linear shares, with no seeder receiving more than `max_share` of the pool. What capped
+/// seeders would have received above the cap is split linearly among the others.
+pub struct Capped {
+    pub max_share: Perbill,
+}
+
+impl RewardCurve for Capped {
+    fn shares(&self, contributions: &[u64], pool: u128) -> Vec<u128> {
+        let cap = self.max_share.mul_floor(pool);
+        let mut shares = sp_std::vec![0u128; contributions.len()];
+        let mut capped = sp_std::vec![false; contributions.len()];
+        let mut remaining = pool;
+
+        // Every round either caps one more seeder or settles the split
+        loop {
+            let weights = contributions
+                .iter()
+                .zip(capped.iter())
+                .map(|(c, capped)| if *capped { 0 } else { *c })
+                .collect::<Vec<_>>();
+            let round = proportional(&weights, remaining);
+            let over = round.iter().zip(capped.iter()).any(|(share, capped)| !capped && *share > cap);
+            if !over {
+                for (share, extra) in shares.iter_mut().zip(round) {
+                    *share = share.saturating_add(extra);
+                }
+                return shares;
+            }
+            for (i, share) in round.iter().enumerate() {
+                if !capped[i] && *share > cap {
+                    capped[i] = true;
+                    shares[i] = cap;
+                    remaining = remaining.saturating_sub(cap);
+                }
+            }
+        }
+    }
+}
+
+/// Contribution level of a `Tiered` curve.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Tier {
+    /// Bytes uploaded from which the tier applies.
+    pub min_bytes: u64,
+    /// Reward weight of every seeder in the tier.
+    pub weight: u32,
+}
+
+/// Shares proportional to the weight of the highest tier each seeder reaches. Seeders below
+/// the first tier get nothing.
+pub struct Tiered<'a> {
+    /// Tiers by ascending `min_bytes`.
+    pub tiers: &'a [Tier],
+}
+
+impl RewardCurve for Tiered<'_> {
+    fn shares(&self, contributions: &[u64], pool: u128) -> Vec<u128> {
+        let weights = contributions
+            .iter()
+            .map(|c| {
+                self.tiers
+                    .iter()
+                    .take_while(|tier| tier.min_bytes <= *c)
+                    .last()
+                    .map_or(0, |tier| tier.weight as u64)
+            })
+            .collect::<Vec<_>>();
+        proportional(&weights, pool)
+    }
+}
+
+/// Reward curve selected by governance.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum RewardCurveKind {
+    Linear,
+    SquareRoot,
+    Capped { max_share: Perbill },
+    Tiered { tiers: BoundedVec<Tier, MaxTiers> },
+}
+
+impl Default for RewardCurveKind {
+    fn default() -> Self {
+        RewardCurveKind::Linear
+    }
+}
+
+impl RewardCurveKind {
+    /// Whether the curve can distribute anything: a nonzero cap, and tiers sorted by
+    /// strictly ascending `min_bytes`.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            RewardCurveKind::Capped { max_share } => !max_share.is_zero(),
+            RewardCurveKind::Tiered { tiers } =>
+                !tiers.is_empty() && tiers.windows(2).all(|pair| pair[0].min_bytes < pair[1].min_bytes),
+            _ => true,
+        }
+    }
+}
+
+impl RewardCurve for RewardCurveKind {
+    fn shares(&self, contributions: &[u64], pool: u128) -> Vec<u128> {
+        match self {
+            RewardCurveKind::Linear => Linear.shares(contributions, pool),
+            RewardCurveKind::SquareRoot => SquareRoot.shares(contributions, pool),
+            RewardCurveKind::Capped { max_share } =>
+                Capped { max_share: *max_share }.shares(contributions, pool),
+            RewardCurveKind::Tiered { tiers } => Tiered { tiers }.shares(contributions, pool),
+        }
+    }
+}
@@ -2,15 +2,31 @@
 
 pub use pallet::*;
 
+pub mod curve;
+
+#[cfg(test)]
+mod tests;
+
 #[frame_support::pallet]
 pub mod pallet {
     use frame_support::{
         pallet_prelude::*,
-        traits::{Currency, ExistenceRequirement, Get},
+        storage::with_storage_layer,
+        traits::{Contains, Currency, ExistenceRequirement, Get},
+    };
+    use frame_system::{
+        offchain::{AppCrypto, CreateSignedTransaction, SendUnsignedTransaction, SignedPayload, SigningTypes},
+        pallet_prelude::*,
+    };
+    use sp_runtime::{
+        traits::{IdentifyAccount, Saturating, UniqueSaturatedInto, Verify},
+        Perbill,
     };
-    use frame_system::pallet_prelude::*;
-    use sp_runtime::Perbill;
     use sp_std::vec::Vec;
+    use pallet_contributor_bonds::BondedStake;
+    use pallet_reputation::{ReputationEvent, ReputationHandler, ReputationProvider};
+    use pallet_reward_vesting::RewardVesting;
+    use crate::curve::{RewardCurve, RewardCurveKind};
 
     type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
@@ -22,131 +38,461 @@ pub mod pallet {
         pub timestamp: BlockNumber,
     }
 
+    /// Confirmation by a downloader that `seeder` served them `bytes` of the chunk with hash
+    /// `chunk_hash` during reward period `period`. This is the payload the downloader signs.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct BandwidthReceipt<AccountId> {
+        pub seeder: AccountId,
+        pub chunk_hash: [u8; 32],
+        pub bytes: u64,
+        pub period: u32,
+    }
+
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct SignedReceipt<AccountId, Signer, Signature> {
+        pub receipt: BandwidthReceipt<AccountId>,
+        pub downloader: Signer,
+        pub signature: Signature,
+    }
+
+    pub type SignedReceiptOf<T> = SignedReceipt<
+        <T as frame_system::Config>::AccountId,
+        <T as Config>::ReceiptSigner,
+        <T as Config>::ReceiptSignature,
+    >;
+
+    /// Payload an offchain worker signs to trigger the distribution of `period`.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct DistributionPayload<Public> {
+        pub period: u32,
+        pub public: Public,
+    }
+
+    impl<T: SigningTypes> SignedPayload<T> for DistributionPayload<T::Public> {
+        fn public(&self) -> T::Public {
+            self.public.clone()
+        }
+    }
+
     #[pallet::config]
-    pub trait Config: frame_system::Config {
+    pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
         type Currency: Currency<Self::AccountId>;
         #[pallet::constant]
         type RewardPoolAccount: Get<Self::AccountId>;
+        /// Portion of every reward locked in a vesting schedule instead of paid out liquid.
         #[pallet::constant]
+        type VestedPortion: Get<Perbill>;
+        type Vesting: RewardVesting<Self::AccountId, BalanceOf<Self>>;
+        /// May be read from storage, e.g. `pallet_parameters::TotalRewardPerPeriod<Runtime>`.
         type TotalRewardPerPeriod: Get<BalanceOf<Self>>;
-        #[pallet::constant]
+        /// May be read from storage, e.g. `pallet_parameters::MinBytesUploaded<Runtime>`.
         type MinBytesUploaded: Get<u64>;
+        /// Public key downloaders sign receipts with.
+        type ReceiptSigner: IdentifyAccount<AccountId = Self::AccountId> + Parameter;
+        type ReceiptSignature: Verify<Signer = Self::ReceiptSigner> + Parameter;
+        /// Stake of the downloaders signing receipts. Only receipts of downloaders with at
+        /// least `MinDownloaderBond` bonded count, so receipts cannot be minted from
+        /// throwaway keys.
+        type Bonds: BondedStake<Self::AccountId, BalanceOf<Self>>;
+        #[pallet::constant]
+        type MinDownloaderBond: Get<BalanceOf<Self>>;
+        /// Maximum bytes a single downloader can confirm per period, across all seeders. Bounds
+        /// what one bonded key colluding with a seeder can add to the seeder's report.
+        #[pallet::constant]
+        type MaxBytesPerDownloader: Get<u64>;
+        /// Maximum number of receipts verified in one `submit_receipts` call.
+        #[pallet::constant]
+        type MaxReceiptsPerBatch: Get<u32>;
         #[pallet::constant]
         type RewardPeriod: Get<Self::BlockNumber>;
+        /// Blocks after the end of a period during which receipts for it are still accepted.
+        /// The period is distributed once this window has closed.
+        #[pallet::constant]
+        type ReportGracePeriod: Get<Self::BlockNumber>;
+        /// Number of distributed periods whose reports are kept before being pruned.
+        #[pallet::constant]
+        type ReportHistoryDepth: Get<u32>;
+        /// Maximum number of accounts with a report in one period. Bounds the weight of a
+        /// distribution.
+        #[pallet::constant]
+        type MaxReportsPerPeriod: Get<u32>;
+        /// Maximum number of reports and receipts removed per block when pruning expired
+        /// periods.
+        #[pallet::constant]
+        type MaxPrunedPerBlock: Get<u32>;
+        /// Key type offchain workers sign distribution payloads with.
+        type AuthorityId: AppCrypto<Self::Public, <Self as SigningTypes>::Signature>;
+        /// Offchain-worker keys allowed to trigger distributions.
+        type Workers: Contains<Self::Public>;
+        /// Priority of unsigned distribution transactions.
+        #[pallet::constant]
+        type UnsignedPriority: Get<TransactionPriority>;
+        /// Origin allowed to change the reward curve.
+        type CurveOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Scales each user's reward by their reputation and records reliable seeding, or
+        /// unreliable seeding for seeders below `MinBytesUploaded`.
+        type Reputation: ReputationProvider<Self::AccountId> + ReputationHandler<Self::AccountId>;
     }
 
+    /// Verified traffic by period and account.
     #[pallet::storage]
     #[pallet::getter(fn reports)]
-    pub type Reports<T: Config> = StorageMap<
-        _, 
-        Blake2_128Concat, 
-        T::AccountId, 
-        SeedingReport<T::BlockNumber>, 
+    pub type Reports<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        u32,
+        Blake2_128Concat,
+        T::AccountId,
+        SeedingReport<T::BlockNumber>,
+        OptionQuery
+    >;
+
+    /// Number of reports by period.
+    #[pallet::storage]
+    pub type ReportCount<T: Config> = StorageMap<_, Twox64Concat, u32, u32, ValueQuery>;
+
+    /// Receipts already counted, by period and receipt hash.
+    #[pallet::storage]
+    pub type UsedReceipts<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        u32,
+        Identity,
+        [u8; 32],
+        (),
         OptionQuery
     >;
 
+    /// Oldest period whose rewards have not been distributed yet.
+    #[pallet::storage]
+    #[pallet::getter(fn next_period_to_distribute)]
+    pub type NextPeriodToDistribute<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Oldest period whose reports and receipts have not been pruned yet.
     #[pallet::storage]
-    #[pallet::getter(fn last_reward_block)]
-    pub type LastRewardBlock<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+    #[pallet::getter(fn next_period_to_prune)]
+    pub type NextPeriodToPrune<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Curve splitting each period's rewards between eligible seeders.
+    #[pallet::storage]
+    #[pallet::getter(fn reward_curve)]
+    pub type ActiveRewardCurve<T: Config> = StorageValue<_, RewardCurveKind, ValueQuery>;
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
-        ReportSubmitted { user: T::AccountId, uploaded: u64, downloaded: u64 },
-        NetworkRewardDistributed { user: T::AccountId, amount: BalanceOf<T> },
+        ReceiptsVerified { seeder: T::AccountId, receipts: u32, bytes: u64 },
+        NetworkRewardDistributed { user: T::AccountId, amount: BalanceOf<T>, vested: BalanceOf<T> },
+        /// Paying `user` failed; the reward stays in the pool.
+        NetworkRewardFailed { user: T::AccountId, amount: BalanceOf<T>, error: DispatchError },
+        PeriodDistributed { period: u32, users: u32 },
+        PeriodPruned { period: u32 },
+        RewardCurveChanged { curve: RewardCurveKind },
     }
 
     #[pallet::error]
     pub enum Error<T> {
-        AlreadyReportedThisPeriod,
-        NotEnoughContribution,
-        RewardPoolEmpty,
+        InvalidReceiptSignature,
+        ReceiptForAnotherSeeder,
+        ReceiptPeriodMismatch,
+        /// The grace window of the receipt's period has closed.
+        ReceiptPeriodClosed,
+        /// The oldest undistributed period is still open or in its grace window.
+        PeriodNotClosed,
+        /// The signed payload is not for the next period to distribute.
+        WrongDistributionPeriod,
+        /// Capped curve with a zero cap, or tiers not sorted by ascending `min_bytes`.
+        InvalidRewardCurve,
+        SelfSignedReceipt,
+        DuplicateReceipt,
+        /// The downloader has less than `MinDownloaderBond` bonded.
+        DownloaderNotBonded,
+        /// The receipt would take the downloader above `MaxBytesPerDownloader` for the period.
+        DownloaderQuotaExceeded,
+        /// The period already has `MaxReportsPerPeriod` reports.
+        TooManyReports,
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Users submit their seeding report for the current period.
-        #[pallet::weight(10_000)]
-        pub fn submit_report(
+        /// Seeders submit receipts signed by the peers they served. Receipts count towards
+        /// the report of the period they were issued for, which must be the current period
+        /// or a past one still in its grace window. Downloaders must be bonded and stay within
+        /// `MaxBytesPerDownloader` per period. The batch is rejected as a whole if any
+        /// receipt is invalid.
+        #[pallet::weight(10_000u64.saturating_add(50_000u64.saturating_mul(receipts.len() as u64)))]
+        pub fn submit_receipts(
             origin: OriginFor<T>,
-            bytes_uploaded: u64,
-            bytes_downloaded: u64,
+            receipts: BoundedVec<SignedReceiptOf<T>, T::MaxReceiptsPerBatch>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            ensure!(bytes_uploaded >= T::MinBytesUploaded::get(), Error::<T>::NotEnoughContribution);
+            let current = Self::current_period();
 
-            // Only one report per user per period
-            ensure!(!Reports::<T>::contains_key(&who), Error::<T>::AlreadyReportedThisPeriod);
+            let mut bytes: u64 = 0;
+            for signed in receipts.iter() {
+                let receipt = &signed.receipt;
+                let period = receipt.period;
+                ensure!(receipt.seeder == who, Error::<T>::ReceiptForAnotherSeeder);
+                ensure!(period <= current, Error::<T>::ReceiptPeriodMismatch);
+                ensure!(Self::accepts_receipts(period), Error::<T>::ReceiptPeriodClosed);
+                let downloader = signed.downloader.clone().into_account();
+                ensure!(downloader != who, Error::<T>::SelfSignedReceipt);
+                ensure!(
+                    receipt.using_encoded(|payload| signed.signature.verify(payload, &downloader)),
+                    Error::<T>::InvalidReceiptSignature
+                );
+                ensure!(
+                    T::Bonds::bonded(&downloader) >= T::MinDownloaderBond::get(),
+                    Error::<T>::DownloaderNotBonded
+                );
+                let downloaded = Self::reports(period, &downloader).map_or(0, |report| report.bytes_downloaded);
+                ensure!(
+                    downloaded.saturating_add(receipt.bytes) <= T::MaxBytesPerDownloader::get(),
+                    Error::<T>::DownloaderQuotaExceeded
+                );
 
-            let now = <frame_system::Pallet<T>>::block_number();
-            Reports::<T>::insert(
-                &who,
-                SeedingReport {
-                    bytes_uploaded,
-                    bytes_downloaded,
-                    timestamp: now,
-                },
-            );
-            Self::deposit_event(Event::ReportSubmitted { user: who, uploaded: bytes_uploaded, downloaded: bytes_downloaded });
+                let receipt_hash = receipt.using_encoded(sp_io::hashing::blake2_256);
+                ensure!(!UsedReceipts::<T>::contains_key(period, receipt_hash), Error::<T>::DuplicateReceipt);
+                UsedReceipts::<T>::insert(period, receipt_hash, ());
+
+                Self::record_bytes(period, &who, receipt.bytes, 0)?;
+                Self::record_bytes(period, &downloader, 0, receipt.bytes)?;
+                bytes = bytes.saturating_add(receipt.bytes);
+            }
+
+            Self::deposit_event(Event::ReceiptsVerified { seeder: who, receipts: receipts.len() as u32, bytes });
             Ok(())
         }
 
-        /// Distribute network rewards to eligible users (root or off-chain worker only)
-        #[pallet::weight(10_000)]
+        /// Distribute the rewards of the oldest undistributed period once its grace window
+        /// has closed. Reports that fall out of `ReportHistoryDepth` are pruned in
+        /// `on_initialize`.
+        #[pallet::weight(Pallet::<T>::distribution_weight())]
         pub fn distribute_network_rewards(origin: OriginFor<T>) -> DispatchResult {
-            ensure_none(origin.clone()).or_else(|_| ensure_root(origin))?;
+            ensure_root(origin)?;
+            Self::do_distribute(Self::next_period_to_distribute())
+        }
+
+        /// Same as `distribute_network_rewards`, submitted by an offchain worker as an
+        /// unsigned transaction carrying a payload signed by an authorized worker key.
+        /// The signature is checked in `validate_unsigned`.
+        #[pallet::weight(Pallet::<T>::distribution_weight())]
+        pub fn distribute_network_rewards_unsigned(
+            origin: OriginFor<T>,
+            payload: DistributionPayload<T::Public>,
+            _signature: <T as SigningTypes>::Signature,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            ensure!(payload.period == Self::next_period_to_distribute(), Error::<T>::WrongDistributionPeriod);
+            Self::do_distribute(payload.period)
+        }
 
+        /// Select the curve used to split the rewards of the next distributed periods.
+        #[pallet::weight(10_000)]
+        pub fn set_reward_curve(origin: OriginFor<T>, curve: RewardCurveKind) -> DispatchResult {
+            T::CurveOrigin::ensure_origin(origin)?;
+            ensure!(curve.is_valid(), Error::<T>::InvalidRewardCurve);
+
+            ActiveRewardCurve::<T>::put(curve.clone());
+            Self::deposit_event(Event::RewardCurveChanged { curve });
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Index of the reward period containing the current block.
+        pub fn current_period() -> u32 {
             let now = <frame_system::Pallet<T>>::block_number();
-            if let Some(last) = Self::last_reward_block() {
-                ensure!(now > last, Error::<T>::AlreadyReportedThisPeriod);
+            (now / T::RewardPeriod::get()).unique_saturated_into()
+        }
+
+        /// First block after the grace window of `period`.
+        fn grace_end(period: u32) -> T::BlockNumber {
+            T::RewardPeriod::get()
+                .saturating_mul(period.saturating_add(1).into())
+                .saturating_add(T::ReportGracePeriod::get())
+        }
+
+        /// Whether receipts for `period` can no longer be submitted.
+        pub fn is_closed(period: u32) -> bool {
+            <frame_system::Pallet<T>>::block_number() >= Self::grace_end(period)
+        }
+
+        /// Whether receipts for the past or current `period` are still accepted.
+        fn accepts_receipts(period: u32) -> bool {
+            period >= Self::next_period_to_distribute() && !Self::is_closed(period)
+        }
+
+        /// Add verified traffic to the report of `who` for `period`, opening the report if
+        /// `who` has none yet and the period has room for it.
+        fn record_bytes(period: u32, who: &T::AccountId, uploaded: u64, downloaded: u64) -> DispatchResult {
+            let now = <frame_system::Pallet<T>>::block_number();
+            Reports::<T>::try_mutate(period, who, |report| -> DispatchResult {
+                if report.is_none() {
+                    ReportCount::<T>::try_mutate(period, |count| -> DispatchResult {
+                        ensure!(*count < T::MaxReportsPerPeriod::get(), Error::<T>::TooManyReports);
+                        *count += 1;
+                        Ok(())
+                    })?;
+                }
+                let report = report.get_or_insert(SeedingReport { bytes_uploaded: 0, bytes_downloaded: 0, timestamp: now });
+                report.bytes_uploaded = report.bytes_uploaded.saturating_add(uploaded);
+                report.bytes_downloaded = report.bytes_downloaded.saturating_add(downloaded);
+                report.timestamp = now;
+                Ok(())
+            })
+        }
+
+        /// Upper bound of the weight of distributing a period with `MaxReportsPerPeriod`
+        /// reports: per report, reading it and the reputation and paying the reward into
+        /// the account and its vesting schedules.
+        pub fn distribution_weight() -> Weight {
+            let reports = u64::from(T::MaxReportsPerPeriod::get());
+            T::DbWeight::get()
+                .reads_writes(2u64.saturating_add(reports.saturating_mul(6)), 2u64.saturating_add(reports.saturating_mul(5)))
+                .saturating_add(Weight::from_parts(10_000u64.saturating_add(reports.saturating_mul(50_000)), 0))
+        }
+
+        /// Pay the rewards of `period`, which must be closed.
+        fn do_distribute(period: u32) -> DispatchResult {
+            ensure!(Self::is_closed(period), Error::<T>::PeriodNotClosed);
+
+            let min_bytes = T::MinBytesUploaded::get();
+            let mut eligible_users: Vec<T::AccountId> = Vec::new();
+            let mut uploads: Vec<u64> = Vec::new();
+            for (user, report) in Reports::<T>::iter_prefix(period) {
+                if report.bytes_uploaded >= min_bytes {
+                    eligible_users.push(user);
+                    uploads.push(report.bytes_uploaded);
+                } else if report.bytes_uploaded > 0 {
+                    // Seeded during the period, but too little to earn a reward
+                    T::Reputation::note(&user, ReputationEvent::SeedingUnreliable);
+                }
             }
 
-            // Aggregate total uploaded bytes
-            let mut total_uploaded: u64 = 0;
-            let mut eligible_users: Vec<(T::AccountId, u64)> = Vec::new();
+            let total_reward: u128 = T::TotalRewardPerPeriod::get().unique_saturated_into();
+            let shares = Self::reward_curve().shares(&uploads, total_reward);
+            let users = eligible_users.len() as u32;
 
-            for (user, report) in Reports::<T>::iter() {
-                if report.bytes_uploaded >= T::MinBytesUploaded::get() {
-                    total_uploaded += report.bytes_uploaded;
-                    eligible_users.push((user, report.bytes_uploaded));
+            for (user, share) in eligible_users.into_iter().zip(shares) {
+                let share: BalanceOf<T> = share.unique_saturated_into();
+                let reward = T::Reputation::reward_multiplier(&user) * share;
+                // A failed payout must not hold back the other users or the next periods
+                match with_storage_layer(|| Self::pay_reward(&user, reward)) {
+                    Ok(vested) => {
+                        T::Reputation::note(&user, ReputationEvent::SeedingReliable);
+                        Self::deposit_event(Event::NetworkRewardDistributed { user, amount: reward, vested });
+                    }
+                    Err(error) =>
+                        Self::deposit_event(Event::NetworkRewardFailed { user, amount: reward, error }),
                 }
             }
 
-            if total_uploaded == 0 {
-                return Ok(()); // No eligible users
+            NextPeriodToDistribute::<T>::put(period.saturating_add(1));
+            Self::deposit_event(Event::PeriodDistributed { period, users });
+            Ok(())
+        }
+
+        /// Remove the reports and receipts of periods that fell out of `ReportHistoryDepth`,
+        /// oldest first, visiting at most `limit` entries or periods.
+        fn prune_periods(limit: u32) -> Weight {
+            let db_weight = T::DbWeight::get();
+            let end = Self::next_period_to_distribute().saturating_sub(T::ReportHistoryDepth::get());
+            let mut period = Self::next_period_to_prune();
+            let mut budget = limit;
+            let mut weight = db_weight.reads(3);
+
+            while period < end && budget > 0 {
+                let receipts = UsedReceipts::<T>::clear_prefix(period, budget, None);
+                budget = budget.saturating_sub(receipts.backend);
+                weight = weight.saturating_add(db_weight.reads_writes(1, receipts.backend.into()));
+                if receipts.maybe_cursor.is_some() || budget == 0 {
+                    break;
+                }
+                let reports = Reports::<T>::clear_prefix(period, budget, None);
+                budget = budget.saturating_sub(reports.backend);
+                weight = weight.saturating_add(db_weight.reads_writes(1, reports.backend.into()));
+                if reports.maybe_cursor.is_some() {
+                    break;
+                }
+                ReportCount::<T>::remove(period);
+                Self::deposit_event(Event::PeriodPruned { period });
+                period = period.saturating_add(1);
+                // Every pruned period counts, so runs of empty periods are bounded too
+                budget = budget.saturating_sub(1);
             }
 
-            let total_reward = T::TotalRewardPerPeriod::get();
-
-            for (user, uploaded) in eligible_users {
-                let share = Perbill::from_rational(uploaded, total_uploaded);
-                let reward = share * total_reward;
-                T::Currency::transfer(
-                    &T::RewardPoolAccount::get(),
-                    &user,
-                    reward,
-                    ExistenceRequirement::KeepAlive,
-                )?;
-                Self::deposit_event(Event::NetworkRewardDistributed { user, amount: reward });
+            NextPeriodToPrune::<T>::put(period);
+            weight.saturating_add(db_weight.writes(2))
+        }
+
+        /// Checks an offchain worker's distribution payload before it enters the pool.
+        fn validate_distribution(
+            payload: &DistributionPayload<T::Public>,
+            signature: &<T as SigningTypes>::Signature,
+        ) -> TransactionValidity {
+            if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+                return InvalidTransaction::BadProof.into();
+            }
+            if !T::Workers::contains(&payload.public) {
+                return InvalidTransaction::BadSigner.into();
+            }
+            let next = Self::next_period_to_distribute();
+            if payload.period < next {
+                return InvalidTransaction::Stale.into();
+            }
+            if payload.period > next || !Self::is_closed(payload.period) {
+                return InvalidTransaction::Future.into();
             }
 
-            // Clear reports for next period
-            Reports::<T>::remove_all(None);
-            LastRewardBlock::<T>::put(now);
-            Ok(())
+            ValidTransaction::with_tag_prefix("NetworkRewardsDistribution")
+                .priority(T::UnsignedPriority::get())
+                .and_provides(payload.period)
+                .longevity(T::RewardPeriod::get().unique_saturated_into())
+                .propagate(true)
+                .build()
+        }
+
+        /// Pay `amount` out of the reward pool, routing `VestedPortion` of it into vesting.
+        fn pay_reward(user: &T::AccountId, amount: BalanceOf<T>) -> Result<BalanceOf<T>, DispatchError> {
+            let pool = T::RewardPoolAccount::get();
+            let vested = T::VestedPortion::get() * amount;
+            T::Currency::transfer(&pool, user, amount.saturating_sub(vested), ExistenceRequirement::KeepAlive)?;
+            T::Vesting::vest(&pool, user, vested)?;
+            Ok(vested)
         }
     }
 
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-        fn offchain_worker(now: T::BlockNumber) {
-            if (now % T::RewardPeriod::get()).is_zero() {
-                if let Some(last) = Self::last_reward_block() {
-                    if now <= last { return; }
-                }
-                let call = Call::<T>::distribute_network_rewards {};
-                let _ = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into());
+        fn on_initialize(_now: T::BlockNumber) -> Weight {
+            Self::prune_periods(T::MaxPrunedPerBlock::get())
+        }
+
+        fn offchain_worker(_now: T::BlockNumber) {
+            // Distribute every period whose grace window has closed, one per block. The
+            // pool deduplicates submissions of several workers by period.
+            let period = Self::next_period_to_distribute();
+            if !Self::is_closed(period) {
+                return;
+            }
+            let _ = frame_system::offchain::Signer::<T, T::AuthorityId>::any_account().send_unsigned_transaction(
+                |account| DistributionPayload { period, public: account.public.clone() },
+                |payload, signature| Call::distribute_network_rewards_unsigned { payload, signature },
+            );
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            match call {
+                Call::distribute_network_rewards_unsigned { payload, signature } =>
+                    Self::validate_distribution(payload, signature),
+                _ => InvalidTransaction::Call.into(),
             }
         }
     }
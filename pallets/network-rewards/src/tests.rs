@@ -0,0 +1,150 @@
+use crate::curve::*;
+use frame_support::BoundedVec;
+use sp_runtime::Perbill;
+
+/// Small xorshift generator so the properties are checked on the same inputs every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    /// Contribution lists mixing small home seeders with a few very large ones.
+    fn contributions(&mut self) -> Vec<u64> {
+        let len = self.below(20) as usize;
+        (0..len)
+            .map(|_| match self.below(4) {
+                0 => 0,
+                1 => self.next(),
+                _ => self.below(1 << 30),
+            })
+            .collect()
+    }
+
+    fn pool(&mut self) -> u128 {
+        match self.below(3) {
+            0 => self.below(1_000) as u128,
+            1 => self.next() as u128,
+            _ => (self.next() as u128) << 64 | self.next() as u128,
+        }
+    }
+}
+
+fn tiered() -> RewardCurveKind {
+    let tiers = vec![
+        Tier { min_bytes: 1 << 20, weight: 1 },
+        Tier { min_bytes: 1 << 30, weight: 2 },
+        Tier { min_bytes: 1 << 40, weight: 5 },
+    ];
+    RewardCurveKind::Tiered { tiers: BoundedVec::try_from(tiers).unwrap() }
+}
+
+fn all_curves() -> Vec<RewardCurveKind> {
+    vec![
+        RewardCurveKind::Linear,
+        RewardCurveKind::SquareRoot,
+        RewardCurveKind::Capped { max_share: Perbill::from_percent(10) },
+        RewardCurveKind::Capped { max_share: Perbill::from_percent(50) },
+        tiered(),
+    ]
+}
+
+#[test]
+fn shares_never_exceed_pool() {
+    let mut rng = Rng(0x5eed);
+    for curve in all_curves() {
+        for _ in 0..2_000 {
+            let contributions = rng.contributions();
+            let pool = rng.pool();
+            let shares = curve.shares(&contributions, pool);
+            assert_eq!(shares.len(), contributions.len());
+            let total = shares.iter().fold(0u128, |acc, s| acc.checked_add(*s).unwrap());
+            assert!(total <= pool, "{:?} paid {} out of {}", curve, total, pool);
+        }
+    }
+}
+
+#[test]
+fn zero_contributions_earn_nothing() {
+    let mut rng = Rng(7);
+    for curve in all_curves() {
+        for _ in 0..500 {
+            let contributions = rng.contributions();
+            let shares = curve.shares(&contributions, rng.pool());
+            for (contribution, share) in contributions.iter().zip(shares) {
+                if *contribution == 0 {
+                    assert_eq!(share, 0);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn larger_contributions_never_earn_less() {
+    let mut rng = Rng(42);
+    for curve in all_curves() {
+        for _ in 0..500 {
+            let contributions = rng.contributions();
+            let shares = curve.shares(&contributions, rng.pool());
+            for (i, a) in contributions.iter().enumerate() {
+                for (j, b) in contributions.iter().enumerate() {
+                    if a > b {
+                        assert!(shares[i] >= shares[j], "{:?} on {:?}", curve, contributions);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn capped_shares_stay_below_cap() {
+    let mut rng = Rng(99);
+    let max_share = Perbill::from_percent(10);
+    let curve = RewardCurveKind::Capped { max_share };
+    for _ in 0..2_000 {
+        let pool = rng.pool();
+        for share in curve.shares(&rng.contributions(), pool) {
+            assert!(share <= max_share.mul_floor(pool));
+        }
+    }
+}
+
+#[test]
+fn capped_redistributes_excess() {
+    let curve = RewardCurveKind::Capped { max_share: Perbill::from_percent(40) };
+    // Linear would pay 900 / 50 / 50
+    assert_eq!(curve.shares(&[900, 50, 50], 1_000), vec![400, 300, 300]);
+    // Nobody above the cap: same as linear
+    assert_eq!(curve.shares(&[30, 30, 40], 1_000), RewardCurveKind::Linear.shares(&[30, 30, 40], 1_000));
+}
+
+#[test]
+fn square_root_dampens_large_seeders() {
+    assert_eq!(RewardCurveKind::Linear.shares(&[400, 100], 1_000), vec![800, 200]);
+    assert_eq!(RewardCurveKind::SquareRoot.shares(&[400, 100], 1_000), vec![666, 333]);
+}
+
+#[test]
+fn tiered_pays_by_highest_tier_reached() {
+    let shares = tiered().shares(&[1 << 10, 1 << 20, (1 << 30) + 1, 1 << 50], 800);
+    assert_eq!(shares, vec![0, 100, 200, 500]);
+}
+
+#[test]
+fn invalid_curves_are_rejected() {
+    assert!(!RewardCurveKind::Capped { max_share: Perbill::zero() }.is_valid());
+    assert!(!RewardCurveKind::Tiered { tiers: BoundedVec::default() }.is_valid());
+    let unsorted = vec![Tier { min_bytes: 10, weight: 2 }, Tier { min_bytes: 10, weight: 1 }];
+    assert!(!RewardCurveKind::Tiered { tiers: BoundedVec::try_from(unsorted).unwrap() }.is_valid());
+    assert!(tiered().is_valid());
+}
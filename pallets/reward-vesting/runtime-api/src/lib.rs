@@ -0,0 +1,19 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_reward_vesting::VestingSchedule;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait RewardVestingApi<AccountId, Balance, BlockNumber> where
+        AccountId: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+    {
+        /// Amount `who` can withdraw with `vested_claim` right now.
+        fn claimable(who: AccountId) -> Balance;
+        /// Outstanding vesting schedules of `who`, each unlocking
+        /// `unlocked_at_start` at `start` and the rest linearly until `end`.
+        fn unlock_timeline(who: AccountId) -> Vec<VestingSchedule<Balance, BlockNumber>>;
+    }
+}
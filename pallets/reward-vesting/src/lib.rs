@@ -0,0 +1,249 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Routes part of a reward payout into a vesting schedule instead of the recipient's free balance.
+pub trait RewardVesting<AccountId, Balance> {
+    /// Move `amount` from `source` into a new linear vesting schedule for `who`.
+    fn vest(source: &AccountId, who: &AccountId, amount: Balance) -> frame_support::dispatch::DispatchResult;
+}
+
+/// Recovers rewards that have not vested yet, e.g. after a contribution turned out to be fraudulent.
+pub trait RewardClawback<AccountId, Balance> {
+    /// Cancel the unvested part of every schedule of `who` and transfer it to `dest`.
+    /// Returns the amount recovered.
+    fn clawback(who: &AccountId, dest: &AccountId) -> Result<Balance, frame_support::dispatch::DispatchError>;
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::{RewardClawback, RewardVesting};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{Currency, ExistenceRequirement, Get},
+        PalletId,
+    };
+    use frame_system::pallet_prelude::*;
+    use sp_runtime::traits::{AccountIdConversion, Saturating, UniqueSaturatedInto, Zero};
+    use sp_std::vec::Vec;
+
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// Reward amount of which `unlocked_at_start` is unlocked at `start` and the rest unlocks
+    /// linearly until `end`.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct VestingSchedule<Balance, BlockNumber> {
+        pub total: Balance,
+        pub claimed: Balance,
+        /// Part of `total` that had already unlocked when the schedule last absorbed a reward.
+        pub unlocked_at_start: Balance,
+        pub start: BlockNumber,
+        pub end: BlockNumber,
+    }
+
+    impl<Balance, BlockNumber> VestingSchedule<Balance, BlockNumber>
+    where
+        Balance: sp_runtime::traits::AtLeast32BitUnsigned + Copy,
+        BlockNumber: sp_runtime::traits::AtLeast32BitUnsigned + Copy,
+    {
+        /// Amount unlocked at block `now`, claimed or not.
+        pub fn unlocked_at(&self, now: BlockNumber) -> Balance {
+            if now >= self.end {
+                return self.total;
+            }
+            if now < self.start {
+                return Zero::zero();
+            }
+            let elapsed: u128 = (now - self.start).unique_saturated_into();
+            let duration: u128 = (self.end - self.start).unique_saturated_into();
+            let locked: u128 = self.total.saturating_sub(self.unlocked_at_start).unique_saturated_into();
+            let unlocked = sp_runtime::helpers_128bit::multiply_by_rational_with_rounding(
+                locked,
+                elapsed,
+                duration,
+                sp_runtime::Rounding::Down,
+            )
+            .unwrap_or(0);
+            self.unlocked_at_start.saturating_add(unlocked.unique_saturated_into())
+        }
+
+        /// Amount that can be claimed at block `now`.
+        pub fn claimable_at(&self, now: BlockNumber) -> Balance {
+            self.unlocked_at(now).saturating_sub(self.claimed)
+        }
+
+        /// Merge `amount`, vesting over `period` from `now`, into this schedule the way
+        /// `pallet_vesting` merges schedules. Whatever has unlocked by `now` stays unlocked,
+        /// and the amount still locked plus `amount` unlocks linearly from `now` until the
+        /// later of the current end and `now + period`, so `amount` never unlocks faster than
+        /// it would in a schedule of its own.
+        pub fn merge(&mut self, amount: Balance, now: BlockNumber, period: BlockNumber) {
+            self.unlocked_at_start = self.unlocked_at(now);
+            self.total = self.total.saturating_add(amount);
+            self.start = now;
+            self.end = self.end.max(now.saturating_add(period));
+        }
+    }
+
+    pub type ScheduleOf<T> = VestingSchedule<BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        type Currency: Currency<Self::AccountId>;
+        /// Holds the locked rewards until they are claimed.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+        /// Number of blocks over which a vested reward unlocks.
+        #[pallet::constant]
+        type VestingPeriod: Get<Self::BlockNumber>;
+        /// Maximum number of schedules per account. Further rewards are merged into the newest
+        /// one, see [`VestingSchedule::merge`].
+        #[pallet::constant]
+        type MaxSchedules: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::storage]
+    #[pallet::getter(fn schedules)]
+    pub type Schedules<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<ScheduleOf<T>, T::MaxSchedules>,
+        ValueQuery
+    >;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        RewardVested { user: T::AccountId, amount: BalanceOf<T>, end: T::BlockNumber },
+        VestedClaimed { user: T::AccountId, amount: BalanceOf<T> },
+        RewardClawedBack { user: T::AccountId, amount: BalanceOf<T> },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        NothingToClaim,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Transfer every unlocked reward of the caller to their free balance.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3).ref_time())]
+        pub fn vested_claim(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let now = <frame_system::Pallet<T>>::block_number();
+
+            let mut schedules = Schedules::<T>::get(&who);
+            let mut amount: BalanceOf<T> = Zero::zero();
+            for schedule in schedules.iter_mut() {
+                let claimable = schedule.claimable_at(now);
+                schedule.claimed = schedule.claimed.saturating_add(claimable);
+                amount = amount.saturating_add(claimable);
+            }
+            ensure!(!amount.is_zero(), Error::<T>::NothingToClaim);
+
+            T::Currency::transfer(&Self::account_id(), &who, amount, ExistenceRequirement::AllowDeath)?;
+            schedules.retain(|schedule| schedule.claimed < schedule.total);
+            if schedules.is_empty() {
+                Schedules::<T>::remove(&who);
+            } else {
+                Schedules::<T>::insert(&who, schedules);
+            }
+            Self::deposit_event(Event::VestedClaimed { user: who, amount });
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Account holding all locked rewards.
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account_truncating()
+        }
+
+        /// Amount `who` can claim right now.
+        pub fn claimable(who: &T::AccountId) -> BalanceOf<T> {
+            let now = <frame_system::Pallet<T>>::block_number();
+            Self::schedules(who)
+                .iter()
+                .fold(Zero::zero(), |acc: BalanceOf<T>, s| acc.saturating_add(s.claimable_at(now)))
+        }
+
+        /// Outstanding schedules of `who`, for the runtime API.
+        pub fn unlock_timeline(who: &T::AccountId) -> Vec<ScheduleOf<T>> {
+            Self::schedules(who).into_inner()
+        }
+    }
+
+    impl<T: Config> RewardVesting<T::AccountId, BalanceOf<T>> for Pallet<T> {
+        fn vest(source: &T::AccountId, who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            if amount.is_zero() {
+                return Ok(());
+            }
+            T::Currency::transfer(source, &Self::account_id(), amount, ExistenceRequirement::KeepAlive)?;
+
+            let now = <frame_system::Pallet<T>>::block_number();
+            let period = T::VestingPeriod::get();
+            let end = Schedules::<T>::mutate(who, |schedules| {
+                let schedule = VestingSchedule {
+                    total: amount,
+                    claimed: Zero::zero(),
+                    unlocked_at_start: Zero::zero(),
+                    start: now,
+                    end: now.saturating_add(period),
+                };
+                match schedules.try_push(schedule.clone()) {
+                    Ok(()) => schedule.end,
+                    // Out of slots: the newest schedule absorbs the reward
+                    Err(_) => match schedules.last_mut() {
+                        Some(newest) => {
+                            newest.merge(amount, now, period);
+                            newest.end
+                        }
+                        None => schedule.end,
+                    },
+                }
+            });
+            Self::deposit_event(Event::RewardVested { user: who.clone(), amount, end });
+            Ok(())
+        }
+    }
+
+    impl<T: Config> RewardClawback<T::AccountId, BalanceOf<T>> for Pallet<T> {
+        fn clawback(who: &T::AccountId, dest: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
+            let now = <frame_system::Pallet<T>>::block_number();
+            let mut schedules = Schedules::<T>::get(who);
+            let mut amount: BalanceOf<T> = Zero::zero();
+            for schedule in schedules.iter_mut() {
+                // Whatever has unlocked stays claimable, the rest is cancelled
+                let unlocked = schedule.unlocked_at(now);
+                amount = amount.saturating_add(schedule.total.saturating_sub(unlocked));
+                schedule.total = unlocked;
+                schedule.end = now;
+            }
+            if amount.is_zero() {
+                return Ok(amount);
+            }
+
+            T::Currency::transfer(&Self::account_id(), dest, amount, ExistenceRequirement::AllowDeath)?;
+            schedules.retain(|schedule| schedule.claimed < schedule.total);
+            if schedules.is_empty() {
+                Schedules::<T>::remove(who);
+            } else {
+                Schedules::<T>::insert(who, schedules);
+            }
+            Self::deposit_event(Event::RewardClawedBack { user: who.clone(), amount });
+            Ok(amount)
+        }
+    }
+}
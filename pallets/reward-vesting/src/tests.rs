@@ -0,0 +1,89 @@
+use crate::{mock::*, Error, Event, RewardClawback, RewardVesting, VestingSchedule};
+use frame_support::{assert_noop, assert_ok};
+
+#[test]
+fn rewards_unlock_linearly_and_are_claimed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 1_000));
+        assert_eq!(Balances::free_balance(RewardVesting::account_id()), 1_000);
+        assert_noop!(RewardVesting::vested_claim(RuntimeOrigin::signed(1)), Error::<Test>::NothingToClaim);
+
+        System::set_block_number(51);
+        assert_eq!(RewardVesting::claimable(&1), 500);
+        assert_ok!(RewardVesting::vested_claim(RuntimeOrigin::signed(1)));
+        System::assert_last_event(Event::VestedClaimed { user: 1, amount: 500 }.into());
+        assert_eq!(Balances::free_balance(1), 500);
+        assert_noop!(RewardVesting::vested_claim(RuntimeOrigin::signed(1)), Error::<Test>::NothingToClaim);
+
+        System::set_block_number(200);
+        assert_ok!(RewardVesting::vested_claim(RuntimeOrigin::signed(1)));
+        assert_eq!(Balances::free_balance(1), 1_000);
+        assert!(RewardVesting::schedules(1).is_empty());
+    });
+}
+
+#[test]
+fn merging_into_a_full_schedule_list_keeps_unlocked_funds() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 1_000));
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 100));
+
+        System::set_block_number(51);
+        assert_eq!(RewardVesting::claimable(&1), 550);
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 1_000));
+
+        // The 50 already unlocked stay unlocked, the other 1_050 unlock over a full period
+        let schedules = RewardVesting::schedules(1);
+        assert_eq!(schedules.len(), 2);
+        assert_eq!(
+            schedules[1],
+            VestingSchedule { total: 1_100, claimed: 0, unlocked_at_start: 50, start: 51, end: 151 }
+        );
+        System::assert_last_event(Event::RewardVested { user: 1, amount: 1_000, end: 151 }.into());
+        assert_eq!(RewardVesting::claimable(&1), 550);
+
+        System::set_block_number(151);
+        assert_ok!(RewardVesting::vested_claim(RuntimeOrigin::signed(1)));
+        assert_eq!(Balances::free_balance(1), 2_100);
+    });
+}
+
+#[test]
+fn a_merged_reward_never_unlocks_faster_than_the_vesting_period() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 1_000));
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 100));
+
+        // Merged into a fully unlocked schedule
+        System::set_block_number(301);
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 100));
+        assert_eq!(
+            RewardVesting::schedules(1)[1],
+            VestingSchedule { total: 200, claimed: 0, unlocked_at_start: 100, start: 301, end: 401 }
+        );
+        for elapsed in 0..=100 {
+            System::set_block_number(301 + elapsed);
+            assert_eq!(RewardVesting::claimable(&1), 1_100 + elapsed as u128);
+        }
+    });
+}
+
+#[test]
+fn clawback_recovers_only_locked_rewards() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(RewardVesting::vest(&SOURCE, &1, 1_000));
+
+        System::set_block_number(26);
+        assert_eq!(RewardVesting::clawback(&1, &TREASURY), Ok(750));
+        System::assert_last_event(Event::RewardClawedBack { user: 1, amount: 750 }.into());
+        assert_eq!(Balances::free_balance(TREASURY), 750);
+
+        // The unlocked part stays claimable, nothing more unlocks
+        System::set_block_number(80);
+        assert_eq!(RewardVesting::claimable(&1), 250);
+        assert_ok!(RewardVesting::vested_claim(RuntimeOrigin::signed(1)));
+        assert_eq!(Balances::free_balance(1), 250);
+        assert!(RewardVesting::schedules(1).is_empty());
+        assert_eq!(RewardVesting::clawback(&1, &TREASURY), Ok(0));
+    });
+}